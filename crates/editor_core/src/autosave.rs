@@ -0,0 +1,143 @@
+use std::{fs, path::Path};
+
+use bevy::{prelude::*, utils::Duration};
+use space_prefab::save::{
+    find_recovery_files, RecoveryFile, SaveConfig, SaveState, SceneFileFormat, ASSETS_DIR,
+};
+use space_shared::{EditorPrefabPath, EditorState, PrefabMarker};
+
+use crate::prelude::OpenScene;
+
+/// Periodically writes the open scene to the recovery folder and finds autosaves left by a crash
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.register_type::<AutosaveSettings>()
+            .init_resource::<AutosaveSettings>()
+            .init_resource::<AutosaveRecovery>();
+
+        app.add_systems(Startup, find_autosave_recovery);
+        app.add_systems(
+            Update,
+            autosave
+                .after(crate::EditorLoadSet)
+                .run_if(in_state(EditorState::Editor)),
+        );
+    }
+}
+
+/// Autosave settings, shown in the settings tab
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource, Default)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// Seconds between autosaves
+    pub interval: f32,
+    /// Skip autosave when the scene was not changed since the previous one
+    pub only_when_dirty: bool,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 120.,
+            only_when_dirty: true,
+        }
+    }
+}
+
+/// Autosaves found on startup which are newer than their scene files
+#[derive(Resource, Default, Clone, Debug)]
+pub struct AutosaveRecovery {
+    pub files: Vec<RecoveryFile>,
+    /// Autosave which is being loaded instead of its scene
+    pub restoring: Option<RecoveryFile>,
+}
+
+impl AutosaveRecovery {
+    /// Delete autosave file which should not be restored
+    pub fn discard(&mut self, file: &RecoveryFile) -> std::io::Result<()> {
+        self.files.retain(|f| f != file);
+        fs::remove_file(Path::new(ASSETS_DIR).join(&file.asset_path))
+    }
+}
+
+fn find_autosave_recovery(mut recovery: ResMut<AutosaveRecovery>) {
+    recovery.files = find_recovery_files(ASSETS_DIR);
+    for file in recovery.files.iter() {
+        info!("Found autosave {} newer than its scene", file.asset_path);
+    }
+}
+
+/// Write the open scene to its recovery file, the scene file is not touched
+fn autosave(world: &mut World) {
+    let Some(settings) = world.get_resource::<AutosaveSettings>().cloned() else {
+        return;
+    };
+    if !settings.enabled {
+        return;
+    }
+    // Autosaves from the previous session are not overwritten until user decides about them
+    if world
+        .get_resource::<AutosaveRecovery>()
+        .is_some_and(|recovery| !recovery.files.is_empty() || recovery.restoring.is_some())
+    {
+        return;
+    }
+    if world
+        .get_resource::<State<SaveState>>()
+        .is_some_and(|state| *state.get() == SaveState::Save)
+        || matches!(
+            world.get_resource::<NextState<SaveState>>(),
+            Some(NextState::Pending(_))
+        )
+    {
+        return;
+    }
+
+    let now = world.resource::<Time<Real>>().elapsed();
+    let interval = Duration::from_secs_f32(settings.interval.max(1.));
+    let mut open_scene = world.resource_mut::<OpenScene>();
+    if now.saturating_sub(open_scene.last_autosave) < interval {
+        return;
+    }
+    open_scene.last_autosave = now;
+    if settings.only_when_dirty && !open_scene.autosave_pending {
+        return;
+    }
+    open_scene.autosave_pending = false;
+    let path = open_scene.recovery_path();
+
+    if world
+        .query_filtered::<(), With<PrefabMarker>>()
+        .iter(world)
+        .next()
+        .is_none()
+    {
+        return;
+    }
+    if let Some(dir) = Path::new(&path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            error!("Failed to create autosave folder {}: {}", dir.display(), e);
+            return;
+        }
+    }
+    let Some(config) = world.get_resource::<SaveConfig>().cloned() else {
+        return;
+    };
+
+    info!("Autosaving scene to {}", path);
+    world.insert_resource(SaveConfig {
+        path: Some(EditorPrefabPath::File(path)),
+        format: SceneFileFormat::Ron,
+        backups: 0,
+        ..config.clone()
+    });
+    if let Err(e) = world.try_run_schedule(OnEnter(SaveState::Save)) {
+        error!("Failed to autosave scene: {}", e);
+    }
+    world.insert_resource(config);
+}
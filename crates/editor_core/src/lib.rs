@@ -1,23 +1,27 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+pub mod autosave;
 pub mod hotkeys;
 mod load;
+pub mod open_scene;
 pub mod selected;
 pub mod task_storage;
 pub mod toast;
 
 pub mod prelude {
-    pub use super::{hotkeys::*, load::*, selected::*, task_storage::*};
+    pub use super::{
+        autosave::*, hotkeys::*, load::*, open_scene::*, selected::*, task_storage::*,
+    };
     pub use crate::*;
     pub use space_undo;
 }
 
 pub mod gltf_unpack;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::Instant};
 
-use prelude::load_listener;
+use prelude::{clear_scene, load_listener, merge_listener, LoadCompleted};
 use space_prefab::save::{SaveConfig, SaveState};
 use space_shared::*;
 use space_undo::AppAutoUndo;
@@ -34,17 +38,20 @@ impl Plugin for EditorCore {
         app.add_plugins(space_persistence::PersistencePlugin);
 
         app.add_plugins(BackgroundTaskStoragePlugin);
+        app.add_plugins(open_scene::OpenScenePlugin);
+        app.add_plugins(autosave::AutosavePlugin);
 
         app.configure_sets(Update, EditorLoadSet.in_set(EditorSet::Editor));
 
         app.add_event::<EditorEvent>();
+        app.add_event::<LoadCompleted>();
 
         app.init_resource::<PrefabMemoryCache>();
         app.init_resource::<EditorLoader>();
 
         app.add_systems(
             Update,
-            (apply_deferred, load_listener)
+            (apply_deferred, load_listener, merge_listener)
                 .chain()
                 .in_set(EditorLoadSet),
         );
@@ -62,9 +69,21 @@ pub struct EditorLoadSet;
 #[derive(Resource, Default, Clone)]
 pub struct EditorLoader {
     pub scene: Option<Handle<DynamicScene>>,
+    /// When the load of `scene` was requested, used for [`LoadCompleted`] duration
+    pub requested_at: Option<Instant>,
+    /// Scenes requested by [`EditorEvent::Merge`] which are not loaded yet
+    pub merges: Vec<PendingMerge>,
+}
+
+/// Scene which is appended to the open scene after its asset is loaded
+#[derive(Clone)]
+pub struct PendingMerge {
+    pub request: MergeScene,
+    pub handle: Handle<DynamicScene>,
 }
 
 fn editor_event_listener(
+    mut commands: Commands,
     mut events: EventReader<EditorEvent>,
     mut load_server: ResMut<EditorLoader>,
     assets: Res<AssetServer>,
@@ -85,10 +104,12 @@ fn editor_event_listener(
                         handle.clone().untyped(),
                     ));
                     load_server.scene = Some(handle);
+                    load_server.requested_at = Some(Instant::now());
                     info!("Loading prefab by editor event from file {}", path);
                 }
                 EditorPrefabPath::MemoryCache => {
                     load_server.scene.clone_from(&cache.scene);
+                    load_server.requested_at = Some(Instant::now());
                     info!("Loading prefab by editor event from memory cache");
                 }
             },
@@ -97,6 +118,22 @@ fn editor_event_listener(
                 save_state.set(SaveState::Save);
                 info!("Saving scene to {:?}", path);
             }
+            EditorEvent::Merge(request) => {
+                let handle = assets.load(request.path.clone());
+                background_tasks.tasks.push(BackgroundTask::AssetLoading(
+                    request.path.clone(),
+                    handle.clone().untyped(),
+                ));
+                load_server.merges.push(PendingMerge {
+                    request: request.clone(),
+                    handle,
+                });
+                info!("Merging prefab from file {} into open scene", request.path);
+            }
+            EditorEvent::New => {
+                commands.add(clear_scene);
+                info!("Starting new scene");
+            }
             EditorEvent::StartGame => {
                 start_game_state.set(EditorState::GamePrepare);
             }
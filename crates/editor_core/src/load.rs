@@ -1,32 +1,135 @@
-use bevy::{ecs::entity::EntityHashMap, prelude::*};
+use std::sync::Arc;
+
+use bevy::{
+    asset::{AssetLoadError, LoadState},
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    scene::SceneSpawnError,
+    utils::{Duration, HashMap, HashSet, Instant},
+};
+use space_prefab::{
+    editor_registry::EditorRegistry,
+    merge::SceneMergeConflicts,
+    save::{ChildrenPrefab, SceneIoError},
+};
 use space_shared::{toast::ToastMessage, *};
+use space_undo::{AddedEntity, EditorChange, ManyChanges, NewChange, OneFrameUndoIgnore};
 
-use crate::EditorLoader;
+use crate::{prelude::OpenScene, EditorLoader};
 
-pub fn load_listener(world: &mut World) {
-    // AppTypeRegistry and are injected in Startup
+/// Sent when scene requested by [`EditorLoader`] is loaded into the editor world or failed to load
+#[derive(Event, Debug, Clone)]
+pub struct LoadCompleted {
+    /// `MemoryCache` for scenes without asset path
+    pub path: EditorPrefabPath,
+    /// Number of loaded entities
+    pub entities: usize,
+    /// Time since the load was requested
+    pub duration: Duration,
+    pub result: Result<(), SceneIoError>,
+}
+
+fn load_failed(world: &mut World, path: EditorPrefabPath, duration: Duration, error: SceneIoError) {
+    if let Some(mut editor_loader) = world.get_resource_mut::<EditorLoader>() {
+        editor_loader.scene = None;
+        editor_loader.requested_at = None;
+    }
+    error!("Failed to load scene {:?}: {}", path, error);
+    world.send_event(ToastMessage::new(
+        &format!("Failed to load scene:\n{error}"),
+        egui_toast::ToastKind::Error,
+    ));
+    world.send_event(LoadCompleted {
+        path,
+        entities: 0,
+        duration,
+        result: Err(error),
+    });
+}
+
+/// Despawn all scene entities and start tracking a new unsaved scene
+pub fn clear_scene(world: &mut World) {
+    let mut query = world.query_filtered::<Entity, With<PrefabMarker>>();
+    let entities = query.iter(world).collect::<Vec<_>>();
+    for entity in entities {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+    reset_scene_resources(world);
+    if let Some(mut open_scene) = world.get_resource_mut::<OpenScene>() {
+        open_scene.reset(None);
+    }
+}
+
+fn reset_scene_resources(world: &mut World) {
+    if let Some(registry) = world.get_resource::<EditorRegistry>().cloned() {
+        registry.reset_scene_resources(world);
+    }
+    // Conflicts belong to the merged file they were loaded from
+    world.remove_resource::<SceneMergeConflicts>();
+}
+
+/// Editable copy of loaded scene asset, `None` while the asset is still loading
+fn loaded_scene(
+    world: &World,
+    handle: &Handle<DynamicScene>,
+) -> Option<Result<DynamicScene, SceneIoError>> {
     let app_registry = world.resource::<AppTypeRegistry>().clone();
+    let assets = world.resource::<Assets<DynamicScene>>();
+    let Some(scene) = assets.get(handle) else {
+        if let Some(LoadState::Failed(err)) = world
+            .get_resource::<AssetServer>()
+            .and_then(|assets| assets.get_load_state(handle))
+        {
+            return Some(Err(match err.as_ref() {
+                AssetLoadError::AssetReaderError(e) => SceneIoError::Io(e.to_string()),
+                e => SceneIoError::Serialization(e.to_string()),
+            }));
+        }
+        return None;
+    };
+    let mut scene = match Scene::from_dynamic_scene(scene, &app_registry) {
+        Ok(scene) => scene,
+        Err(err) => {
+            return Some(Err(match err {
+                SceneSpawnError::UnregisteredComponent { type_path }
+                | SceneSpawnError::UnregisteredResource { type_path }
+                | SceneSpawnError::UnregisteredButReflectedType { type_path } => {
+                    SceneIoError::UnknownType(type_path)
+                }
+                SceneSpawnError::UnregisteredType { std_type_name } => {
+                    SceneIoError::UnknownType(std_type_name)
+                }
+                err => SceneIoError::Serialization(err.to_string()),
+            }));
+        }
+    };
+    scene.world.insert_resource(app_registry);
+    Some(Ok(DynamicScene::from_scene(&scene))) //kill me, is it clone() analog for DynamicScene
+}
+
+pub fn load_listener(world: &mut World) {
     let Some(load_server) = world.get_resource::<EditorLoader>().cloned() else {
         error!("Failed to get Editor Loader");
         return;
     };
-    let mut prefab;
-    {
-        let assets = world.resource::<Assets<DynamicScene>>();
-        if let Some(scene) = &load_server.scene {
-            if let Some(scene) = assets.get(scene) {
-                let Ok(mut scene) = Scene::from_dynamic_scene(scene, &app_registry) else {
-                    return;
-                };
-                scene.world.insert_resource(app_registry);
-                prefab = DynamicScene::from_scene(&scene); //kill me, is it clone() analog for DynamicScene
-            } else {
-                return;
-            }
-        } else {
+    let Some(handle) = &load_server.scene else {
+        return;
+    };
+    let requested_at = load_server.requested_at.unwrap_or_else(Instant::now);
+    let path = handle.path().map_or(EditorPrefabPath::MemoryCache, |path| {
+        EditorPrefabPath::File(path.to_string())
+    });
+
+    let mut prefab = match loaded_scene(world, handle) {
+        Some(Ok(prefab)) => prefab,
+        Some(Err(error)) => {
+            load_failed(world, path, requested_at.elapsed(), error);
             return;
         }
-    }
+        None => return,
+    };
     let Some(mut editor_loader) = world.get_resource_mut::<EditorLoader>() else {
         world.send_event(ToastMessage::new(
             "Failed to get prefab loader",
@@ -35,6 +138,7 @@ pub fn load_listener(world: &mut World) {
         return;
     };
     editor_loader.scene = None;
+    editor_loader.requested_at = None;
 
     let mut query = world.query_filtered::<(Entity, Option<&Name>), With<PrefabMarker>>();
     let mark_to_delete: Vec<_> = query
@@ -68,21 +172,187 @@ pub fn load_listener(world: &mut World) {
         entity.components.push(Box::new(PrefabMarker));
     }
 
+    // Scene settings missing in the file are not kept from the previous scene
+    reset_scene_resources(world);
     let mut map = EntityHashMap::default();
     let res = prefab.write_to_world(world, &mut map);
-    match res {
+    let result = match res {
         Ok(_) => {
             world.send_event(ToastMessage::new(
                 "Prefab loaded successfully",
                 egui_toast::ToastKind::Success,
             ));
+            Ok(())
         }
         Err(err) => {
             world.send_event(ToastMessage::new(
                 &format!("Failed to create scene:\n{err}"),
                 egui_toast::ToastKind::Error,
             ));
-            bevy::log::error!("{}", err)
+            bevy::log::error!("{}", err);
+            Err(SceneIoError::Serialization(err.to_string()))
+        }
+    };
+    world.send_event(LoadCompleted {
+        path,
+        entities: prefab.entities.len(),
+        duration: requested_at.elapsed(),
+        result,
+    });
+}
+
+/// Append scene to the world without touching the open scene and its settings.
+/// Root entities of the scene are attached to `parent` and moved by `offset`.
+/// Spawning of all merged entities is recorded as one undoable change. Returns the merged root entities.
+pub fn merge_scene(
+    world: &mut World,
+    mut scene: DynamicScene,
+    parent: Option<Entity>,
+    offset: Transform,
+) -> Result<Vec<Entity>, SceneIoError> {
+    scene.resources.clear();
+    for entity in &mut scene.entities {
+        entity.components.push(Box::new(PrefabMarker));
+    }
+    let mut map = EntityHashMap::default();
+    scene
+        .write_to_world(world, &mut map)
+        .map_err(|err| SceneIoError::Serialization(err.to_string()))?;
+
+    let merged = scene
+        .entities
+        .iter()
+        .filter_map(|entity| map.get(&entity.entity).copied())
+        .collect::<Vec<_>>();
+    // Hierarchy of the merged entities is restored from `ChildrenPrefab` later
+    let parents = merged
+        .iter()
+        .filter_map(|entity| Some((*entity, world.get::<ChildrenPrefab>(*entity)?)))
+        .flat_map(|(parent, children)| children.0.iter().map(move |child| (*child, parent)))
+        .collect::<HashMap<_, _>>();
+    let children = parents.keys().copied().collect::<HashSet<_>>();
+    let roots = merged
+        .iter()
+        .copied()
+        .filter(|entity| !children.contains(entity) && world.get::<Parent>(*entity).is_none())
+        .collect::<Vec<_>>();
+    let parent = parent.filter(|parent| world.get_entity(*parent).is_some());
+
+    for entity in merged.iter() {
+        // Components of merged entities are part of the spawn change
+        world
+            .entity_mut(*entity)
+            .insert(OneFrameUndoIgnore::default());
+    }
+    for root in roots.iter() {
+        if let Some(mut transform) = world.get_mut::<Transform>(*root) {
+            *transform = offset.mul_transform(*transform);
+        }
+        if let Some(parent) = parent {
+            world.entity_mut(parent).add_child(*root);
+        }
+    }
+
+    // Children are removed before their parents, so undo works before the hierarchy is restored
+    let depth = |mut entity: Entity| {
+        let mut depth = 0;
+        while let (Some(parent), true) = (parents.get(&entity), depth <= merged.len()) {
+            entity = *parent;
+            depth += 1;
+        }
+        depth
+    };
+    let mut added = merged.clone();
+    added.sort_by_cached_key(|entity| std::cmp::Reverse(depth(*entity)));
+    world.send_event(NewChange {
+        change: Arc::new(ManyChanges::new(
+            added
+                .into_iter()
+                .map(|entity| {
+                    Arc::new(AddedEntity { entity }) as Arc<dyn EditorChange + Send + Sync>
+                })
+                .collect(),
+        )),
+    });
+    Ok(roots)
+}
+
+/// Append scenes requested by [`EditorEvent::Merge`] after they are loaded
+pub fn merge_listener(world: &mut World) {
+    let Some(mut editor_loader) = world.get_resource_mut::<EditorLoader>() else {
+        return;
+    };
+    if editor_loader.merges.is_empty() {
+        return;
+    }
+    let merges = std::mem::take(&mut editor_loader.merges);
+    let mut pending = vec![];
+    for merge in merges {
+        let result = match loaded_scene(world, &merge.handle) {
+            Some(Ok(scene)) => {
+                merge_scene(world, scene, merge.request.parent, merge.request.offset)
+            }
+            Some(Err(error)) => Err(error),
+            None => {
+                pending.push(merge);
+                continue;
+            }
+        };
+        match result {
+            Ok(roots) => {
+                info!("Merged {} into open scene", merge.request.path);
+                world.send_event(ToastMessage::new(
+                    &format!(
+                        "Merged {} ({} root entities)",
+                        merge.request.path,
+                        roots.len()
+                    ),
+                    egui_toast::ToastKind::Success,
+                ));
+            }
+            Err(error) => {
+                error!("Failed to merge scene {}: {}", merge.request.path, error);
+                world.send_event(ToastMessage::new(
+                    &format!("Failed to merge scene {}:\n{error}", merge.request.path),
+                    egui_toast::ToastKind::Error,
+                ));
+            }
         }
     }
+    world.resource_mut::<EditorLoader>().merges.extend(pending);
+}
+
+#[cfg(test)]
+mod tests {
+    use space_undo::{ChangeChain, UndoPlugin, UndoRedo};
+
+    use super::*;
+
+    #[test]
+    fn one_undo_removes_whole_merge() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, UndoPlugin))
+            .register_type::<Name>()
+            .register_type::<ChildrenPrefab>()
+            .register_type::<PrefabMarker>();
+        app.update();
+
+        let mut source = World::new();
+        source.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        let child = source.spawn(Name::new("child")).id();
+        source.spawn((Name::new("root"), ChildrenPrefab(vec![child])));
+        source.spawn(Name::new("other root"));
+        let scene = DynamicScene::from_world(&source);
+
+        let roots = merge_scene(app.world_mut(), scene, None, Transform::default()).unwrap();
+        assert_eq!(roots.len(), 2);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<ChangeChain>().changes.len(), 1);
+
+        app.world_mut().send_event(UndoRedo::Undo);
+        app.update();
+        let mut names = app.world_mut().query::<&Name>();
+        assert_eq!(names.iter(app.world()).count(), 0);
+    }
 }
@@ -0,0 +1,177 @@
+use std::{fs, sync::Arc};
+
+use bevy::{prelude::*, utils::Duration};
+use space_prefab::{
+    load::PrefabLoader,
+    save::{
+        recovery_asset_path, ChildrenPrefab, SaveCompleted, SaveConfig, ASSETS_DIR, RECOVERY_DIR,
+    },
+};
+use space_shared::{EditorEvent, EditorPrefabPath};
+use space_undo::{ChangeChain, EditorChange, NewChange, UndoRedo, UndoSet};
+
+use crate::prelude::{AutosaveRecovery, LoadCompleted};
+
+/// Tracks the file and unsaved changes of the edited scene
+pub struct OpenScenePlugin;
+
+impl Plugin for OpenScenePlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenScene>();
+        // After the change chain is updated, so changes buffered during the load are in it
+        app.add_systems(
+            PostUpdate,
+            track_open_scene
+                .after(UndoSet::UpdateAll)
+                .before(UndoSet::Remapping),
+        );
+    }
+}
+
+type SavePoint = Option<Arc<dyn EditorChange + Send + Sync>>;
+
+/// Scene currently edited in the editor
+#[derive(Resource, Default, Clone)]
+pub struct OpenScene {
+    /// File the scene was loaded from or saved to, `None` for a new scene
+    pub path: Option<String>,
+    /// Scene has changes since the last save point in the change chain
+    pub dirty: bool,
+    /// Scene has changes which are not autosaved yet
+    pub autosave_pending: bool,
+    /// Last change of the change chain which is saved to `path`
+    save_point: SavePoint,
+    /// Last change of the change chain when save was requested
+    requested_save_point: Option<(String, SavePoint)>,
+    /// Scene was restored from autosave and differs from `path` regardless of the change chain
+    restored: bool,
+    /// Scene was just loaded or cleared, its changes are part of the load until the world settles
+    settling: bool,
+    pub(crate) last_autosave: Duration,
+}
+
+impl OpenScene {
+    /// Path of the autosave file of this scene
+    pub fn recovery_path(&self) -> String {
+        format!("{ASSETS_DIR}/{}", recovery_asset_path(self.path.as_deref()))
+    }
+
+    /// File name of the scene for titles, "Untitled" for a new scene
+    pub fn name(&self) -> String {
+        self.path
+            .as_deref()
+            .and_then(|path| {
+                path.replace('\\', "/")
+                    .rsplit('/')
+                    .next()
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| "Untitled".to_string())
+    }
+
+    /// Start tracking a freshly loaded or cleared scene
+    pub fn reset(&mut self, path: Option<String>) {
+        self.path = path;
+        self.dirty = false;
+        self.autosave_pending = false;
+        self.restored = false;
+        self.settling = true;
+    }
+}
+
+fn is_save_point(
+    top: Option<&Arc<dyn EditorChange + Send + Sync>>,
+    save_point: &SavePoint,
+) -> bool {
+    match (top, save_point) {
+        (None, None) => true,
+        (Some(top), Some(save_point)) => Arc::ptr_eq(top, save_point),
+        _ => false,
+    }
+}
+
+fn track_open_scene(
+    mut open_scene: ResMut<OpenScene>,
+    mut recovery: ResMut<AutosaveRecovery>,
+    change_chain: Res<ChangeChain>,
+    mut editor_events: EventReader<EditorEvent>,
+    mut loads: EventReader<LoadCompleted>,
+    mut saves: EventReader<SaveCompleted>,
+    mut changes: EventReader<NewChange>,
+    mut undo_redo: EventReader<UndoRedo>,
+    save_config: Option<Res<SaveConfig>>,
+    pending_children: Query<(), With<ChildrenPrefab>>,
+    pending_instances: Query<(), (With<PrefabLoader>, Without<Children>)>,
+) {
+    let top = change_chain.changes.last();
+
+    for event in editor_events.read() {
+        if let EditorEvent::Save(EditorPrefabPath::File(path)) = event {
+            // Saves report the written file, which has another extension for binary scenes
+            let file = save_config
+                .as_ref()
+                .map_or_else(|| path.clone(), |config| config.format.file_path(path));
+            open_scene.requested_save_point = Some((file, top.cloned()));
+        }
+    }
+
+    for event in loads.read() {
+        let EditorPrefabPath::File(path) = &event.path else {
+            continue;
+        };
+        let restored = if recovery
+            .restoring
+            .as_ref()
+            .is_some_and(|file| file.asset_path == *path)
+        {
+            recovery.restoring.take()
+        } else {
+            None
+        };
+        if event.result.is_err() {
+            continue;
+        }
+        if let Some(restored) = restored {
+            open_scene.reset(restored.scene);
+            open_scene.restored = true;
+        } else {
+            open_scene.reset(Some(format!("{ASSETS_DIR}/{path}")));
+        }
+    }
+
+    let recovery_prefix = format!("{ASSETS_DIR}/{RECOVERY_DIR}/");
+    for event in saves.read() {
+        let (Some(EditorPrefabPath::File(path)), Ok(())) = (&event.path, &event.result) else {
+            continue;
+        };
+        if path.replace('\\', "/").starts_with(&recovery_prefix) {
+            continue;
+        }
+        // Autosave of the previous location is stale after a real save
+        let _ = fs::remove_file(open_scene.recovery_path());
+        open_scene.path = Some(path.clone());
+        let _ = fs::remove_file(open_scene.recovery_path());
+        open_scene.save_point = match open_scene.requested_save_point.take() {
+            Some((requested, save_point)) if requested == *path => save_point,
+            _ => top.cloned(),
+        };
+        open_scene.restored = false;
+        open_scene.autosave_pending = false;
+    }
+
+    let changed = changes.read().count() + undo_redo.read().count() > 0;
+    if open_scene.settling {
+        // Hierarchy and prefab instances are rebuilt over the next frames, and changes
+        // recorded by that are committed to the chain in the first frame without new ones
+        let rebuilding = !pending_children.is_empty() || !pending_instances.is_empty();
+        open_scene.settling = changed || rebuilding;
+        open_scene.save_point = top.cloned();
+    } else if changed {
+        open_scene.autosave_pending = true;
+    }
+    let dirty = open_scene.restored || !is_save_point(top, &open_scene.save_point);
+    if open_scene.dirty != dirty {
+        open_scene.dirty = dirty;
+    }
+}
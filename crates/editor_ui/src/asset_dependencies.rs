@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::egui::{self, RichText};
+use space_editor_tabs::prelude::*;
+use space_prefab::{
+    asset_paths::{
+        asset_exists, entity_asset_references, find_moved_asset, relink_asset_reference,
+        AssetReference,
+    },
+    asset_rename::{plan_asset_rename, renamed_asset_path, AssetRename},
+    diff::short_type_path,
+    save::{configured_backups, ASSETS_DIR},
+};
+use space_shared::{
+    toast::{ToastKind, ToastMessage},
+    PrefabMarker,
+};
+
+use crate::{colors::*, editor_tab_name::EditorTabName};
+
+pub struct AssetDependenciesPlugin;
+
+impl Plugin for AssetDependenciesPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.editor_tab_by_trait(AssetDependenciesTab::default());
+    }
+}
+
+/// Asset paths used by the open scene, with relinking of missing assets
+/// and moving of asset files with all scenes referencing them
+#[derive(Resource, Default)]
+pub struct AssetDependenciesTab {
+    /// New paths typed for missing assets, by missing path
+    relink: HashMap<String, String>,
+    only_missing: bool,
+    move_from: String,
+    move_to: String,
+    /// Scene files changed by the move, shown before it is applied
+    preview: Option<Result<AssetRename, String>>,
+}
+
+impl EditorTab for AssetDependenciesTab {
+    fn ui(&mut self, ui: &mut egui::Ui, _commands: &mut Commands, world: &mut World) {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let entities = world
+            .query_filtered::<Entity, With<PrefabMarker>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        let mut paths = BTreeMap::<String, Vec<AssetReference>>::new();
+        for entity in entities {
+            for reference in entity_asset_references(world, entity, &registry) {
+                paths
+                    .entry(reference.path.clone())
+                    .or_default()
+                    .push(reference);
+            }
+        }
+        let missing = paths
+            .keys()
+            .filter(|path| !asset_exists(ASSETS_DIR, path))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.relink.retain(|path, _| missing.contains(path));
+
+        let mut relink = false;
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} assets used, {} missing",
+                paths.len(),
+                missing.len()
+            ));
+            ui.checkbox(&mut self.only_missing, "Only missing");
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!missing.is_empty(), egui::Button::new("Find by file name"))
+                .on_hover_text("Suggest files with the same name from the assets folder")
+                .clicked()
+            {
+                for path in missing.iter() {
+                    if let Some(found) = find_moved_asset(ASSETS_DIR, path) {
+                        self.relink.insert(path.clone(), found);
+                    }
+                }
+            }
+            let ready = self
+                .relink
+                .values()
+                .filter(|new_path| !new_path.is_empty())
+                .count();
+            if ui
+                .add_enabled(
+                    ready > 0,
+                    egui::Button::new(format!("Relink {ready} paths")),
+                )
+                .clicked()
+            {
+                relink = true;
+            }
+        });
+        let mut plan_move = false;
+        let mut apply_move = false;
+        egui::CollapsingHeader::new("Move asset")
+            .default_open(!self.move_from.is_empty())
+            .show(ui, |ui| {
+                let from = ui.add(
+                    egui::TextEdit::singleline(&mut self.move_from).hint_text("file or folder"),
+                );
+                let to =
+                    ui.add(egui::TextEdit::singleline(&mut self.move_to).hint_text("new path"));
+                if from.changed() || to.changed() {
+                    self.preview = None;
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !self.move_from.is_empty() && !self.move_to.is_empty(),
+                            egui::Button::new("Preview"),
+                        )
+                        .on_hover_text("List scene files that reference the asset")
+                        .clicked()
+                    {
+                        plan_move = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            matches!(self.preview, Some(Ok(_))),
+                            egui::Button::new("Move"),
+                        )
+                        .clicked()
+                    {
+                        apply_move = true;
+                    }
+                });
+                match &self.preview {
+                    Some(Ok(rename)) => {
+                        if !rename.moves_asset() {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} not found, only paths are updated",
+                                    rename.from
+                                ))
+                                .color(WARN_COLOR),
+                            );
+                        }
+                        ui.label(format!(
+                            "{} references in {} scene files",
+                            rename.references(),
+                            rename.scenes.len()
+                        ));
+                        for scene in rename.scenes.iter() {
+                            ui.label(format!("{} ({})", scene.file.display(), scene.references));
+                        }
+                        for (file, error) in rename.errors.iter() {
+                            ui.label(
+                                RichText::new(format!("Skipped {}: {error}", file.display()))
+                                    .color(ERROR_COLOR),
+                            );
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.label(RichText::new(e).color(ERROR_COLOR));
+                    }
+                    None => {}
+                }
+            });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("asset_dependencies")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (path, references) in paths.iter() {
+                        let is_missing = missing.contains(path);
+                        if self.only_missing && !is_missing {
+                            continue;
+                        }
+                        let users = references
+                            .iter()
+                            .map(|reference| {
+                                let entity = world.get::<Name>(reference.entity).map_or_else(
+                                    || format!("{}", reference.entity),
+                                    |name| name.to_string(),
+                                );
+                                format!(
+                                    "{entity}: {}.{}",
+                                    short_type_path(&reference.type_path),
+                                    reference.field
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        let text = if is_missing {
+                            RichText::new(path).color(ERROR_COLOR)
+                        } else {
+                            RichText::new(path)
+                        };
+                        ui.label(text).on_hover_text(users);
+                        ui.label(format!("{} uses", references.len()));
+                        if is_missing {
+                            let new_path = self.relink.entry(path.clone()).or_default();
+                            ui.add(egui::TextEdit::singleline(new_path).hint_text("new path"));
+                        } else if ui.small_button("Move").clicked() {
+                            let file = path.split('#').next().unwrap_or_default();
+                            self.move_from = file.to_string();
+                            self.move_to = file.to_string();
+                            self.preview = None;
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if relink {
+            // Changed components are recorded by auto undo as one change of this frame
+            let mut count = 0;
+            for (old_path, new_path) in self.relink.drain() {
+                if new_path.is_empty() {
+                    continue;
+                }
+                for reference in paths.get(&old_path).into_iter().flatten() {
+                    if relink_asset_reference(world, reference, &new_path, &registry) {
+                        count += 1;
+                    }
+                }
+            }
+            world.send_event(ToastMessage::new(
+                &format!("Relinked {count} asset references"),
+                ToastKind::Success,
+            ));
+        }
+
+        if apply_move {
+            if let Some(Ok(rename)) = self.preview.take() {
+                // Paths in the open scene are changed with undo, the files on disk are rewritten
+                for reference in paths.values().flatten() {
+                    if let Some(new_path) =
+                        renamed_asset_path(&reference.path, &rename.from, &rename.to)
+                    {
+                        relink_asset_reference(world, reference, &new_path, &registry);
+                    }
+                }
+                drop(registry);
+                match rename.apply(configured_backups(world)) {
+                    Ok(()) => {
+                        world.send_event(ToastMessage::new(
+                            &format!(
+                                "Moved {} to {}, updated {} scene files",
+                                rename.from,
+                                rename.to,
+                                rename.scenes.len()
+                            ),
+                            ToastKind::Success,
+                        ));
+                        self.move_from.clear();
+                        self.move_to.clear();
+                    }
+                    Err(e) => {
+                        world.send_event(ToastMessage::new(
+                            &format!("Failed to move {}: {e}", rename.from),
+                            ToastKind::Error,
+                        ));
+                    }
+                }
+            }
+        } else if plan_move {
+            drop(registry);
+            self.preview = Some(
+                plan_asset_rename(world, ASSETS_DIR, &self.move_from, &self.move_to)
+                    .map_err(|e| e.to_string()),
+            );
+        }
+    }
+
+    fn tab_name(&self) -> space_editor_tabs::tab_name::TabNameHolder {
+        EditorTabName::AssetDependencies.into()
+    }
+}
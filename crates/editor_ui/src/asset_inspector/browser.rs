@@ -0,0 +1,310 @@
+use std::{collections::BTreeSet, time::SystemTime};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::egui::{self, collapsing_header::CollapsingState};
+use space_editor_tabs::prelude::*;
+use space_prefab::{
+    component::GltfPrefab, import_settings::META_EXTENSION, load::PrefabBundle, save::ASSETS_DIR,
+};
+use space_shared::{EditorCameraMarker, PrefabMarker};
+
+use super::{AssetDetectorPlugin, DetectedAssets, EditorAsset};
+use crate::{editor_tab_name::EditorTabName, prelude::GameModeSettings};
+
+/// Extensions of images that can be used as textures
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "tga", "webp", "hdr", "exr", "dds", "ktx2",
+];
+/// Extensions of images shown as previews in the browser
+const PREVIEW_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp"];
+const PREVIEW_SIZE: f32 = 32.0;
+
+pub struct AssetBrowserPlugin;
+
+impl Plugin for AssetBrowserPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<AssetDetectorPlugin>() {
+            app.add_plugins(AssetDetectorPlugin);
+        }
+        app.editor_tab_by_trait(AssetBrowserTab::default());
+    }
+}
+
+/// Drag and drop payload with an asset path, dragged from the asset browser
+#[derive(Clone, Debug)]
+pub struct DraggedAsset {
+    /// Path relative to the assets folder
+    pub path: String,
+}
+
+impl DraggedAsset {
+    fn extension(&self) -> String {
+        self.path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default()
+    }
+
+    pub fn is_image(&self) -> bool {
+        IMAGE_EXTENSIONS.contains(&self.extension().as_str())
+    }
+
+    pub fn is_model(&self) -> bool {
+        matches!(self.extension().as_str(), "glb" | "gltf")
+    }
+
+    pub fn is_scene(&self) -> bool {
+        self.path.ends_with(".scn.ron")
+    }
+}
+
+/// Files of the assets folder by folder, with previews of images
+#[derive(Resource, Default)]
+pub struct AssetBrowserTab {
+    /// Selected folder relative to the assets folder, empty for the assets folder itself
+    folder: String,
+    extension: Option<String>,
+    search: String,
+    /// Modification time of previewed images, to reload them when the file changes
+    previews: HashMap<String, Option<SystemTime>>,
+}
+
+impl EditorTab for AssetBrowserTab {
+    fn ui(&mut self, ui: &mut egui::Ui, _commands: &mut Commands, world: &mut World) {
+        let mut refresh = false;
+        {
+            // Import settings are edited in the inspector instead of shown as files
+            let assets = world
+                .resource::<DetectedAssets>()
+                .assets
+                .iter()
+                .filter(|asset| asset.ext != META_EXTENSION)
+                .collect::<Vec<_>>();
+            let extensions = assets
+                .iter()
+                .map(|asset| asset.ext.to_lowercase())
+                .collect::<BTreeSet<_>>();
+            let folders = assets
+                .iter()
+                .flat_map(|asset| parent_folders(&asset.path))
+                .collect::<BTreeSet<_>>();
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.search)
+                        .hint_text("Search")
+                        .desired_width(120.),
+                );
+                egui::ComboBox::from_id_source("asset_browser_extension")
+                    .selected_text(self.extension.as_deref().unwrap_or("All files"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.extension, None, "All files");
+                        for ext in extensions.iter() {
+                            ui.selectable_value(
+                                &mut self.extension,
+                                Some(ext.clone()),
+                                ext.as_str(),
+                            );
+                        }
+                    });
+                if ui
+                    .button("⟳")
+                    .on_hover_text("Rescan assets folder")
+                    .clicked()
+                {
+                    refresh = true;
+                }
+            });
+            ui.separator();
+
+            ui.columns(2, |columns| {
+                egui::ScrollArea::vertical()
+                    .id_source("asset_browser_folders")
+                    .show(&mut columns[0], |ui| {
+                        ui.selectable_value(&mut self.folder, String::new(), ASSETS_DIR);
+                        ui.indent("asset_browser_root", |ui| {
+                            folder_tree(ui, &folders, "", &mut self.folder);
+                        });
+                    });
+
+                let search = self.search.to_lowercase();
+                let files = assets
+                    .iter()
+                    .copied()
+                    .filter(|asset| {
+                        let in_folder = if search.is_empty() {
+                            folder_of(&asset.path) == self.folder
+                        } else {
+                            file_name(&asset.path).to_lowercase().contains(&search)
+                        };
+                        in_folder
+                            && self
+                                .extension
+                                .as_ref()
+                                .map_or(true, |ext| asset.ext.to_lowercase() == *ext)
+                    })
+                    .collect::<Vec<_>>();
+                egui::ScrollArea::vertical()
+                    .id_source("asset_browser_files")
+                    .show(&mut columns[1], |ui| {
+                        for asset in files {
+                            self.file_ui(ui, asset);
+                        }
+                    });
+            });
+        }
+        if refresh {
+            world.resource_mut::<DetectedAssets>().rescan();
+        }
+    }
+
+    fn tab_name(&self) -> space_editor_tabs::tab_name::TabNameHolder {
+        EditorTabName::AssetBrowser.into()
+    }
+}
+
+impl AssetBrowserTab {
+    /// File row that can be dragged into the game view or onto asset fields in the inspector
+    fn file_ui(&mut self, ui: &mut egui::Ui, asset: &EditorAsset) {
+        let id = egui::Id::new(("asset_browser_file", &asset.path));
+        let payload = DraggedAsset {
+            path: asset.path.clone(),
+        };
+        let hint = if payload.is_model() || payload.is_scene() {
+            "Drag into the game view to spawn"
+        } else if payload.is_image() {
+            "Drag onto a texture field in the inspector"
+        } else {
+            ""
+        };
+        let ext = asset.ext.to_lowercase();
+        let response = ui
+            .dnd_drag_source(id, payload, |ui| {
+                ui.horizontal(|ui| {
+                    if PREVIEW_EXTENSIONS.contains(&ext.as_str()) {
+                        let uri = format!("file://{ASSETS_DIR}/{}", asset.path);
+                        // egui caches loaded images by uri
+                        if self.previews.get(&uri) != Some(&asset.modified) {
+                            ui.ctx().forget_image(&uri);
+                            self.previews.insert(uri.clone(), asset.modified);
+                        }
+                        ui.add(
+                            egui::Image::new(uri)
+                                .fit_to_exact_size(egui::vec2(PREVIEW_SIZE, PREVIEW_SIZE)),
+                        );
+                    } else {
+                        ui.add_sized(
+                            [PREVIEW_SIZE, PREVIEW_SIZE],
+                            egui::Label::new(file_icon(&ext)),
+                        );
+                    }
+                    ui.label(file_name(&asset.path));
+                });
+            })
+            .response;
+        if !hint.is_empty() {
+            response.on_hover_text(format!("{}\n{hint}", asset.path));
+        } else {
+            response.on_hover_text(asset.path.as_str());
+        }
+    }
+}
+
+/// Subfolders of `parent` as a tree of selectable labels
+fn folder_tree(ui: &mut egui::Ui, folders: &BTreeSet<String>, parent: &str, selected: &mut String) {
+    for folder in folders.iter().filter(|folder| folder_of(folder) == parent) {
+        let name = file_name(folder);
+        let has_children = folders.iter().any(|child| folder_of(child) == folder);
+        if has_children {
+            CollapsingState::load_with_default_open(
+                ui.ctx(),
+                ui.make_persistent_id(("asset_browser_folder", folder)),
+                selected == folder || selected.starts_with(&format!("{folder}/")),
+            )
+            .show_header(ui, |ui| {
+                ui.selectable_value(selected, folder.clone(), name);
+            })
+            .body(|ui| folder_tree(ui, folders, folder, selected));
+        } else {
+            ui.selectable_value(selected, folder.clone(), name);
+        }
+    }
+}
+
+/// Folder of the asset at `path`, empty for files in the assets folder itself
+fn folder_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(folder, _)| folder)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+/// `a`, `a/b` for the asset path `a/b/c.png`
+fn parent_folders(path: &str) -> Vec<String> {
+    let mut folders = vec![];
+    let mut folder = folder_of(path);
+    while !folder.is_empty() {
+        folders.push(folder.to_string());
+        folder = folder_of(folder);
+    }
+    folders
+}
+
+fn file_icon(ext: &str) -> &'static str {
+    match ext {
+        "glb" | "gltf" => "🎲",
+        "ron" | "scn" => "🎬",
+        "ogg" | "wav" | "mp3" | "flac" => "🎵",
+        "ttf" | "otf" => "🗛",
+        _ if IMAGE_EXTENSIONS.contains(&ext) => "🖼",
+        _ => "📄",
+    }
+}
+
+/// Point under `pointer` in the game view where dropped assets are placed:
+/// on the ground plane in 3D and on the `z = 0` plane in 2D
+pub fn drop_position(world: &mut World, viewport: egui::Rect, pointer: egui::Pos2) -> Vec3 {
+    let is_2d = world
+        .get_resource::<GameModeSettings>()
+        .is_some_and(GameModeSettings::is_2d);
+    let mut cameras =
+        world.query_filtered::<(&Camera, &GlobalTransform), With<EditorCameraMarker>>();
+    let Ok((camera, transform)) = cameras.get_single(world) else {
+        return Vec3::ZERO;
+    };
+    let position = Vec2::new(pointer.x - viewport.min.x, pointer.y - viewport.min.y);
+    let Some(ray) = camera.viewport_to_world(transform, position) else {
+        return Vec3::ZERO;
+    };
+    let normal = if is_2d { Vec3::Z } else { Vec3::Y };
+    // In front of the camera when the plane is not under the pointer
+    let distance = ray
+        .intersect_plane(Vec3::ZERO, InfinitePlane3d::new(normal))
+        .unwrap_or(5.0);
+    ray.get_point(distance)
+}
+
+/// Spawn a model as [`GltfPrefab`] or a scene as prefab at `position`.
+/// Returns `false` for other assets.
+pub fn spawn_dropped_asset(commands: &mut Commands, asset: &DraggedAsset, position: Vec3) -> bool {
+    let transform = Transform::from_translation(position);
+    if asset.is_model() {
+        commands.spawn((
+            SpatialBundle::from_transform(transform),
+            GltfPrefab {
+                path: asset.path.clone(),
+                scene: "Scene0".into(),
+            },
+            PrefabMarker,
+        ));
+    } else if asset.is_scene() {
+        commands
+            .spawn((PrefabBundle::new(&asset.path), PrefabMarker))
+            .insert(transform);
+    } else {
+        return false;
+    }
+    true
+}
@@ -1,50 +1,94 @@
-use bevy::prelude::*;
-use std::fs;
-use std::path::Path;
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+    time::common_conditions::on_timer,
+};
+use space_prefab::{asset_paths::scan_asset_files, save::ASSETS_DIR};
+use std::time::{Duration, SystemTime};
 
-///Not used right now. Planned to be UI inspector for all assets in asset/ folder
+/// Asset browser tab with drag and drop of assets into the scene
+pub mod browser;
+
+/// File found in the assets folder
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EditorAsset {
+    /// Path relative to the assets folder with `/` separators, as used by the asset server
     pub path: String,
     pub ext: String,
+    pub modified: Option<SystemTime>,
 }
 
-///Not used right now. Planned to be UI inspector for all assets in asset/ folder
+/// Files of the assets folder, scanned on startup and refreshed when the folder changes
 #[derive(Resource, Default)]
 pub struct DetectedAssets {
     pub assets: Vec<EditorAsset>,
 }
 
-///Not used right now. Planned to be UI inspector for all assets in asset/ folder
+impl DetectedAssets {
+    /// Scan the assets folder again
+    pub fn rescan(&mut self) {
+        self.assets = scan_assets();
+    }
+}
+
+/// Fills [`DetectedAssets`] with files of the assets folder
 pub struct AssetDetectorPlugin;
 
 impl Plugin for AssetDetectorPlugin {
     #[cfg(not(tarpaulin_include))]
     fn build(&self, app: &mut App) {
         app.init_resource::<DetectedAssets>();
+        app.init_resource::<AssetScan>();
 
         app.add_systems(Startup, detect_assets.before(crate::ui_camera_block));
+        app.add_systems(
+            Update,
+            (
+                start_asset_scan.run_if(on_timer(Duration::from_secs(2))),
+                refresh_detected_assets,
+            )
+                .chain(),
+        );
     }
 }
 
+/// Files with an extension in the assets folder
+fn scan_assets() -> Vec<EditorAsset> {
+    scan_asset_files(ASSETS_DIR)
+        .into_iter()
+        .filter(|file| !file.extension().is_empty())
+        .map(|file| EditorAsset {
+            ext: file.extension().to_string(),
+            path: file.path,
+            modified: file.modified,
+        })
+        .collect()
+}
+
 fn detect_assets(mut assets: ResMut<DetectedAssets>) {
-    get_assets_in_directory(Path::new("assets"), &mut assets.assets);
-}
-
-fn get_assets_in_directory(dir_path: &Path, assets: &mut Vec<EditorAsset>) {
-    if let Ok(entries) = fs::read_dir(dir_path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    let editor_asset = EditorAsset {
-                        path: path.to_str().unwrap().to_string(),
-                        ext: ext.to_str().unwrap().to_string(),
-                    };
-                    assets.push(editor_asset);
-                }
-            } else if path.is_dir() {
-                get_assets_in_directory(&path, assets);
-            }
-        }
+    assets.rescan();
+}
+
+/// Rescan of the assets folder running in the background
+#[derive(Resource, Default)]
+struct AssetScan(Option<Task<Vec<EditorAsset>>>);
+
+fn start_asset_scan(mut scan: ResMut<AssetScan>) {
+    if scan.0.is_none() {
+        scan.0 = Some(IoTaskPool::get().spawn(async { scan_assets() }));
+    }
+}
+
+/// Take the finished rescan, the resource is only changed when files were added, removed or modified
+fn refresh_detected_assets(mut scan: ResMut<AssetScan>, mut assets: ResMut<DetectedAssets>) {
+    let Some(task) = scan.0.as_mut() else {
+        return;
+    };
+    let Some(scanned) = block_on(future::poll_once(task)) else {
+        return;
+    };
+    scan.0 = None;
+    if assets.assets != scanned {
+        assets.assets = scanned;
     }
 }
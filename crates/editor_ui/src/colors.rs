@@ -12,4 +12,5 @@ pub const ERROR_COLOR: Color32 = Color32::from_rgb(255, 59, 33);
 pub const HYPERLINK_COLOR: Color32 = Color32::from_rgb(99, 235, 231);
 pub const WARN_COLOR: Color32 = Color32::from_rgb(225, 206, 67);
 pub const SELECTED_ITEM_COLOR: Color32 = Color32::from_rgb(76, 93, 235);
+pub const OVERRIDE_COLOR: Color32 = Color32::from_rgb(66, 165, 245);
 pub const TEXT_COLOR: Color32 = Color32::WHITE;
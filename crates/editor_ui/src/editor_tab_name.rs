@@ -12,6 +12,10 @@ pub enum EditorTabName {
     Settings,
     ToolBox,
     ChangeChain,
+    SceneDiff,
+    AssetDependencies,
+    UnusedAssets,
+    AssetBrowser,
     DebugWorldInspector,
 }
 
@@ -32,6 +36,10 @@ impl TabName for EditorTabName {
             Self::Settings => "Settings".to_string(),
             Self::ToolBox => "Tool Box".to_string(),
             Self::ChangeChain => "Change Chain".to_string(),
+            Self::SceneDiff => "Scene Diff".to_string(),
+            Self::AssetDependencies => "Asset Dependencies".to_string(),
+            Self::UnusedAssets => "Unused Assets".to_string(),
+            Self::AssetBrowser => "Asset Browser".to_string(),
             Self::DebugWorldInspector => "Debug World Inspector".to_string(),
         }
     }
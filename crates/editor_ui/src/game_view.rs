@@ -5,7 +5,10 @@ use transform_gizmo_egui::GizmoMode;
 
 use space_shared::*;
 
-use crate::editor_tab_name::EditorTabName;
+use crate::{
+    asset_inspector::browser::{drop_position, spawn_dropped_asset, DraggedAsset},
+    editor_tab_name::EditorTabName,
+};
 
 use super::tool::EditorTool;
 use space_editor_tabs::prelude::*;
@@ -104,6 +107,30 @@ impl EditorTab for GameViewTab {
                 warn_if_debug_build(ui);
             }
         });
+
+        // Models and scenes dragged from the asset browser are spawned where they are dropped
+        let drop_area = ui.interact(
+            ui.clip_rect(),
+            ui.id().with("asset_drop"),
+            egui::Sense::hover(),
+        );
+        if drop_area
+            .dnd_hover_payload::<DraggedAsset>()
+            .is_some_and(|asset| asset.is_model() || asset.is_scene())
+        {
+            ui.painter().rect_stroke(
+                ui.clip_rect().shrink(1.),
+                0.,
+                egui::Stroke::new(2., SELECTED_ITEM_COLOR),
+            );
+        }
+        if let (Some(asset), Some(pointer)) = (
+            drop_area.dnd_release_payload::<DraggedAsset>(),
+            ui.ctx().pointer_latest_pos(),
+        ) {
+            let position = drop_position(world, ui.clip_rect(), pointer);
+            spawn_dropped_asset(commands, &asset, position);
+        }
     }
 
     fn tab_name(&self) -> space_editor_tabs::tab_name::TabNameHolder {
@@ -7,10 +7,20 @@ use bevy_egui::{
     *,
 };
 use space_editor_core::prelude::*;
-use space_prefab::{component::SceneAutoChild, editor_registry::EditorRegistry};
+use space_prefab::{
+    component::SceneAutoChild,
+    editor_registry::EditorRegistry,
+    load::PrefabLoader,
+    pack::{create_prefab_from_entities, file_asset_path, unpack_prefab_instance},
+    save::ASSETS_DIR,
+};
 use space_undo::{AddedEntity, NewChange, RemovedEntity, UndoSet};
 
-use space_shared::*;
+use space_shared::{
+    ext::egui_file,
+    toast::{ToastKind, ToastMessage},
+    *,
+};
 
 use space_editor_tabs::prelude::*;
 
@@ -49,6 +59,9 @@ pub struct HierarchyTabState {
     pub show_editor_entities: bool,
     pub show_spawnable_bundles: bool,
     pub entity_filter: String,
+    pub create_prefab_dialog: Option<egui_file::FileDialog>,
+    /// Entities which will be saved to prefab chosen in `create_prefab_dialog`
+    pub create_prefab_entities: Vec<Entity>,
 }
 
 pub type HierarchyQueryIter<'a> = (
@@ -69,6 +82,7 @@ pub fn show_hierarchy(
     mut changes: EventWriter<NewChange>,
     mut state: ResMut<HierarchyTabState>,
     auto_children: Query<(), With<SceneAutoChild>>,
+    instances: Query<(), With<PrefabLoader>>,
 ) {
     let mut all: Vec<_> = if state.show_editor_entities {
         all_entities.iter().collect()
@@ -94,6 +108,7 @@ pub fn show_hierarchy(
     });
     ui.spacing();
     let lower_filter = state.entity_filter.to_lowercase();
+    let mut create_prefab = None;
 
     egui::ScrollArea::vertical().show(ui, |ui| {
         for (entity, _name, _children, parent) in all.iter().filter(|(_, name, _, _)| {
@@ -112,6 +127,8 @@ pub fn show_hierarchy(
                         &mut clone_events,
                         &mut changes,
                         &auto_children,
+                        &instances,
+                        &mut create_prefab,
                     );
                 } else {
                     draw_entity::<With<PrefabMarker>>(
@@ -123,11 +140,61 @@ pub fn show_hierarchy(
                         &mut clone_events,
                         &mut changes,
                         &auto_children,
+                        &instances,
+                        &mut create_prefab,
                     );
                 }
             }
         }
     });
+
+    if let Some(entities) = create_prefab {
+        let mut dialog = egui_file::FileDialog::save_file(Some("./assets".into()))
+            .default_filename("Prefab.scn.ron")
+            .title("Create Prefab");
+        dialog.open();
+        state.create_prefab_dialog = Some(dialog);
+        state.create_prefab_entities = entities;
+    }
+    let mut dialog_selected = false;
+    if let Some(dialog) = &mut state.create_prefab_dialog {
+        if dialog.show(ui.ctx()).selected() {
+            dialog_selected = true;
+            if let Some(file) = dialog.path() {
+                let entities = std::mem::take(&mut state.create_prefab_entities);
+                // Prefabs are loaded by asset path, so files outside of assets could never be spawned
+                match file_asset_path(file) {
+                    Some(mut path) => {
+                        if !path.ends_with(".scn.ron") && !path.ends_with(".scn.bin") {
+                            path.push_str(".scn.ron");
+                        }
+                        commands.add(move |world: &mut World| {
+                            if let Err(err) = create_prefab_from_entities(world, &entities, &path) {
+                                error!("Failed to create prefab: {err}");
+                                world.send_event(ToastMessage::new(
+                                    &format!("Failed to create prefab: {err}"),
+                                    ToastKind::Error,
+                                ));
+                            }
+                        });
+                    }
+                    None => {
+                        let message = format!(
+                            "Prefab must be saved inside the {ASSETS_DIR} folder: {}",
+                            file.display()
+                        );
+                        error!("{message}");
+                        commands.add(move |world: &mut World| {
+                            world.send_event(ToastMessage::new(&message, ToastKind::Error));
+                        });
+                    }
+                }
+            }
+        }
+    }
+    if dialog_selected {
+        state.create_prefab_dialog = None;
+    }
 }
 
 type DrawIter<'a> = (
@@ -146,6 +213,8 @@ fn draw_entity<F: QueryFilter>(
     clone_events: &mut EventWriter<CloneEvent>,
     changes: &mut EventWriter<NewChange>,
     auto_children: &Query<(), With<SceneAutoChild>>,
+    instances: &Query<(), With<PrefabLoader>>,
+    create_prefab: &mut Option<Vec<Entity>>,
 ) {
     let Ok((_, name, children, parent)) = query.get(entity) else {
         return;
@@ -190,6 +259,8 @@ fn draw_entity<F: QueryFilter>(
                         clone_events,
                         selected,
                         parent,
+                        instances.contains(entity),
+                        create_prefab,
                     );
                 });
             }
@@ -223,6 +294,8 @@ fn draw_entity<F: QueryFilter>(
                     clone_events,
                     changes,
                     auto_children,
+                    instances,
+                    create_prefab,
                 );
             }
         });
@@ -253,6 +326,8 @@ fn draw_entity<F: QueryFilter>(
                     clone_events,
                     selected,
                     parent,
+                    instances.contains(entity),
+                    create_prefab,
                 );
             });
         }
@@ -284,6 +359,8 @@ fn hierarchy_entity_context(
     clone_events: &mut EventWriter<'_, CloneEvent>,
     selected: &mut Query<'_, '_, Entity, With<Selected>>,
     parent: Option<&Parent>,
+    is_instance: bool,
+    create_prefab: &mut Option<Vec<Entity>>,
 ) {
     if ui.button("Add child").clicked() {
         let new_id = commands.spawn_empty().insert(PrefabMarker).id();
@@ -312,6 +389,27 @@ fn hierarchy_entity_context(
     if parent.is_some() && ui.button("Detach").clicked() {
         commands.entity(entity).remove_parent();
     }
+    if ui.button("Create prefab").clicked() {
+        // Whole selection is saved if the clicked entity is selected
+        let mut entities = selected.iter().collect::<Vec<_>>();
+        if !entities.contains(&entity) {
+            entities = vec![entity];
+        }
+        *create_prefab = Some(entities);
+        ui.close_menu();
+    }
+    if is_instance && ui.button("Unpack prefab").clicked() {
+        commands.add(move |world: &mut World| {
+            if let Err(err) = unpack_prefab_instance(world, entity) {
+                error!("Failed to unpack prefab: {err}");
+                world.send_event(ToastMessage::new(
+                    &format!("Failed to unpack prefab: {err}"),
+                    ToastKind::Error,
+                ));
+            }
+        });
+        ui.close_menu();
+    }
 }
 
 #[derive(Component)]
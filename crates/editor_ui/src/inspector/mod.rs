@@ -4,7 +4,7 @@ pub mod refl_impl;
 pub mod resources;
 pub mod runtime_assets;
 
-use std::any::TypeId;
+use std::{any::TypeId, collections::BTreeSet, io};
 
 use bevy::{
     ecs::{change_detection::MutUntyped, world::CommandQueue},
@@ -17,7 +17,16 @@ use bevy::{
 use bevy_egui::{egui::TextEdit, *};
 
 use space_editor_core::prelude::*;
-use space_prefab::{component::EntityLink, editor_registry::EditorRegistry};
+use space_prefab::{
+    asset_paths::{entity_asset_references, set_asset_path, ReflectAssetPaths},
+    component::{EntityLink, GuidLink},
+    diff::short_type_path,
+    editor_registry::EditorRegistry,
+    format::unknown::UnknownComponents,
+    import_settings::{meta_path, AssetImportSettings},
+    overrides::{apply_override_to_prefab, entity_overrides, revert_override, PrefabOverride},
+    save::ASSETS_DIR,
+};
 use space_shared::{
     ext::bevy_inspector_egui::{
         inspector_egui_impls::InspectorEguiImpl, reflect_inspector::InspectorUi,
@@ -25,13 +34,19 @@ use space_shared::{
     toast::{ToastKind, ToastMessage},
 };
 
-use crate::{editor_tab_name::EditorTabName, icons::add_component_icon};
+use crate::{
+    asset_inspector::browser::DraggedAsset, editor_tab_name::EditorTabName,
+    icons::add_component_icon,
+};
 use space_editor_tabs::prelude::*;
 
 use self::{
     components_order::{ComponentsOrder, ComponentsPriority},
     events_dispatcher::EventDispatcherTab,
-    refl_impl::{entity_ref_ui, entity_ref_ui_readonly, many_unimplemented},
+    refl_impl::{
+        entity_ref_ui, entity_ref_ui_readonly, guid_link_ui, guid_link_ui_readonly,
+        many_unimplemented,
+    },
     resources::ResourceTab,
     runtime_assets::RuntimeAssetsTab,
 };
@@ -68,6 +83,8 @@ impl Plugin for SpaceInspectorPlugin {
 pub struct InspectorTab {
     open_components: HashMap<String, bool>,
     show_all_components: bool,
+    /// Edited import settings of assets used by the selected entity, by asset path
+    import_settings: HashMap<String, AssetImportSettings>,
 }
 
 impl EditorTab for InspectorTab {
@@ -122,6 +139,19 @@ impl EditorTab for InspectorTab {
             priority_a.cmp(priority_b).then(name_a.cmp(name_b))
         });
 
+        // Differences from the source prefab, if the entity is a part of spawned prefab
+        let overrides = entity_overrides(world, selected_entity);
+        // Files used by the entity, images and models among them have import settings
+        let used_assets = entity_asset_references(world, selected_entity, &app_registry)
+            .into_iter()
+            .map(|reference| {
+                let file = reference.path.split('#').next().unwrap_or_default();
+                file.to_string()
+            })
+            .filter(|path| !path.is_empty())
+            .collect::<BTreeSet<_>>();
+        let mut saved_settings = vec![];
+
         let cell = world.as_unsafe_world_cell();
         let Some(mut state) = (unsafe { cell.get_resource_mut::<InspectState>() }) else {
             error!("Failed to load inspect state");
@@ -212,6 +242,15 @@ impl EditorTab for InspectorTab {
                                     let (ptr, mut set_changed) = mut_untyped_split(data);
 
                                     let value = unsafe { reflect_from_ptr.from_ptr_mut()(ptr) };
+                                    let type_path = registration.type_info().type_path();
+                                    let component_overrides = overrides
+                                        .iter()
+                                        .filter(|o| o.component == type_path)
+                                        .collect::<Vec<_>>();
+                                    let asset_fields = registration
+                                        .data::<ReflectAssetPaths>()
+                                        .map(|data| data.fields.as_slice())
+                                        .unwrap_or_default();
 
                                     if is_editor_component {
                                         if !editor_registry_resource
@@ -225,6 +264,9 @@ impl EditorTab for InspectorTab {
                                                 &mut env,
                                                 value,
                                                 &mut set_changed,
+                                                &component_overrides,
+                                                asset_fields,
+                                                &mut commands,
                                             );
 
                                             ui.push_id(
@@ -258,6 +300,9 @@ impl EditorTab for InspectorTab {
                                             &mut env,
                                             value,
                                             &mut set_changed,
+                                            &component_overrides,
+                                            asset_fields,
+                                            &mut commands,
                                         );
                                         ui.end_row();
                                     }
@@ -267,6 +312,13 @@ impl EditorTab for InspectorTab {
                     }
                 });
 
+                if let Some(unknown) = unsafe { e.get::<UnknownComponents>() } {
+                    show_unknown_components(ui, e.id(), unknown);
+                }
+                show_removed_components(ui, e.id(), &overrides, &mut commands);
+
+                self.show_import_settings(ui, &used_assets, &mut env, &mut saved_settings);
+
                 ui.separator();
             }
         });
@@ -358,6 +410,18 @@ impl EditorTab for InspectorTab {
 
         state.commands = commands;
 
+        for (path, result) in saved_settings {
+            match result {
+                Ok(()) => {
+                    world.resource::<AssetServer>().reload(path);
+                }
+                Err(e) => world.send_event(ToastMessage::new(
+                    &format!("Failed to save import settings of {path}: {e}"),
+                    ToastKind::Error,
+                )),
+            };
+        }
+
         if let (Some(mut editor_camera_enabled), true) = (
             world.get_resource_mut::<crate::EditorCameraEnabled>(),
             disable_pan_orbit,
@@ -374,6 +438,7 @@ impl EditorTab for InspectorTab {
 }
 
 impl InspectorTab {
+    #[allow(clippy::too_many_arguments)]
     fn show_component(
         &mut self,
         ui: &mut egui::Ui,
@@ -382,12 +447,22 @@ impl InspectorTab {
         env: &mut InspectorUi<'_, '_>,
         value: &mut dyn Reflect,
         set_changed: &mut impl FnMut(),
+        overrides: &[&PrefabOverride],
+        asset_fields: &[&'static str],
+        commands: &mut Vec<InspectCommand>,
     ) {
         ui.push_id(format!("{:?}-{}", &e.id(), &name), |ui| {
             let default = name.to_lowercase() == *"transform";
-            let header = egui::CollapsingHeader::new(name)
+            let title = if overrides.is_empty() {
+                egui::RichText::new(name)
+            } else {
+                egui::RichText::new(format!("{name} ●")).color(OVERRIDE_COLOR)
+            };
+            let header = egui::CollapsingHeader::new(title)
+                .id_source(name)
                 .default_open(*self.open_components.get(name).unwrap_or(&default))
                 .show(ui, |ui| {
+                    show_overrides(ui, e.id(), overrides, commands);
                     ui.push_id(format!("content-{:?}-{}", &e.id(), &name), |ui| {
                         if env.ui_for_reflect_with_options(value, ui, ui.id(), &()) {
                             (set_changed)();
@@ -399,6 +474,179 @@ impl InspectorTab {
                 //At click header not opened simultaneously so its need to check percent of opened
                 *open_name = header.openness < 0.5;
             }
+            // Assets dragged from the asset browser can be dropped on asset path fields
+            if !asset_fields.is_empty()
+                && egui::DragAndDrop::has_payload_of_type::<DraggedAsset>(ui.ctx())
+            {
+                for field in asset_fields {
+                    let (_, dropped) = ui.dnd_drop_zone::<DraggedAsset, _>(
+                        egui::Frame::default().inner_margin(4.),
+                        |ui| ui.label(format!("Drop on {field}")),
+                    );
+                    if let Some(asset) = dropped {
+                        if set_asset_path(value, field, &asset.path) {
+                            (set_changed)();
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Import settings of images and models used by the entity, saved to `.meta` files next to them
+    fn show_import_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        used_assets: &BTreeSet<String>,
+        env: &mut InspectorUi<'_, '_>,
+        saved: &mut Vec<(String, io::Result<()>)>,
+    ) {
+        for path in used_assets {
+            if !self.import_settings.contains_key(path) {
+                if let Some(settings) = AssetImportSettings::load(ASSETS_DIR, path) {
+                    self.import_settings.insert(path.clone(), settings);
+                }
+            }
+        }
+        let paths = used_assets
+            .iter()
+            .filter(|path| self.import_settings.contains_key(*path))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+
+        egui::CollapsingHeader::new("Import settings").show(ui, |ui| {
+            for path in paths {
+                let mut revert = false;
+                ui.push_id(("import_settings", path), |ui| {
+                    let Some(settings) = self.import_settings.get_mut(path) else {
+                        return;
+                    };
+                    ui.label(path.as_str());
+                    match settings {
+                        AssetImportSettings::Image(settings) => {
+                            env.ui_for_reflect_with_options(settings, ui, ui.id(), &());
+                            if settings.needs_explicit_filter() {
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    "Choose Nearest or Linear filter to change the wrap",
+                                );
+                            }
+                        }
+                        AssetImportSettings::Gltf(settings) => {
+                            env.ui_for_reflect_with_options(settings, ui, ui.id(), &());
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Apply")
+                            .on_hover_text(format!(
+                                "Save to {} and reload the asset",
+                                meta_path(ASSETS_DIR, path).display()
+                            ))
+                            .clicked()
+                        {
+                            saved.push((path.clone(), settings.save(ASSETS_DIR, path)));
+                        }
+                        if ui
+                            .button("Revert")
+                            .on_hover_text("Discard changes that are not applied")
+                            .clicked()
+                        {
+                            revert = true;
+                        }
+                    });
+                });
+                if revert {
+                    self.import_settings.remove(path);
+                }
+            }
+        });
+    }
+}
+
+/// List of values that differ from the source prefab, with buttons to revert or apply them
+fn show_overrides(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    overrides: &[&PrefabOverride],
+    commands: &mut Vec<InspectCommand>,
+) {
+    for value in overrides {
+        ui.horizontal(|ui| {
+            let field = if value.is_removed() {
+                "removed"
+            } else if value.field.is_empty() {
+                "whole component"
+            } else {
+                value.field.as_str()
+            };
+            ui.label(egui::RichText::new(format!("● {field}")).color(OVERRIDE_COLOR))
+                .on_hover_text("Overridden on this prefab instance");
+            if ui
+                .small_button("↺")
+                .on_hover_text("Revert to prefab")
+                .clicked()
+            {
+                commands.push(InspectCommand::RevertOverride(
+                    entity,
+                    value.component.clone(),
+                    value.field.clone(),
+                ));
+            }
+            if ui
+                .small_button("⤴")
+                .on_hover_text("Apply to prefab")
+                .clicked()
+            {
+                commands.push(InspectCommand::ApplyOverride(
+                    entity,
+                    value.component.clone(),
+                    value.field.clone(),
+                ));
+            }
+        });
+    }
+}
+
+/// Components of the source prefab which were removed from the spawned entity
+fn show_removed_components(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    overrides: &[PrefabOverride],
+    commands: &mut Vec<InspectCommand>,
+) {
+    let removed = overrides
+        .iter()
+        .filter(|value| value.is_removed())
+        .collect::<Vec<_>>();
+    if removed.is_empty() {
+        return;
+    }
+    ui.label("Removed prefab components:");
+    for value in removed {
+        ui.push_id(format!("removed-{:?}-{}", entity, value.component), |ui| {
+            ui.label(short_type_path(&value.component));
+            show_overrides(ui, entity, &[value], commands);
+        });
+    }
+}
+
+/// Read-only view of components which types are not registered in this build
+fn show_unknown_components(ui: &mut egui::Ui, entity: Entity, unknown: &UnknownComponents) {
+    if unknown.0.is_empty() {
+        return;
+    }
+    ui.label("Unknown components:");
+    for component in unknown.0.iter() {
+        ui.push_id(format!("unknown-{:?}-{}", entity, component.type_path), |ui| {
+            egui::CollapsingHeader::new(format!("❓ {}", component.type_path))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(&component.ron).monospace());
+                })
+                .header_response
+                .on_hover_text("Type is not registered in this build. Component is read-only and saved unchanged");
         });
     }
 }
@@ -413,6 +661,14 @@ fn register_custom_impls(registry: Res<AppTypeRegistry>) {
             entity_ref_ui_readonly,
             many_unimplemented::<EntityRef>,
         ));
+    registry
+        .get_mut(TypeId::of::<GuidLink>())
+        .unwrap_or_else(|| panic!("{} not registered", std::any::type_name::<GuidLink>()))
+        .insert(InspectorEguiImpl::new(
+            guid_link_ui,
+            guid_link_ui_readonly,
+            many_unimplemented::<GuidLink>,
+        ));
 }
 
 /// Function form `bevy_inspector_egui` to split component to data ptr and "set changed" function
@@ -440,6 +696,9 @@ struct FilterComponentState {
 enum InspectCommand {
     AddComponent(Entity, TypeId),
     RemoveComponent(Entity, TypeId),
+    /// Entity, component type path and field of prefab override
+    RevertOverride(Entity, String, String),
+    ApplyOverride(Entity, String, String),
 }
 
 fn execute_inspect_command(
@@ -456,6 +715,30 @@ fn execute_inspect_command(
             InspectCommand::RemoveComponent(e, id) => {
                 registration.remove_by_id(&mut commands.entity(*e), id);
             }
+            InspectCommand::RevertOverride(e, component, field) => {
+                let (e, component, field) = (*e, component.clone(), field.clone());
+                commands.add(move |world: &mut World| {
+                    if let Err(err) = revert_override(world, e, &component, &field) {
+                        error!("Failed to revert prefab override: {err}");
+                        world.send_event(ToastMessage::new(
+                            &format!("Failed to revert prefab override: {err}"),
+                            ToastKind::Error,
+                        ));
+                    }
+                });
+            }
+            InspectCommand::ApplyOverride(e, component, field) => {
+                let (e, component, field) = (*e, component.clone(), field.clone());
+                commands.add(move |world: &mut World| {
+                    if let Err(err) = apply_override_to_prefab(world, e, &component, &field) {
+                        error!("Failed to apply override to prefab: {err}");
+                        world.send_event(ToastMessage::new(
+                            &format!("Failed to apply override to prefab: {err}"),
+                            ToastKind::Error,
+                        ));
+                    }
+                });
+            }
         }
     }
     state.commands.clear();
@@ -1,7 +1,7 @@
 use std::any::{Any, TypeId};
 
 use bevy::{
-    prelude::{AppTypeRegistry, ResMut},
+    prelude::{AppTypeRegistry, Entity, Name, Parent, ResMut, World},
     reflect::Reflect,
 };
 use bevy_egui::egui;
@@ -9,7 +9,10 @@ use space_shared::ext::bevy_inspector_egui::{
     inspector_egui_impls::InspectorEguiImpl, reflect_inspector::InspectorUi,
 };
 
-use space_prefab::component::EntityLink;
+use space_prefab::{
+    component::{EntityLink, GuidLink, PrefabGuid},
+    load::PrefabLoader,
+};
 
 /// Method from `bevy_inspector_egui` to make dummy reflection ui
 pub fn many_unimplemented<T: Any>(
@@ -75,3 +78,97 @@ pub fn entity_ref_ui_readonly(
     _: InspectorUi<'_, '_>,
 ) {
 }
+
+/// Custom UI for [`GuidLink`] struct
+pub fn guid_link_ui(
+    value: &mut dyn Any,
+    ui: &mut egui::Ui,
+    _options: &dyn Any,
+    id: egui::Id,
+    env: InspectorUi<'_, '_>,
+) -> bool {
+    let Some(value) = value.downcast_mut::<GuidLink>() else {
+        return false;
+    };
+    let Some(world) = &env.context.world else {
+        ui.label(format!("{:?}", value.target));
+        return false;
+    };
+    let world_ref = unsafe { world.world().world() };
+
+    let selected = value
+        .target
+        .and_then(|target| {
+            world_ref
+                .iter_entities()
+                .find(|e| e.get::<PrefabGuid>().is_some_and(|guid| guid.0 == target))
+                .map(|e| guid_entity_label(world_ref, e.id()))
+        })
+        .unwrap_or_else(|| "None".to_string());
+
+    let mut changed = false;
+    egui::ComboBox::new(id, "")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            if ui
+                .selectable_label(value.target.is_none(), "None")
+                .clicked()
+            {
+                *value = GuidLink::default();
+                changed = true;
+            }
+            for e in world_ref.iter_entities() {
+                let Some(guid) = e.get::<PrefabGuid>() else {
+                    continue;
+                };
+                if ui
+                    .selectable_label(
+                        value.target == Some(guid.0),
+                        guid_entity_label(world_ref, e.id()),
+                    )
+                    .clicked()
+                {
+                    value.target = Some(guid.0);
+                    value.instance = prefab_instance_guid(world_ref, e.id());
+                    changed = true;
+                }
+            }
+        });
+    changed
+}
+
+/// Custom UI for [`GuidLink`] struct
+pub fn guid_link_ui_readonly(
+    value: &dyn Any,
+    ui: &mut egui::Ui,
+    _: &dyn Any,
+    _: egui::Id,
+    _: InspectorUi<'_, '_>,
+) {
+    if let Some(value) = value.downcast_ref::<GuidLink>() {
+        ui.label(format!("{:?}", value.target));
+    }
+}
+
+fn guid_entity_label(world: &World, entity: Entity) -> String {
+    world.get::<Name>(entity).map_or_else(
+        || {
+            world
+                .get::<PrefabGuid>(entity)
+                .map_or_else(|| format!("{entity:?}"), |guid| guid.0.to_string())
+        },
+        |name| name.to_string(),
+    )
+}
+
+/// Guid of the closest [`PrefabLoader`] ancestor, if the entity was spawned from another prefab
+fn prefab_instance_guid(world: &World, entity: Entity) -> Option<space_prefab::ext::uuid::Uuid> {
+    let mut current = world.get::<Parent>(entity).map(Parent::get);
+    while let Some(ancestor) = current {
+        if world.get::<PrefabLoader>(ancestor).is_some() {
+            return world.get::<PrefabGuid>(ancestor).map(|guid| guid.0);
+        }
+        current = world.get::<Parent>(ancestor).map(Parent::get);
+    }
+    None
+}
@@ -1,7 +1,11 @@
-use bevy::{prelude::*, utils::HashMap};
+use std::any::TypeId;
+
+use bevy::{prelude::*, reflect::TypeRegistry, utils::HashMap};
 
 use bevy_egui::*;
 
+use space_prefab::editor_registry::EditorRegistry;
+
 use crate::prelude::*;
 
 #[derive(Resource, Default)]
@@ -39,7 +43,47 @@ pub fn inspect(ui: &mut egui::Ui, world: &mut World, open_resources: &mut HashMa
         .collect();
     resources.sort_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b));
 
-    egui::Grid::new("Resources ID".to_string()).show(ui, |ui| {
+    // Resources registered with `editor_resource_registry` are saved with the scene
+    let saved = world
+        .get_resource::<EditorRegistry>()
+        .map(|registry| registry.saved_resources())
+        .unwrap_or_default();
+    let (scene_resources, resources): (Vec<_>, Vec<_>) = resources
+        .into_iter()
+        .partition(|(_, type_id)| saved.contains(type_id));
+
+    if !scene_resources.is_empty() {
+        ui.heading("Scene settings");
+        resource_grid(
+            ui,
+            world,
+            "Scene settings ID",
+            scene_resources,
+            open_resources,
+            &type_registry,
+        );
+        ui.separator();
+        ui.heading("Resources");
+    }
+    resource_grid(
+        ui,
+        world,
+        "Resources ID",
+        resources,
+        open_resources,
+        &type_registry,
+    );
+}
+
+fn resource_grid(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    id: &str,
+    resources: Vec<(String, TypeId)>,
+    open_resources: &mut HashMap<String, bool>,
+    type_registry: &TypeRegistry,
+) {
+    egui::Grid::new(id.to_string()).show(ui, |ui| {
         for (resource_name, type_id) in resources {
             ui.push_id(format!("{:?}-{}", &type_id, &resource_name), |ui| {
                 let header = egui::CollapsingHeader::new(resource_name.clone())
@@ -51,7 +95,7 @@ pub fn inspect(ui: &mut egui::Ui, world: &mut World, open_resources: &mut HashMa
                                 type_id,
                                 ui,
                                 &resource_name,
-                                &type_registry,
+                                type_registry,
                             );
                         });
                     });
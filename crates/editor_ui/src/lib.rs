@@ -3,7 +3,7 @@
 /// This module contains ui logics, which will be work through events with editor core module and prefab module
 mod mouse_check;
 
-/// This module will be used to create Unity like project file dialog. Currently NOT USED
+/// This module contains the scan of files in the assets folder and Asset Browser tab logic
 pub mod asset_inspector;
 
 /// This module contains logic for menu toolbars
@@ -30,6 +30,15 @@ pub mod meshless_visualizer;
 /// This module contains Settings tab logic
 pub mod settings;
 
+/// This module contains Scene Diff tab logic
+pub mod scene_diff;
+
+/// This module contains Asset Dependencies tab logic
+pub mod asset_dependencies;
+
+/// This module contains Unused Assets tab logic
+pub mod unused_assets;
+
 /// This module contains traits and methods to register tools in game view tab
 pub mod tool;
 
@@ -321,10 +330,12 @@ fn clear_and_load_on_start(
         Some(space_shared::EditorPrefabPath::File(path)) => {
             info!("Loading prefab from file {}", path);
             load_server.scene = Some(assets.load(format!("{}.scn.ron", path)));
+            load_server.requested_at = Some(bevy::utils::Instant::now());
         }
         Some(space_shared::EditorPrefabPath::MemoryCache) => {
             info!("Loading prefab from cache");
             load_server.scene.clone_from(&cache.scene);
+            load_server.requested_at = Some(bevy::utils::Instant::now());
         }
         _ => {}
     }
@@ -1,6 +1,12 @@
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowCloseRequested},
+};
 use bevy_egui::{
     egui::{Align, Align2, Margin, Pos2, Stroke, Widget},
     *,
@@ -12,8 +18,19 @@ use space_editor_core::{
     toast::{ClearToastMessage, ToastStorage},
 };
 use space_editor_tabs::prelude::*;
-use space_prefab::{component::GltfPrefab, load::PrefabBundle, plugins::PrefabPlugin};
-use space_shared::{ext::egui_file, *};
+use space_prefab::{
+    component::GltfPrefab,
+    load::PrefabBundle,
+    merge::{resolve_merge_conflict, MergeSide, SceneMergeConflicts},
+    plugins::PrefabPlugin,
+    save::{newest_backup, restore_newest_backup, SaveCompleted, SaveConfig, ASSETS_DIR},
+    validate::is_scene_file,
+};
+use space_shared::{
+    ext::egui_file,
+    toast::{ToastKind, ToastMessage},
+    *,
+};
 use space_undo::{AddedEntity, NewChange, RemovedEntity};
 
 use crate::{
@@ -38,6 +55,7 @@ impl Plugin for BottomMenuPlugin {
 
         app.init_resource::<EditorLoader>();
         app.init_resource::<MenuToolbarState>();
+        app.init_resource::<UnsavedChangesPrompt>();
 
         app.add_systems(
             Update,
@@ -54,6 +72,28 @@ impl Plugin for BottomMenuPlugin {
                 .run_if(in_state(EditorState::Editor).and_then(in_state(ShowEditorUi::Show))),
         );
         app.add_systems(Update, in_game_menu.in_set(EditorSet::Game));
+        app.add_systems(
+            Update,
+            (unsaved_changes_prompt, update_window_title)
+                .chain()
+                .after(top_menu)
+                .in_set(EditorSet::Editor)
+                .run_if(in_state(EditorState::Editor)),
+        );
+        // Window close is requested in every editor state, including a running game
+        app.add_systems(Update, request_close_window.before(unsaved_changes_prompt));
+        app.add_systems(
+            Update,
+            (
+                scene_recovery_window,
+                autosave_recovery_window,
+                merge_scene_window,
+                merge_conflicts_window,
+            )
+                .after(EditorLoadSet)
+                .in_set(EditorSet::Editor)
+                .run_if(in_state(EditorState::Editor)),
+        );
         app.add_event::<MenuLoadEvent>();
     }
 }
@@ -147,8 +187,437 @@ pub struct MenuToolbarState {
     pub save_dialog: Option<egui_file::FileDialog>,
     pub load_dialog: Option<egui_file::FileDialog>,
     pub subscene_dialog: Option<egui_file::FileDialog>,
+    pub merge_dialog: Option<egui_file::FileDialog>,
+    /// Scene chosen to be merged into the open scene, waiting for merge options
+    pub merge: Option<MergeOptions>,
     show_toasts: bool,
     pub path: String,
+    /// Scene that failed to load and has a backup to restore from
+    pub recovery: Option<SceneRecovery>,
+}
+
+/// Options of a scene merge edited before it is requested
+#[derive(Clone, Debug, Default)]
+pub struct MergeOptions {
+    /// Asset path of the merged scene
+    pub path: String,
+    /// Attach merged root entities to the selected entity
+    pub under_selected: bool,
+    pub translation: Vec3,
+}
+
+/// Failed scene load offered to be restored from backup
+#[derive(Clone, Debug)]
+pub struct SceneRecovery {
+    /// Asset path of the scene
+    pub path: String,
+    pub error: String,
+    pub backup: PathBuf,
+}
+
+/// Scene action which discards unsaved changes of the open scene
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneAction {
+    Load(EditorPrefabPath),
+    New,
+    CloseWindow(Entity),
+}
+
+/// Scene action waiting for the user to save or discard unsaved changes
+#[derive(Resource, Default)]
+pub struct UnsavedChangesPrompt {
+    action: Option<SceneAction>,
+    /// Path of the save requested from the prompt
+    saving: Option<String>,
+}
+
+impl UnsavedChangesPrompt {
+    /// Run the action, asking first if the open scene has unsaved changes
+    pub fn request(&mut self, action: SceneAction) {
+        self.action = Some(action);
+        self.saving = None;
+    }
+}
+
+/// Ask before closing the window, requires `WindowPlugin::close_when_requested` to be disabled.
+/// The prompt is shown in the editor, so a running game is stopped first.
+fn request_close_window(
+    mut close_requests: EventReader<WindowCloseRequested>,
+    mut prompt: ResMut<UnsavedChangesPrompt>,
+    state: Res<State<EditorState>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    for event in close_requests.read() {
+        prompt.request(SceneAction::CloseWindow(event.window));
+    }
+    // During `GamePrepare` the scene is still being saved, the game is stopped once it starts
+    if *state.get() == EditorState::Game
+        && matches!(prompt.action, Some(SceneAction::CloseWindow(_)))
+    {
+        next_state.set(EditorState::Editor);
+    }
+}
+
+fn run_scene_action(
+    action: SceneAction,
+    commands: &mut Commands,
+    editor_events: &mut EventWriter<EditorEvent>,
+) {
+    match action {
+        SceneAction::Load(path) => {
+            editor_events.send(EditorEvent::Load(path));
+        }
+        SceneAction::New => {
+            editor_events.send(EditorEvent::New);
+        }
+        SceneAction::CloseWindow(window) => {
+            if let Some(mut window) = commands.get_entity(window) {
+                window.despawn();
+            }
+        }
+    }
+}
+
+/// Save / Discard / Cancel modal for actions requested with unsaved changes
+fn unsaved_changes_prompt(
+    mut commands: Commands,
+    mut ctxs: EguiContexts,
+    mut prompt: ResMut<UnsavedChangesPrompt>,
+    open_scene: Res<OpenScene>,
+    mut saves: EventReader<SaveCompleted>,
+    mut editor_events: EventWriter<EditorEvent>,
+    save_config: Option<Res<SaveConfig>>,
+) {
+    for event in saves.read() {
+        let Some(EditorPrefabPath::File(path)) = &event.path else {
+            continue;
+        };
+        if prompt.saving.as_ref() != Some(path) {
+            continue;
+        }
+        prompt.saving = None;
+        if event.result.is_ok() {
+            if let Some(action) = prompt.action.take() {
+                run_scene_action(action, &mut commands, &mut editor_events);
+            }
+        }
+    }
+
+    let Some(action) = prompt.action.clone() else {
+        return;
+    };
+    if !open_scene.dirty {
+        prompt.action = None;
+        run_scene_action(action, &mut commands, &mut editor_events);
+        return;
+    }
+
+    enum Choice {
+        Save,
+        Discard,
+        Cancel,
+    }
+    let mut choice = None;
+    egui::Window::new("Unsaved changes")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctxs.ctx_mut(), |ui| {
+            ui.label(format!(
+                "Save changes to {} before closing it?",
+                open_scene.name()
+            ));
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(prompt.saving.is_none(), |ui| {
+                    let save = ui
+                        .add_enabled(open_scene.path.is_some(), egui::Button::new("Save"))
+                        .on_disabled_hover_text("Scene has no file yet, use the save button");
+                    if save.clicked() {
+                        choice = Some(Choice::Save);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(Choice::Discard);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(Choice::Cancel);
+                    }
+                });
+                if prompt.saving.is_some() {
+                    ui.spinner();
+                }
+            });
+        });
+
+    match choice {
+        Some(Choice::Save) => {
+            if let Some(path) = open_scene.path.clone() {
+                // Saves report the written file, which has another extension for binary scenes
+                prompt.saving = Some(
+                    save_config
+                        .as_ref()
+                        .map_or_else(|| path.clone(), |config| config.format.file_path(&path)),
+                );
+                editor_events.send(EditorEvent::Save(EditorPrefabPath::File(path)));
+            }
+        }
+        Some(Choice::Discard) => {
+            prompt.action = None;
+            run_scene_action(action, &mut commands, &mut editor_events);
+        }
+        Some(Choice::Cancel) => {
+            prompt.action = None;
+        }
+        None => {}
+    }
+}
+
+/// Show the open scene and its unsaved changes in the window title
+fn update_window_title(
+    mut base_title: Local<Option<String>>,
+    open_scene: Res<OpenScene>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !open_scene.is_changed() && base_title.is_some() {
+        return;
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let base_title = base_title.get_or_insert_with(|| window.title.clone());
+    let title = format!(
+        "{} - {}{}",
+        base_title,
+        open_scene.name(),
+        if open_scene.dirty { "*" } else { "" }
+    );
+    if window.title != title {
+        window.title = title;
+    }
+}
+
+/// Offer to restore autosaves left from the previous session
+fn autosave_recovery_window(
+    mut ctxs: EguiContexts,
+    mut recovery: ResMut<AutosaveRecovery>,
+    mut editor_events: EventWriter<EditorEvent>,
+    mut toasts: EventWriter<ToastMessage>,
+) {
+    if recovery.files.is_empty() {
+        return;
+    }
+    let mut restore = None;
+    let mut discard = None;
+    let mut later = false;
+    egui::Window::new("Recover autosave")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctxs.ctx_mut(), |ui| {
+            ui.label("Editor was closed with unsaved changes. Autosaves newer than their scenes:");
+            egui::Grid::new("autosave_recovery_grid").show(ui, |ui| {
+                for file in recovery.files.iter() {
+                    ui.label(file.scene.as_deref().unwrap_or("Untitled scene"));
+                    if ui.button("Restore").clicked() {
+                        restore = Some(file.clone());
+                    }
+                    if ui.button("Discard").clicked() {
+                        discard = Some(file.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button("Ignore").clicked() {
+                later = true;
+            }
+        });
+
+    if let Some(file) = discard {
+        if let Err(e) = recovery.discard(&file) {
+            error!("Failed to delete autosave {}: {}", file.asset_path, e);
+        }
+    }
+    if let Some(file) = restore {
+        info!("Restoring autosave {}", file.asset_path);
+        toasts.send(ToastMessage::new(
+            "Restored scene is not saved to its file until you save it",
+            ToastKind::Info,
+        ));
+        editor_events.send(EditorEvent::Load(EditorPrefabPath::File(
+            file.asset_path.clone(),
+        )));
+        recovery.restoring = Some(file);
+        later = true;
+    }
+    if later {
+        recovery.files.clear();
+    }
+}
+
+/// Ask where the chosen scene is merged into the open scene
+fn merge_scene_window(
+    mut ctxs: EguiContexts,
+    mut menu_state: ResMut<MenuToolbarState>,
+    mut editor_events: EventWriter<EditorEvent>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let Some(mut options) = menu_state.merge.clone() else {
+        return;
+    };
+    let parent = selected.get_single().ok();
+    let mut merge = false;
+    let mut close = false;
+    egui::Window::new("Merge scene")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctxs.ctx_mut(), |ui| {
+            ui.label(format!("Append {} to the open scene", options.path));
+            ui.add_enabled(
+                parent.is_some(),
+                egui::Checkbox::new(&mut options.under_selected, "Attach to selected entity"),
+            )
+            .on_disabled_hover_text("Select one entity to attach merged scene to it");
+            ui.horizontal(|ui| {
+                ui.label("Offset");
+                ui.add(egui::DragValue::new(&mut options.translation.x).prefix("x: "));
+                ui.add(egui::DragValue::new(&mut options.translation.y).prefix("y: "));
+                ui.add(egui::DragValue::new(&mut options.translation.z).prefix("z: "));
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Merge").clicked() {
+                    merge = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if merge {
+        editor_events.send(EditorEvent::Merge(MergeScene {
+            path: options.path.clone(),
+            parent: parent.filter(|_| options.under_selected),
+            offset: Transform::from_translation(options.translation),
+        }));
+        close = true;
+    }
+    menu_state.merge = if close { None } else { Some(options) };
+}
+
+/// List conflicts left in a scene file by `space_scene merge` and apply the chosen version
+fn merge_conflicts_window(
+    mut ctxs: EguiContexts,
+    conflicts: Option<Res<SceneMergeConflicts>>,
+    mut commands: Commands,
+) {
+    let Some(conflicts) = conflicts else {
+        return;
+    };
+    let mut resolve = None;
+    egui::Window::new("Merge conflicts")
+        .collapsible(false)
+        .default_width(500.)
+        .show(ctxs.ctx_mut(), |ui| {
+            ui.label(format!(
+                "{} values were changed in both merged versions. The scene uses our version until another is chosen.",
+                conflicts.conflicts.len()
+            ));
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(400.)
+                .show(ui, |ui| {
+                    for (index, conflict) in conflicts.conflicts.iter().enumerate() {
+                        ui.label(RichText::new(conflict.label()).strong())
+                            .on_hover_text(&conflict.type_path);
+                        ui.columns(2, |columns| {
+                            let sides = [
+                                (MergeSide::Ours, "Use ours", &conflict.ours),
+                                (MergeSide::Theirs, "Use theirs", &conflict.theirs),
+                            ];
+                            for (ui, (side, title, value)) in columns.iter_mut().zip(sides) {
+                                if ui.button(title).clicked() {
+                                    resolve = Some((index, side));
+                                }
+                                ui.monospace(value.as_deref().unwrap_or("removed"));
+                            }
+                        });
+                        ui.separator();
+                    }
+                });
+        });
+
+    if let Some((index, side)) = resolve {
+        commands.add(move |world: &mut World| {
+            if let Err(e) = resolve_merge_conflict(world, index, side) {
+                world.send_event(ToastMessage::new(
+                    &format!("Failed to resolve merge conflict: {e}"),
+                    ToastKind::Error,
+                ));
+            }
+        });
+    }
+}
+
+/// Offer to restore a scene from its newest backup when it fails to load
+fn scene_recovery_window(
+    mut ctxs: EguiContexts,
+    mut loads: EventReader<LoadCompleted>,
+    mut menu_state: ResMut<MenuToolbarState>,
+    mut editor_events: EventWriter<EditorEvent>,
+    mut toasts: EventWriter<ToastMessage>,
+) {
+    for event in loads.read() {
+        if let (EditorPrefabPath::File(path), Err(error)) = (&event.path, &event.result) {
+            menu_state.recovery =
+                newest_backup(Path::new(ASSETS_DIR).join(path)).map(|backup| SceneRecovery {
+                    path: path.clone(),
+                    error: error.to_string(),
+                    backup,
+                });
+        }
+    }
+
+    let Some(recovery) = menu_state.recovery.clone() else {
+        return;
+    };
+    let mut close = false;
+    egui::Window::new("Scene recovery")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctxs.ctx_mut(), |ui| {
+            ui.label(format!("Failed to load scene {}:", recovery.path));
+            ui.label(RichText::new(&recovery.error).color(ERROR_COLOR));
+            ui.label(format!(
+                "Restore from newest backup {}?",
+                recovery.backup.display()
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    close = true;
+                    match restore_newest_backup(Path::new(ASSETS_DIR).join(&recovery.path)) {
+                        Ok(_) => {
+                            editor_events.send(EditorEvent::Load(EditorPrefabPath::File(
+                                recovery.path.clone(),
+                            )));
+                        }
+                        Err(e) => {
+                            error!("Failed to restore scene {}: {}", recovery.path, e);
+                            toasts.send(ToastMessage::new(
+                                &format!("Failed to restore scene from backup: {e}"),
+                                ToastKind::Error,
+                            ));
+                        }
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+    if close {
+        menu_state.recovery = None;
+    }
 }
 
 pub fn bottom_menu(
@@ -159,7 +628,7 @@ pub fn bottom_menu(
     mut changes: EventWriter<NewChange>,
     mut state: ResMut<HierarchyTabState>,
     ui_reg: Res<BundleReg>,
-    menu_state: Res<MenuToolbarState>,
+    open_scene: Res<OpenScene>,
     sizing: Res<Sizing>,
     q_pan_cam: Query<&PanOrbitCamera>,
 ) {
@@ -278,19 +747,48 @@ pub fn bottom_menu(
                 let distance = ui.available_width() * 0.66 * 12. / sizing.text;
                 ui.add_space(distance);
                 ui.label(to_label(
-                    &format!("Current Scene: {}", menu_state.path),
+                    &format!(
+                        "Current Scene: {}{}",
+                        open_scene.name(),
+                        if open_scene.dirty { "*" } else { "" }
+                    ),
                     sizing.text,
                 ));
             });
         });
 }
 
+/// Opened dialog choosing a scene file of any scene format
+fn scene_file_dialog(dir: &str, title: &str) -> egui_file::FileDialog {
+    let mut dialog = egui_file::FileDialog::open_file(Some(dir.into()))
+        .show_files_filter(Box::new(|path| is_scene_file(&path.to_string_lossy())))
+        .title(title);
+    dialog.open();
+    dialog
+}
+
+/// Asset path of the file chosen in a dialog, `None` for files outside the assets folder
+fn chosen_asset_path(file: &Path) -> Option<String> {
+    let path = file.to_string_lossy().replace('\\', "/");
+    path.strip_prefix("assets/").map(ToString::to_string)
+}
+
+/// Opened dialog choosing where the scene is saved
+fn save_scene_dialog() -> egui_file::FileDialog {
+    let mut dialog = egui_file::FileDialog::save_file(Some("./assets/scenes".into()))
+        .default_filename("Scene0.scn.ron")
+        .title("Save Scene");
+    dialog.open();
+    dialog
+}
+
 pub fn top_menu(
     mut commands: Commands,
     mut ctxs: EguiContexts,
     _state: ResMut<NextState<EditorState>>,
     mut events: EventReader<MenuLoadEvent>,
     mut menu_state: ResMut<MenuToolbarState>,
+    mut prompt: ResMut<UnsavedChangesPrompt>,
     mut editor_events: EventWriter<EditorEvent>,
     mut clear_toast: EventWriter<ClearToastMessage>,
     background_tasks: Res<BackgroundTaskStorage>,
@@ -306,33 +804,57 @@ pub fn top_menu(
                 let stl = ui.style_mut();
                 stl.spacing.button_padding = egui::Vec2::new(8., 4.);
 
+                // File menu
+                ui.menu_button("File", |ui| {
+                    if ui.button("New scene").clicked() {
+                        prompt.request(SceneAction::New);
+                        ui.close_menu();
+                    }
+                    if ui.button("Open scene…").clicked() {
+                        menu_state.load_dialog = Some(scene_file_dialog(
+                            "assets/scenes",
+                            "Load Scene (*.scn.ron, *.scn.bin, *.scn)",
+                        ));
+                        ui.close_menu();
+                    }
+                    if ui.button("Save scene…").clicked() {
+                        menu_state.save_dialog = Some(save_scene_dialog());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Merge scene…").clicked() {
+                        menu_state.merge_dialog = Some(scene_file_dialog(
+                            "assets/scenes",
+                            "Merge Scene (*.scn.ron, *.scn.bin, *.scn)",
+                        ));
+                        ui.close_menu();
+                    }
+                });
+                // END File menu
+
+                // New Scene
+                let new_button = egui::Button::new(to_richtext("🗋", &sizing.icon))
+                    .stroke(stroke_default_color());
+                if ui.add(new_button).on_hover_text("New scene").clicked() {
+                    prompt.request(SceneAction::New);
+                }
+
                 // Open Assets Folder
                 let open_button = egui::Button::new(to_richtext("📂", &sizing.icon))
                     .stroke(stroke_default_color());
                 if ui.add(open_button).clicked() {
-                    let mut dialog = egui_file::FileDialog::open_file(Some("assets/".into()))
-                        .show_files_filter(Box::new(|path| {
-                            path.to_str().unwrap().ends_with(".scn.ron")
-                        }))
-                        .title("File Explorer (Scene/Bundle) (*.scn.ron)");
-                    dialog.open();
-                    menu_state.file_dialog = Some(dialog);
+                    menu_state.file_dialog = Some(scene_file_dialog(
+                        "assets/",
+                        "File Explorer (Scene/Bundle) (*.scn.ron, *.scn.bin, *.scn)",
+                    ));
                 }
 
                 if let Some(dialog) = &mut menu_state.file_dialog {
                     if dialog.show(ctx).selected() {
-                        if let Some(file) = dialog.path() {
-                            let mut path = file.to_str().unwrap().to_string();
-                            //remove assets/ from path
-                            if path.starts_with("assets/") {
-                                path = path.replace("assets/", "");
-                                //remove .scn.ron
-                                path = path.replace(".scn.ron", "");
-                                menu_state.path = path;
-                                editor_events.send(EditorEvent::Load(EditorPrefabPath::File(
-                                    format!("{}.scn.ron", menu_state.path.clone()),
-                                )));
-                            }
+                        if let Some(path) = dialog.path().and_then(chosen_asset_path) {
+                            menu_state.path =
+                                path.strip_suffix(".scn.ron").unwrap_or(&path).to_string();
+                            prompt.request(SceneAction::Load(EditorPrefabPath::File(path)));
                         }
                     } else {
                         let mut need_move_to_default_dir = false;
@@ -358,12 +880,7 @@ pub fn top_menu(
                     .on_hover_text("Save current scene")
                     .clicked()
                 {
-                    let mut save_dialog =
-                        egui_file::FileDialog::save_file(Some("./assets/scenes".into()))
-                            .default_filename("Scene0.scn.ron")
-                            .title("Save Scene");
-                    save_dialog.open();
-                    menu_state.save_dialog = Some(save_dialog);
+                    menu_state.save_dialog = Some(save_scene_dialog());
                 }
 
                 if let Some(save_dialog) = &mut menu_state.save_dialog {
@@ -403,29 +920,18 @@ pub fn top_menu(
                     .on_hover_text("Load scene file")
                     .clicked()
                 {
-                    let mut dialog = egui_file::FileDialog::open_file(Some("assets/scenes".into()))
-                        .show_files_filter(Box::new(|path| {
-                            path.to_str().unwrap().ends_with(".scn.ron")
-                        }))
-                        .title("Load Scene (*.scn.ron)");
-                    dialog.open();
-                    menu_state.load_dialog = Some(dialog);
+                    menu_state.load_dialog = Some(scene_file_dialog(
+                        "assets/scenes",
+                        "Load Scene (*.scn.ron, *.scn.bin, *.scn)",
+                    ));
                 }
 
                 if let Some(dialog) = &mut menu_state.load_dialog {
                     if dialog.show(ctx).selected() {
-                        if let Some(file) = dialog.path() {
-                            let mut path = file.to_str().unwrap().to_string();
-                            //remove assets/ from path
-                            if path.starts_with("assets/") {
-                                path = path.replace("assets/", "");
-                                //remove .scn.ron
-                                path = path.replace(".scn.ron", "");
-                                menu_state.path = path;
-                                editor_events.send(EditorEvent::Load(EditorPrefabPath::File(
-                                    format!("{}.scn.ron", menu_state.path.clone()),
-                                )));
-                            }
+                        if let Some(path) = dialog.path().and_then(chosen_asset_path) {
+                            menu_state.path =
+                                path.strip_suffix(".scn.ron").unwrap_or(&path).to_string();
+                            prompt.request(SceneAction::Load(EditorPrefabPath::File(path)));
                         }
                     } else {
                         let mut need_move_to_default_dir = false;
@@ -443,6 +949,28 @@ pub fn top_menu(
                 }
                 // END Load Scene
 
+                // Merge Scene, opened from the File menu
+                if let Some(dialog) = &mut menu_state.merge_dialog {
+                    if dialog.show(ctx).selected() {
+                        if let Some(path) = dialog.path().and_then(chosen_asset_path) {
+                            menu_state.merge = Some(MergeOptions { path, ..default() });
+                        }
+                    } else {
+                        let mut need_move_to_default_dir = false;
+                        if let Some(path) = dialog.directory().to_str() {
+                            if !path.contains("assets") {
+                                need_move_to_default_dir = true;
+                            }
+                        } else {
+                            need_move_to_default_dir = true;
+                        }
+                        if need_move_to_default_dir {
+                            dialog.set_path("assets/");
+                        }
+                    }
+                }
+                // END Merge Scene
+
                 // Open GLTF
                 let open_gltf_button =
                     prefab_icon(sizing.icon.to_size(), "").stroke(stroke_default_color());
@@ -633,7 +1161,7 @@ pub fn top_menu(
 
     for event in events.read() {
         menu_state.path.clone_from(&event.path);
-        editor_events.send(EditorEvent::Load(EditorPrefabPath::File(format!(
+        prompt.request(SceneAction::Load(EditorPrefabPath::File(format!(
             "{}.scn.ron",
             menu_state.path.clone()
         ))));
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy_egui::egui::{self, RichText};
+use space_editor_core::prelude::OpenScene;
+use space_editor_tabs::prelude::*;
+use space_prefab::{
+    diff::{diff_scenes, short_type_path, ComponentChange, ComponentDiff, EntityChange, SceneDiff},
+    format::loader::PrefabSceneLoader,
+    save::{saved_scene, SaveConfig},
+};
+
+use crate::{colors::*, editor_tab_name::EditorTabName};
+
+pub struct SceneDiffPlugin;
+
+impl Plugin for SceneDiffPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.editor_tab_by_trait(SceneDiffTab::default());
+    }
+}
+
+/// Differences between the open scene and its file on disk
+#[derive(Resource, Default)]
+pub struct SceneDiffTab {
+    diff: Option<Result<SceneDiff, String>>,
+}
+
+impl EditorTab for SceneDiffTab {
+    fn ui(&mut self, ui: &mut egui::Ui, _commands: &mut Commands, world: &mut World) {
+        let Some(path) = world
+            .get_resource::<OpenScene>()
+            .and_then(|open_scene| open_scene.path.clone())
+        else {
+            ui.label("Open scene is not saved to a file yet");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            if ui.button("Refresh").clicked() {
+                self.diff = Some(compare_with_file(world, &path));
+            }
+            ui.label(&path);
+        });
+        ui.separator();
+
+        match &self.diff {
+            None => {
+                ui.label("Press Refresh to compare the open scene with the saved file");
+            }
+            Some(Err(error)) => {
+                ui.label(RichText::new(error).color(ERROR_COLOR));
+            }
+            Some(Ok(diff)) if diff.is_empty() => {
+                ui.label("No changes");
+            }
+            Some(Ok(diff)) => {
+                egui::ScrollArea::vertical().show(ui, |ui| diff_ui(ui, diff));
+            }
+        }
+    }
+
+    fn tab_name(&self) -> space_editor_tabs::tab_name::TabNameHolder {
+        EditorTabName::SceneDiff.into()
+    }
+}
+
+fn compare_with_file(world: &mut World, path: &str) -> Result<SceneDiff, String> {
+    let format = world
+        .get_resource::<SaveConfig>()
+        .map(|config| config.format)
+        .unwrap_or_default();
+    let loader = PrefabSceneLoader::from_world(world);
+    let saved = loader
+        .load_from_file(format.file_path(path))
+        .map_err(|e| format!("Failed to load {path}: {e}"))?;
+    let open = saved_scene(world).map_err(|e| e.to_string())?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    Ok(diff_scenes(&saved, &open, &registry))
+}
+
+fn components_ui(ui: &mut egui::Ui, components: &[ComponentDiff]) {
+    for component in components {
+        let name = short_type_path(&component.type_path);
+        match &component.change {
+            ComponentChange::Added => {
+                ui.label(RichText::new(format!("+ {name}")).color(PLAY_COLOR));
+            }
+            ComponentChange::Removed => {
+                ui.label(RichText::new(format!("- {name}")).color(ERROR_COLOR));
+            }
+            ComponentChange::Changed(fields) => {
+                for field in fields {
+                    ui.label(RichText::new(format!("~ {name}{field}")).color(WARN_COLOR))
+                        .on_hover_text(&component.type_path);
+                }
+            }
+        }
+    }
+}
+
+fn diff_ui(ui: &mut egui::Ui, diff: &SceneDiff) {
+    if !diff.resources.is_empty() {
+        egui::CollapsingHeader::new(RichText::new("Scene settings").color(WARN_COLOR))
+            .default_open(true)
+            .show(ui, |ui| components_ui(ui, &diff.resources));
+    }
+    for entity in diff.entities.iter() {
+        match &entity.change {
+            EntityChange::Added => {
+                ui.label(RichText::new(format!("+ {}", entity.path)).color(PLAY_COLOR));
+            }
+            EntityChange::Removed => {
+                ui.label(RichText::new(format!("- {}", entity.path)).color(ERROR_COLOR));
+            }
+            EntityChange::Changed {
+                moved_from,
+                components,
+            } => {
+                egui::CollapsingHeader::new(
+                    RichText::new(format!("~ {}", entity.path)).color(WARN_COLOR),
+                )
+                .id_source(&entity.path)
+                .default_open(true)
+                .show(ui, |ui| {
+                    if let Some(old) = moved_from {
+                        ui.label(format!("moved from {old}"));
+                    }
+                    components_ui(ui, components);
+                });
+            }
+        }
+    }
+}
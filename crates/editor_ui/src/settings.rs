@@ -5,7 +5,7 @@ use bevy::{
     utils::{HashMap, HashSet},
 };
 use bevy_egui::*;
-use space_editor_core::hotkeys::AllHotkeys;
+use space_editor_core::{autosave::AutosaveSettings, hotkeys::AllHotkeys};
 use space_shared::ext::bevy_inspector_egui::bevy_inspector;
 use space_undo::ChangeChainSettings;
 
@@ -39,6 +39,7 @@ impl Plugin for SettingsWindowPlugin {
             app.persistence_resource::<NewWindowSettings>()
                 .persistence_resource::<Sizing>()
                 .persistence_resource::<ChangeChainSettings>()
+                .persistence_resource::<AutosaveSettings>()
                 .persistence_resource::<GameModeSettings>();
         }
     }
@@ -164,6 +165,26 @@ impl EditorTab for SettingsWindow {
             );
         });
 
+        ui.add_space(12.);
+        ui.heading("Autosave");
+        if let Some(mut settings) = world.get_resource_mut::<AutosaveSettings>() {
+            ui.checkbox(&mut settings.enabled, "Enabled");
+            let enabled = settings.enabled;
+            ui.add_enabled(
+                enabled,
+                egui::DragValue::new(&mut settings.interval)
+                    .range(5.0..=3600.0)
+                    .prefix("Interval: ")
+                    .suffix(" s"),
+            );
+            ui.add_enabled(
+                enabled,
+                egui::Checkbox::new(&mut settings.only_when_dirty, "Only when scene changed"),
+            );
+        } else {
+            error!("Autosave settings not available");
+        }
+
         ui.add_space(12.);
         ui.heading("New Tab Behaviour");
         if let Some(new_window_settings) = &mut world.get_resource_mut::<NewWindowSettings>() {
@@ -48,6 +48,10 @@ impl FlatPluginList for EditorUiPlugin {
             .add(SpaceInspectorPlugin)
             .add(GizmoToolPlugin)
             .add(ChangeChainViewPlugin)
+            .add(scene_diff::SceneDiffPlugin)
+            .add(asset_dependencies::AssetDependenciesPlugin)
+            .add(unused_assets::UnusedAssetsPlugin)
+            .add(asset_inspector::browser::AssetBrowserPlugin)
             .add(settings::SettingsWindowPlugin);
 
         if self.use_standard_layout {
@@ -0,0 +1,157 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_egui::egui::{self, RichText};
+use space_editor_tabs::prelude::*;
+use space_prefab::{
+    save::ASSETS_DIR,
+    unused_assets::{quarantine_assets, used_assets, QUARANTINE_DIR},
+};
+use space_shared::toast::{ToastKind, ToastMessage};
+
+use crate::{
+    asset_inspector::{AssetDetectorPlugin, DetectedAssets},
+    colors::*,
+    editor_tab_name::EditorTabName,
+};
+
+pub struct UnusedAssetsPlugin;
+
+impl Plugin for UnusedAssetsPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<AssetDetectorPlugin>() {
+            app.add_plugins(AssetDetectorPlugin);
+        }
+        app.editor_tab_by_trait(UnusedAssetsTab::default());
+    }
+}
+
+/// Report of asset files not used by any scene, with moving them to a quarantine folder
+#[derive(Resource, Default)]
+pub struct UnusedAssetsTab {
+    /// Unused asset paths of the last scan, `None` before the first scan
+    unused: Option<Vec<String>>,
+    selected: HashSet<String>,
+    /// Scene files that failed to load in the last scan
+    errors: Vec<String>,
+    scenes: usize,
+}
+
+impl UnusedAssetsTab {
+    fn scan(&mut self, world: &mut World) {
+        world.resource_mut::<DetectedAssets>().rescan();
+        match used_assets(world, ASSETS_DIR) {
+            Ok(used) => {
+                let detected = world.resource::<DetectedAssets>();
+                let unused = used.unused(detected.assets.iter().map(|asset| asset.path.as_str()));
+                self.selected = unused.iter().cloned().collect();
+                self.unused = Some(unused);
+                self.scenes = used.scenes;
+                self.errors = used
+                    .errors
+                    .iter()
+                    .map(|(file, error)| format!("{}: {error}", file.display()))
+                    .collect();
+            }
+            Err(e) => {
+                self.unused = None;
+                world.send_event(ToastMessage::new(
+                    &format!("Failed to scan {ASSETS_DIR}: {e}"),
+                    ToastKind::Error,
+                ));
+            }
+        }
+    }
+}
+
+impl EditorTab for UnusedAssetsTab {
+    fn ui(&mut self, ui: &mut egui::Ui, _commands: &mut Commands, world: &mut World) {
+        let mut scan = false;
+        let mut quarantine = false;
+        ui.horizontal(|ui| {
+            if ui
+                .button("Scan")
+                .on_hover_text("Find assets not used by scenes, prefabs or registered code paths")
+                .clicked()
+            {
+                scan = true;
+            }
+            if ui
+                .add_enabled(
+                    !self.selected.is_empty(),
+                    egui::Button::new(format!("Move {} to {QUARANTINE_DIR}", self.selected.len())),
+                )
+                .on_hover_text("Move selected files out of the assets folder, keeping their paths")
+                .clicked()
+            {
+                quarantine = true;
+            }
+        });
+
+        let Some(unused) = &self.unused else {
+            ui.label("Press Scan to find unused assets");
+            if scan {
+                self.scan(world);
+            }
+            return;
+        };
+        ui.label(format!(
+            "{} unused of {} assets, checked {} scenes",
+            unused.len(),
+            world.resource::<DetectedAssets>().assets.len(),
+            self.scenes
+        ));
+        for error in self.errors.iter() {
+            ui.label(
+                RichText::new(format!(
+                    "Skipped {error}, assets used by it are reported as unused"
+                ))
+                .color(WARN_COLOR),
+            );
+        }
+        ui.horizontal(|ui| {
+            if ui.small_button("Select all").clicked() {
+                self.selected = unused.iter().cloned().collect();
+            }
+            if ui.small_button("Select none").clicked() {
+                self.selected.clear();
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for path in unused.iter() {
+                let mut checked = self.selected.contains(path);
+                if ui.checkbox(&mut checked, path.as_str()).changed() {
+                    if checked {
+                        self.selected.insert(path.clone());
+                    } else {
+                        self.selected.remove(path);
+                    }
+                }
+            }
+        });
+
+        if quarantine {
+            let mut paths = self.selected.drain().collect::<Vec<_>>();
+            paths.sort();
+            match quarantine_assets(ASSETS_DIR, QUARANTINE_DIR, &paths) {
+                Ok(moved) => world.send_event(ToastMessage::new(
+                    &format!("Moved {moved} unused assets to {QUARANTINE_DIR}"),
+                    ToastKind::Success,
+                )),
+                Err(e) => world.send_event(ToastMessage::new(
+                    &format!("Failed to move unused assets: {e}"),
+                    ToastKind::Error,
+                )),
+            };
+            scan = true;
+        }
+        if scan {
+            self.scan(world);
+        }
+    }
+
+    fn tab_name(&self) -> space_editor_tabs::tab_name::TabNameHolder {
+        EditorTabName::UnusedAssets.into()
+    }
+}
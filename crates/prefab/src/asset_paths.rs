@@ -0,0 +1,366 @@
+use std::{any::TypeId, fs, path::Path, time::SystemTime};
+
+use bevy::{
+    prelude::*,
+    reflect::{GetTypeRegistration, ReflectMut, ReflectRef, TypeRegistration, TypeRegistry},
+};
+
+use crate::{
+    format::split::PARTS_DIR_EXTENSION,
+    save::{is_backup_file, RECOVERY_DIR},
+};
+
+/// Type data with names of `String` fields that hold asset paths,
+/// added with [`AssetPathsExt::register_asset_paths`]
+#[derive(Clone, Debug, Default)]
+pub struct ReflectAssetPaths {
+    pub fields: Vec<&'static str>,
+}
+
+/// Registration of asset path fields of components
+pub trait AssetPathsExt {
+    /// Mark `String` fields of `T` as asset paths,
+    /// so scene validation and asset tools can find and rewrite them
+    fn register_asset_paths<T: GetTypeRegistration>(
+        &mut self,
+        fields: &[&'static str],
+    ) -> &mut Self;
+
+    /// Mark asset files or folders loaded by game code, so they are not reported as unused
+    fn register_code_asset_paths(&mut self, paths: &[&str]) -> &mut Self;
+}
+
+impl AssetPathsExt for App {
+    fn register_asset_paths<T: GetTypeRegistration>(
+        &mut self,
+        fields: &[&'static str],
+    ) -> &mut Self {
+        self.register_type::<T>();
+        let registry = self.world().resource::<AppTypeRegistry>().clone();
+        let mut registry = registry.write();
+        if let Some(registration) = registry.get_mut(TypeId::of::<T>()) {
+            let mut data = registration
+                .data::<ReflectAssetPaths>()
+                .cloned()
+                .unwrap_or_default();
+            for field in fields {
+                if !data.fields.contains(field) {
+                    data.fields.push(field);
+                }
+            }
+            registration.insert(data);
+        }
+        self
+    }
+
+    fn register_code_asset_paths(&mut self, paths: &[&str]) -> &mut Self {
+        let mut code_paths = self
+            .world_mut()
+            .get_resource_or_insert_with(CodeAssetPaths::default);
+        for path in paths {
+            let path = path.trim_end_matches('/').to_string();
+            if !code_paths.paths.contains(&path) {
+                code_paths.paths.push(path);
+            }
+        }
+        self
+    }
+}
+
+/// Asset files and folders loaded by game code instead of scenes,
+/// added with [`AssetPathsExt::register_code_asset_paths`]
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CodeAssetPaths {
+    pub paths: Vec<String>,
+}
+
+/// Asset path stored in a component of an entity
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetReference {
+    pub entity: Entity,
+    pub type_path: String,
+    pub field: &'static str,
+    pub path: String,
+}
+
+/// Registrations of types with asset path fields
+pub fn asset_path_types(registry: &TypeRegistry) -> impl Iterator<Item = &TypeRegistration> {
+    registry
+        .iter()
+        .filter(|registration| registration.data::<ReflectAssetPaths>().is_some())
+}
+
+/// `(field, path)` of asset paths stored in the reflected value. Empty paths are skipped.
+pub fn asset_paths(value: &dyn Reflect, registry: &TypeRegistry) -> Vec<(&'static str, String)> {
+    let Some(fields) = value
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .and_then(|registration| registration.data::<ReflectAssetPaths>())
+    else {
+        return vec![];
+    };
+    let ReflectRef::Struct(value) = value.reflect_ref() else {
+        return vec![];
+    };
+    fields
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let path = value.field(field)?.downcast_ref::<String>()?;
+            (!path.is_empty()).then(|| (*field, path.clone()))
+        })
+        .collect()
+}
+
+/// Replace asset path in `field` of the reflected value. Returns `false` when there is no such field.
+pub fn set_asset_path(value: &mut dyn Reflect, field: &str, path: &str) -> bool {
+    let ReflectMut::Struct(value) = value.reflect_mut() else {
+        return false;
+    };
+    let Some(current) = value
+        .field_mut(field)
+        .and_then(|current| current.downcast_mut::<String>())
+    else {
+        return false;
+    };
+    path.clone_into(current);
+    true
+}
+
+/// Asset paths stored in components of scene entities
+pub fn scene_asset_references(
+    scene: &DynamicScene,
+    registry: &TypeRegistry,
+) -> Vec<AssetReference> {
+    let mut references = vec![];
+    for entity in scene.entities.iter() {
+        for component in entity.components.iter() {
+            let Some(info) = component.get_represented_type_info() else {
+                continue;
+            };
+            for (field, path) in asset_paths(component.as_ref(), registry) {
+                references.push(AssetReference {
+                    entity: entity.entity,
+                    type_path: info.type_path().to_string(),
+                    field,
+                    path,
+                });
+            }
+        }
+    }
+    references
+}
+
+/// Asset paths stored in components of `entity` in the world
+pub fn entity_asset_references(
+    world: &World,
+    entity: Entity,
+    registry: &TypeRegistry,
+) -> Vec<AssetReference> {
+    let Some(entity_ref) = world.get_entity(entity) else {
+        return vec![];
+    };
+    let mut references = vec![];
+    for registration in asset_path_types(registry) {
+        let Some(component) = registration
+            .data::<ReflectComponent>()
+            .and_then(|reflect_component| reflect_component.reflect(entity_ref))
+        else {
+            continue;
+        };
+        for (field, path) in asset_paths(component, registry) {
+            references.push(AssetReference {
+                entity,
+                type_path: registration.type_info().type_path().to_string(),
+                field,
+                path,
+            });
+        }
+    }
+    references
+}
+
+/// Replace the asset path of the reference in the world, returns `false` when the component is gone
+pub fn relink_asset_reference(
+    world: &mut World,
+    reference: &AssetReference,
+    path: &str,
+    registry: &TypeRegistry,
+) -> bool {
+    let Some(reflect_component) = registry
+        .get_with_type_path(&reference.type_path)
+        .and_then(|registration| registration.data::<ReflectComponent>())
+    else {
+        return false;
+    };
+    let Some(mut entity) = world.get_entity_mut(reference.entity) else {
+        return false;
+    };
+    reflect_component
+        .reflect_mut(&mut entity)
+        .is_some_and(|mut component| {
+            set_asset_path(component.as_reflect_mut(), reference.field, path)
+        })
+}
+
+/// Whether the file of asset `path` exists in `asset_root`. Labels like `#Scene0` are ignored
+/// and paths of other asset sources like `embedded://` are treated as existing.
+pub fn asset_exists(asset_root: impl AsRef<Path>, path: &str) -> bool {
+    let file = path.split('#').next().unwrap_or_default();
+    file.is_empty() || file.contains("://") || asset_root.as_ref().join(file).is_file()
+}
+
+/// File found in the assets folder
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetFile {
+    /// Path relative to the assets folder with `/` separators, as used by the asset server
+    pub path: String,
+    pub modified: Option<SystemTime>,
+}
+
+impl AssetFile {
+    /// Extension of the file name, empty for files without one
+    pub fn extension(&self) -> &str {
+        let name = self.path.rsplit('/').next().unwrap_or_default();
+        name.rsplit_once('.').map_or("", |(_, ext)| ext)
+    }
+}
+
+/// All files in `asset_root` and its subfolders, in path order.
+/// Backups, autosaves and parts of split scenes written by the editor are skipped.
+pub fn scan_asset_files(asset_root: impl AsRef<Path>) -> Vec<AssetFile> {
+    let root = asset_root.as_ref();
+    let parts_dir = format!(".{PARTS_DIR_EXTENSION}");
+    let mut files = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if name != RECOVERY_DIR && !name.ends_with(&parts_dir) {
+                    dirs.push(path);
+                }
+            } else if is_backup_file(&name) {
+                continue;
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                let parts = relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>();
+                files.push(AssetFile {
+                    path: parts.join("/"),
+                    modified: entry.metadata().and_then(|meta| meta.modified()).ok(),
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// Paths of all files in `asset_root` relative to it, with `/` separators, in path order
+pub fn asset_files(asset_root: impl AsRef<Path>) -> Vec<String> {
+    scan_asset_files(asset_root)
+        .into_iter()
+        .map(|file| file.path)
+        .collect()
+}
+
+/// File in `asset_root` with the same file name as the missing asset `path`, to suggest it for relinking
+pub fn find_moved_asset(asset_root: impl AsRef<Path>, path: &str) -> Option<String> {
+    let file = path.split('#').next().unwrap_or_default();
+    let name = file.rsplit('/').next().filter(|name| !name.is_empty())?;
+    let label = &path[file.len()..];
+    asset_files(asset_root)
+        .into_iter()
+        .find(|candidate| candidate.rsplit('/').next() == Some(name))
+        .map(|candidate| format!("{candidate}{label}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{MaterialPrefab, SpriteTexture};
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn collects_and_relinks_registered_asset_paths() {
+        let mut app = App::new();
+        app.register_asset_paths::<SpriteTexture>(&["texture"])
+            .register_asset_paths::<MaterialPrefab>(&["base_color_texture", "normal_map_texture"]);
+        let entity = app
+            .world_mut()
+            .spawn((
+                SpriteTexture {
+                    texture: "old/hero.png".to_string(),
+                },
+                MaterialPrefab {
+                    base_color_texture: "wall.png".to_string(),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let registry = app.world().resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let mut references = entity_asset_references(app.world(), entity, &registry);
+        references.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            references.len(),
+            2,
+            "empty paths are skipped: {references:?}"
+        );
+        assert_eq!(references[0].path, "old/hero.png");
+        assert_eq!(references[0].type_path, SpriteTexture::type_path());
+        assert_eq!(references[1].field, "base_color_texture");
+
+        assert!(relink_asset_reference(
+            app.world_mut(),
+            &references[0],
+            "new/hero.png",
+            &registry
+        ));
+        assert_eq!(
+            app.world().get::<SpriteTexture>(entity).unwrap().texture,
+            "new/hero.png"
+        );
+    }
+
+    #[test]
+    fn finds_moved_asset_by_file_name() {
+        let dir = TempDir::new("assets");
+        fs::create_dir_all(dir.join("models/props")).unwrap();
+        fs::write(dir.join("models/props/chair.glb"), "glb").unwrap();
+
+        assert!(asset_exists(&dir, "models/props/chair.glb#Scene0"));
+        assert!(!asset_exists(&dir, "chair.glb"));
+        assert!(asset_exists(&dir, "embedded://chair.glb"));
+        assert_eq!(
+            asset_files(&dir),
+            vec!["models/props/chair.glb".to_string()]
+        );
+        assert_eq!(
+            find_moved_asset(&dir, "old/chair.glb#Scene0").as_deref(),
+            Some("models/props/chair.glb#Scene0")
+        );
+        assert_eq!(find_moved_asset(&dir, "table.glb"), None);
+
+        fs::write(dir.join("LICENSE"), "").unwrap();
+        fs::write(dir.join("level.scn.ron.bak1"), "").unwrap();
+        fs::write(dir.join("level.scn.ron.tmp"), "").unwrap();
+        for folder in [RECOVERY_DIR, "level.parts"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            fs::write(dir.join(folder).join("level.scn.ron"), "").unwrap();
+        }
+        let files = scan_asset_files(&dir);
+        assert_eq!(files[0].path, "LICENSE");
+        assert_eq!(files[0].extension(), "");
+        assert_eq!(files[1].extension(), "glb");
+        assert!(files[1].modified.is_some());
+        assert_eq!(files.len(), 2, "editor files are not assets: {files:?}");
+    }
+}
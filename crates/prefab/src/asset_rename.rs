@@ -0,0 +1,350 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_paths::{asset_paths, set_asset_path},
+    format::{
+        loader::PrefabSceneLoader,
+        split::{is_split_scene_file, parts_dir, split_scene, write_scene_parts, SplitScene},
+        SceneTree,
+    },
+    import_settings::meta_path,
+    save::{prefab_file_bytes, write_scene_file},
+    validate::scene_files,
+};
+
+/// Scene file with asset paths changed by an [`AssetRename`]
+#[derive(Clone, Debug)]
+pub struct RenamedScene {
+    pub file: PathBuf,
+    /// Number of changed asset paths
+    pub references: usize,
+    bytes: Vec<u8>,
+    /// Index and parts when the scene file is split
+    split: Option<SplitScene>,
+}
+
+/// Move of an asset file or folder together with the scene files referencing it.
+/// Made by [`plan_asset_rename`], nothing is changed on disk until [`AssetRename::apply`].
+#[derive(Clone, Debug)]
+pub struct AssetRename {
+    pub from: String,
+    pub to: String,
+    pub asset_root: PathBuf,
+    /// Scene files with changed asset paths, in path order
+    pub scenes: Vec<RenamedScene>,
+    /// Scene files that could not be loaded, so their references are not updated
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+/// Error of [`plan_asset_rename`] and [`AssetRename::apply`]
+#[derive(Debug)]
+pub enum AssetRenameError {
+    /// Source or target path is empty or outside of the asset folder
+    InvalidPath(String),
+    /// Target path is already used by another file
+    TargetExists(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for AssetRenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPath(path) => write!(f, "Invalid asset path {path:?}"),
+            Self::TargetExists(path) => write!(f, "Asset {path} already exists"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for AssetRenameError {}
+
+impl From<io::Error> for AssetRenameError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// New asset path for `path` when `from` (a file or folder) is moved to `to`.
+/// Labels like `#Scene0` are kept. Returns `None` for paths outside of `from`.
+pub fn renamed_asset_path(path: &str, from: &str, to: &str) -> Option<String> {
+    let file = path.split('#').next().unwrap_or_default();
+    let label = &path[file.len()..];
+    let rest = file.strip_prefix(from)?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    Some(format!("{to}{rest}{label}"))
+}
+
+fn normalize_asset_path(path: &str) -> Result<String, AssetRenameError> {
+    let normalized = path.replace('\\', "/").trim_matches('/').to_string();
+    if normalized.is_empty()
+        || normalized.contains("://")
+        || normalized.contains('#')
+        || normalized.split('/').any(|part| part == "..")
+    {
+        return Err(AssetRenameError::InvalidPath(path.to_string()));
+    }
+    Ok(normalized)
+}
+
+/// Find asset paths in scene files of `asset_root` that change when `from` is moved to `to`.
+///
+/// Scenes are read with types registered in the `world`
+/// and encoded with its [`SaveConfig`](crate::save::SaveConfig), like saved scenes.
+pub fn plan_asset_rename(
+    world: &mut World,
+    asset_root: impl AsRef<Path>,
+    from: &str,
+    to: &str,
+) -> Result<AssetRename, AssetRenameError> {
+    let asset_root = asset_root.as_ref().to_path_buf();
+    let from = normalize_asset_path(from)?;
+    let to = normalize_asset_path(to)?;
+    if asset_root.join(&to).exists() {
+        return Err(AssetRenameError::TargetExists(to));
+    }
+
+    let loader = PrefabSceneLoader::from_world(world);
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let mut scenes = vec![];
+    let mut errors = vec![];
+    for file in scene_files(&asset_root)? {
+        let mut scene = match loader.load_from_file(&file) {
+            Ok(scene) => scene,
+            Err(e) => {
+                errors.push((file, e.to_string()));
+                continue;
+            }
+        };
+        let mut references = 0;
+        {
+            let registry = registry.read();
+            for entity in scene.entities.iter_mut() {
+                for component in entity.components.iter_mut() {
+                    for (field, path) in asset_paths(component.as_ref(), &registry) {
+                        if let Some(new_path) = renamed_asset_path(&path, &from, &to) {
+                            if set_asset_path(component.as_mut(), field, &new_path) {
+                                references += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if references == 0 {
+            continue;
+        }
+        let bytes = match prefab_file_bytes(world, &scene, &file.to_string_lossy()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                errors.push((file, e));
+                continue;
+            }
+        };
+        let split = if is_split_scene_file(&file) {
+            match SceneTree::parse(&String::from_utf8_lossy(&bytes)) {
+                Ok(tree) => Some(split_scene(&tree, &registry.read())),
+                Err(e) => {
+                    errors.push((file, e.to_string()));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        scenes.push(RenamedScene {
+            file,
+            references,
+            bytes,
+            split,
+        });
+    }
+    Ok(AssetRename {
+        from,
+        to,
+        asset_root,
+        scenes,
+        errors,
+    })
+}
+
+impl AssetRename {
+    /// Whether the asset file or folder exists and is moved by [`Self::apply`].
+    /// When it was already moved by hand only the scene files are rewritten.
+    pub fn moves_asset(&self) -> bool {
+        self.asset_root.join(&self.from).exists()
+    }
+
+    /// Number of changed asset paths in all scene files
+    pub fn references(&self) -> usize {
+        self.scenes.iter().map(|scene| scene.references).sum()
+    }
+
+    /// Path of the scene file after the move, scenes inside the moved folder move with it
+    pub fn new_scene_path(&self, file: &Path) -> PathBuf {
+        let source = self.asset_root.join(&self.from);
+        file.strip_prefix(&source).map_or_else(
+            |_| file.to_path_buf(),
+            |rest| self.asset_root.join(&self.to).join(rest),
+        )
+    }
+
+    /// Move the asset and write the changed scene files, keeping `backups` of them
+    pub fn apply(&self, backups: usize) -> Result<(), AssetRenameError> {
+        let source = self.asset_root.join(&self.from);
+        let target = self.asset_root.join(&self.to);
+        if source.exists() {
+            if target.exists() {
+                return Err(AssetRenameError::TargetExists(self.to.clone()));
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&source, &target)?;
+            // Parts of a split scene are stored next to its index file
+            let parts = parts_dir(&source);
+            if target.is_file() && parts.is_dir() {
+                fs::rename(&parts, parts_dir(&target))?;
+            }
+            // Import settings are stored next to the asset
+            let meta = meta_path(&self.asset_root, &self.from);
+            if target.is_file() && meta.is_file() {
+                fs::rename(&meta, meta_path(&self.asset_root, &self.to))?;
+            }
+        }
+        for scene in self.scenes.iter() {
+            let file = self.new_scene_path(&scene.file);
+            match &scene.split {
+                Some(split) => write_scene_parts(&file, split, backups)?,
+                None => write_scene_file(&file, &scene.bytes, backups)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AssetRename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.moves_asset() {
+            writeln!(f, "Move {} -> {}", self.from, self.to)?;
+        }
+        for scene in self.scenes.iter() {
+            writeln!(
+                f,
+                "Update {} ({} references)",
+                scene.file.display(),
+                scene.references
+            )?;
+        }
+        for (file, error) in self.errors.iter() {
+            writeln!(f, "Skip {}: {error}", file.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::{
+        asset_paths::ReflectAssetPaths, component::SpriteTexture, format::migration::SceneVersion,
+    };
+
+    fn world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<SpriteTexture>();
+            registry.register::<SceneVersion>();
+            registry
+                .get_mut(std::any::TypeId::of::<SpriteTexture>())
+                .unwrap()
+                .insert(ReflectAssetPaths {
+                    fields: vec!["texture"],
+                });
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    fn sprite_scene(texture: &str) -> String {
+        let mut world = world();
+        world.spawn(SpriteTexture {
+            texture: texture.to_string(),
+        });
+        let registry = world.resource::<AppTypeRegistry>().read();
+        DynamicScene::from_world(&world)
+            .serialize(&registry)
+            .unwrap()
+    }
+
+    #[test]
+    fn moves_asset_folder_and_rewrites_scenes() {
+        let dir = TempDir::new("rename");
+        fs::create_dir_all(dir.join("textures")).unwrap();
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        fs::write(dir.join("textures/hero.png"), "png").unwrap();
+        let other = sprite_scene("textures_old/hero.png");
+        fs::write(
+            dir.join("scenes/hero.scn.ron"),
+            sprite_scene("textures/hero.png"),
+        )
+        .unwrap();
+        fs::write(dir.join("scenes/other.scn.ron"), &other).unwrap();
+
+        let mut world = world();
+        let rename = plan_asset_rename(&mut world, &dir, "textures/", "art/textures").unwrap();
+        assert!(rename.moves_asset());
+        assert!(rename.errors.is_empty(), "{:?}", rename.errors);
+        assert_eq!(rename.scenes.len(), 1, "{rename}");
+        assert_eq!(rename.scenes[0].file, dir.join("scenes/hero.scn.ron"));
+        assert_eq!(rename.references(), 1);
+        assert!(
+            dir.join("textures/hero.png").is_file(),
+            "planning does not change files"
+        );
+
+        rename.apply(0).unwrap();
+        assert!(dir.join("art/textures/hero.png").is_file());
+        assert!(!dir.join("textures").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("scenes/other.scn.ron")).unwrap(),
+            other
+        );
+        let scene = PrefabSceneLoader::from_world(&mut world)
+            .load_from_file(dir.join("scenes/hero.scn.ron"))
+            .unwrap();
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let paths = scene
+            .entities
+            .iter()
+            .flat_map(|entity| entity.components.iter())
+            .flat_map(|component| asset_paths(component.as_ref(), &registry))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![("texture", "art/textures/hero.png".to_string())]
+        );
+    }
+
+    #[test]
+    fn renamed_asset_path_keeps_labels() {
+        assert_eq!(
+            renamed_asset_path("models/a.glb#Scene0", "models/a.glb", "props/a.glb").as_deref(),
+            Some("props/a.glb#Scene0")
+        );
+        assert_eq!(
+            renamed_asset_path("models/a.glb", "models", "props").as_deref(),
+            Some("props/a.glb")
+        );
+        assert_eq!(renamed_asset_path("models2/a.glb", "models", "props"), None);
+    }
+}
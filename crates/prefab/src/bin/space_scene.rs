@@ -0,0 +1,17 @@
+//! Command line tool for scene files, see [`space_prefab::cli`]
+
+use std::process::ExitCode;
+
+use bevy::prelude::*;
+use space_prefab::{cli::run_scene_cli, plugins::BasePrefabPlugin};
+
+fn main() -> ExitCode {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        bevy::state::app::StatesPlugin,
+        BasePrefabPlugin,
+    ));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    run_scene_cli(&mut app, &args)
+}
@@ -0,0 +1,196 @@
+use std::process::ExitCode;
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_paths::asset_files,
+    asset_rename::plan_asset_rename,
+    diff::diff_scenes,
+    format::loader::PrefabSceneLoader,
+    merge::merge_scenes,
+    save::{configured_backups, prefab_file_bytes, write_scene_file, ASSETS_DIR},
+    unused_assets::used_assets,
+    validate::validate_scenes,
+};
+
+const USAGE: &str = "Usage:
+  space_scene diff <old.scn.ron> <new.scn.ron>    Show changed entities, components and fields
+  space_scene merge <base> <ours> <theirs>        Three-way merge into <ours>, usable as git merge driver
+  space_scene convert <input> <output>            Convert scene between .scn.ron and .scn.bin by output extension
+  space_scene validate <dir> [<assets dir>]       Check every scene in <dir>, print JSON report
+  space_scene rename <from> <to> [<assets dir>] [--dry-run]
+                                                  Move asset file or folder and update all scenes
+  space_scene unused [<assets dir>]               List asset files not used by any scene";
+
+/// Run scene command line tool with `args` (without the program name).
+///
+/// Types registered in the `app` are used to read the scene files,
+/// so a game can build its own tool binary with all of its types registered.
+/// Components of other types are compared by their RON text.
+pub fn run_scene_cli(app: &mut App, args: &[String]) -> ExitCode {
+    let loader = PrefabSceneLoader::from_world(app.world_mut());
+    let load = |path: &str| {
+        loader
+            .load_from_file(path)
+            .map_err(|e| format!("Failed to load {path}: {e}"))
+    };
+
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["diff", old, new] => {
+            let scenes = load(old).and_then(|old| load(new).map(|new| (old, new)));
+            let (old, new) = match scenes {
+                Ok(scenes) => scenes,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::from(2);
+                }
+            };
+            let registry = app.world().resource::<AppTypeRegistry>().read();
+            let diff = diff_scenes(&old, &new, &registry);
+            print!("{diff}");
+            // Same exit codes as `diff`: 1 when the scenes differ
+            if diff.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        ["merge", base, ours, theirs] => {
+            let read = |path: &str| {
+                std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))
+            };
+            let merged = read(base).and_then(|base| {
+                let ours_text = read(ours)?;
+                let theirs_text = read(theirs)?;
+                merge_scenes(app.world_mut(), &base, &ours_text, &theirs_text)
+                    .map_err(|e| format!("Failed to merge {ours}: {e}"))
+            });
+            let merged = match merged {
+                Ok(merged) => merged,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::from(2);
+                }
+            };
+            if let Err(e) = write_scene_file(ours, merged.text.as_bytes(), 0) {
+                eprintln!("Failed to write {ours}: {e}");
+                return ExitCode::from(2);
+            }
+            for conflict in merged.conflicts.iter() {
+                println!("CONFLICT {conflict}");
+            }
+            // Git keeps the file as conflicted when the merge driver fails
+            if merged.conflicts.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        ["convert", input, output] => {
+            // Scenes are migrated on load, so the output has the current version
+            let bytes = load(input).and_then(|scene| {
+                prefab_file_bytes(app.world(), &scene, output)
+                    .map_err(|e| format!("Failed to convert {input}: {e}"))
+            });
+            let bytes = match bytes {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::from(2);
+                }
+            };
+            if let Err(e) = write_scene_file(output, &bytes, 0) {
+                eprintln!("Failed to write {output}: {e}");
+                return ExitCode::from(2);
+            }
+            ExitCode::SUCCESS
+        }
+        ["validate", dir, rest @ ..] if rest.len() <= 1 => {
+            let asset_root = rest.first().copied().unwrap_or(ASSETS_DIR);
+            let report = match validate_scenes(app.world_mut(), dir, asset_root) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Failed to read {dir}: {e}");
+                    return ExitCode::from(2);
+                }
+            };
+            println!("{}", report.to_json());
+            for issue in report.issues.iter() {
+                eprintln!("{issue}");
+            }
+            eprintln!(
+                "Checked {} scenes, found {} issues",
+                report.scenes,
+                report.issues.len()
+            );
+            if report.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        ["rename", from, to, rest @ ..] if rest.len() <= 2 => {
+            let dry_run = rest.contains(&"--dry-run");
+            let dirs = rest
+                .iter()
+                .copied()
+                .filter(|arg| *arg != "--dry-run")
+                .collect::<Vec<_>>();
+            if dirs.len() > 1 || dirs.iter().any(|dir| dir.starts_with("--")) {
+                eprintln!("{USAGE}");
+                return ExitCode::from(2);
+            }
+            let asset_root = dirs.first().copied().unwrap_or(ASSETS_DIR);
+            let rename = match plan_asset_rename(app.world_mut(), asset_root, from, to) {
+                Ok(rename) => rename,
+                Err(e) => {
+                    eprintln!("Failed to rename {from}: {e}");
+                    return ExitCode::from(2);
+                }
+            };
+            print!("{rename}");
+            if dry_run {
+                return ExitCode::SUCCESS;
+            }
+            if let Err(e) = rename.apply(configured_backups(app.world())) {
+                eprintln!("Failed to rename {from}: {e}");
+                return ExitCode::from(2);
+            }
+            ExitCode::SUCCESS
+        }
+        ["unused", rest @ ..] if rest.len() <= 1 => {
+            let asset_root = rest.first().copied().unwrap_or(ASSETS_DIR);
+            let used = match used_assets(app.world_mut(), asset_root) {
+                Ok(used) => used,
+                Err(e) => {
+                    eprintln!("Failed to read {asset_root}: {e}");
+                    return ExitCode::from(2);
+                }
+            };
+            for (file, error) in used.errors.iter() {
+                eprintln!("Skipped {}: {error}", file.display());
+            }
+            let files = asset_files(asset_root);
+            let unused = used.unused(files.iter().map(String::as_str));
+            for path in unused.iter() {
+                println!("{path}");
+            }
+            eprintln!(
+                "Checked {} scenes, found {} unused of {} assets",
+                used.scenes,
+                unused.len(),
+                files.len()
+            );
+            if unused.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use space_shared::PrefabMarker;
+use uuid::Uuid;
+
+use crate::load::PrefabAutoChild;
+
+/// Persistent identifier of a prefab entity.
+/// Assigned automatically to every [`PrefabMarker`] entity and kept across save and load.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[reflect(Component, Default)]
+pub struct PrefabGuid(pub Uuid);
+
+impl Default for PrefabGuid {
+    fn default() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// Link to an entity by its [`PrefabGuid`].
+///
+/// Unlike [`EntityLink`](super::EntityLink) it survives save/load and can point into another prefab.
+/// Resolve it with [`GuidResolver`] once both prefabs are loaded.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Default)]
+pub struct GuidLink {
+    /// Guid of the [`PrefabLoader`](crate::load::PrefabLoader) entity whose prefab contains the target.
+    /// `None` searches the whole world.
+    pub instance: Option<Uuid>,
+    /// Guid of the target entity
+    pub target: Option<Uuid>,
+}
+
+impl GuidLink {
+    pub const fn new(target: Uuid) -> Self {
+        Self {
+            instance: None,
+            target: Some(target),
+        }
+    }
+
+    pub const fn in_instance(instance: Uuid, target: Uuid) -> Self {
+        Self {
+            instance: Some(instance),
+            target: Some(target),
+        }
+    }
+}
+
+/// Index of all entities with [`PrefabGuid`]. Entities of prefabs spawned several times share guids,
+/// so one guid can map to several entities.
+#[derive(Resource, Default)]
+pub struct GuidIndex {
+    entities: HashMap<Uuid, Vec<Entity>>,
+    guids: HashMap<Entity, Uuid>,
+}
+
+impl GuidIndex {
+    /// All entities with this guid
+    pub fn get(&self, guid: &Uuid) -> &[Entity] {
+        self.entities.get(guid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Guid of the entity, if it has one
+    pub fn guid(&self, entity: Entity) -> Option<Uuid> {
+        self.guids.get(&entity).copied()
+    }
+
+    fn insert(&mut self, entity: Entity, guid: Uuid) {
+        self.remove(entity);
+        self.entities.entry(guid).or_default().push(entity);
+        self.guids.insert(entity, guid);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(guid) = self.guids.remove(&entity) else {
+            return;
+        };
+        if let Some(entities) = self.entities.get_mut(&guid) {
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.entities.remove(&guid);
+            }
+        }
+    }
+}
+
+/// System param to resolve [`GuidLink`] into entities
+#[derive(SystemParam)]
+pub struct GuidResolver<'w, 's> {
+    index: Res<'w, GuidIndex>,
+    parents: Query<'w, 's, &'static Parent>,
+}
+
+impl<'w, 's> GuidResolver<'w, 's> {
+    /// Returns `None` until the target (and the instance, if set) is spawned
+    pub fn resolve(&self, link: &GuidLink) -> Option<Entity> {
+        let candidates = self.index.get(link.target.as_ref()?);
+        let Some(instance) = link.instance else {
+            return candidates.first().copied();
+        };
+        let instances = self.index.get(&instance);
+        candidates.iter().copied().find(|candidate| {
+            self.parents
+                .iter_ancestors(*candidate)
+                .any(|ancestor| instances.contains(&ancestor))
+        })
+    }
+
+    pub fn index(&self) -> &GuidIndex {
+        &self.index
+    }
+}
+
+/// Give a guid to every prefab entity that does not have one yet
+pub fn assign_prefab_guid(
+    mut commands: Commands,
+    query: Query<Entity, (With<PrefabMarker>, Without<PrefabGuid>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(PrefabGuid::default());
+    }
+}
+
+/// Keep [`GuidIndex`] in sync and give new guids to copies of scene entities (for example after cloning)
+pub fn update_guid_index(
+    mut commands: Commands,
+    mut index: ResMut<GuidIndex>,
+    mut removed: RemovedComponents<PrefabGuid>,
+    changed: Query<(Entity, &PrefabGuid), Changed<PrefabGuid>>,
+    scene_entities: Query<(), (With<PrefabMarker>, Without<PrefabAutoChild>)>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+
+    for (entity, guid) in changed.iter() {
+        let duplicate = scene_entities.contains(entity)
+            && index
+                .get(&guid.0)
+                .iter()
+                .any(|other| *other != entity && scene_entities.contains(*other));
+        if duplicate {
+            index.remove(entity);
+            commands.entity(entity).insert(PrefabGuid::default());
+        } else {
+            index.insert(entity, guid.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<GuidIndex>().add_systems(
+            Update,
+            (
+                assign_prefab_guid,
+                apply_deferred,
+                update_guid_index,
+                apply_deferred,
+            )
+                .chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn assigns_guid_to_prefab_entities() {
+        let mut app = app();
+        let guid = PrefabGuid::default();
+        let with_guid = app.world_mut().spawn((PrefabMarker, guid)).id();
+        let without_guid = app.world_mut().spawn(PrefabMarker).id();
+        let not_prefab = app.world_mut().spawn_empty().id();
+
+        app.update();
+
+        assert_eq!(app.world().get::<PrefabGuid>(with_guid), Some(&guid));
+        assert!(app.world().get::<PrefabGuid>(without_guid).is_some());
+        assert!(app.world().get::<PrefabGuid>(not_prefab).is_none());
+        assert_eq!(
+            app.world().resource::<GuidIndex>().get(&guid.0),
+            &[with_guid]
+        );
+    }
+
+    #[test]
+    fn regenerates_duplicated_guid() {
+        let mut app = app();
+        let guid = PrefabGuid::default();
+        let original = app.world_mut().spawn((PrefabMarker, guid)).id();
+        app.update();
+
+        let copy = app.world_mut().spawn((PrefabMarker, guid)).id();
+        app.update();
+        app.update();
+
+        assert_eq!(app.world().get::<PrefabGuid>(original), Some(&guid));
+        assert_ne!(app.world().get::<PrefabGuid>(copy), Some(&guid));
+        assert_eq!(
+            app.world().resource::<GuidIndex>().get(&guid.0),
+            &[original]
+        );
+    }
+
+    #[test]
+    fn guid_survives_save_and_load() {
+        let mut app = App::new();
+        app.register_type::<Uuid>().register_type::<PrefabGuid>();
+        let guid = PrefabGuid::default();
+        let entity = app.world_mut().spawn(guid).id();
+
+        let scene = DynamicSceneBuilder::from_world(app.world())
+            .extract_entity(entity)
+            .build();
+        let text = scene
+            .serialize(&app.world().resource::<AppTypeRegistry>().read())
+            .unwrap();
+        assert!(text.contains(&guid.0.to_string()));
+
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let loaded = loader.load_from_str(&text).unwrap();
+        let component = loaded.entities[0].components[0].as_ref();
+        assert_eq!(PrefabGuid::from_reflect(component), Some(guid));
+    }
+
+    #[test]
+    fn resolves_link_inside_instance() {
+        let mut app = app();
+        let target = PrefabGuid::default();
+        let instance_a = PrefabGuid::default();
+        let instance_b = PrefabGuid::default();
+
+        // Same prefab spawned twice, so its entities share guid
+        let mut targets = vec![];
+        for instance in [instance_a, instance_b] {
+            let child = app.world_mut().spawn((PrefabAutoChild, target)).id();
+            app.world_mut()
+                .spawn((PrefabMarker, instance))
+                .add_child(child);
+            targets.push(child);
+        }
+        app.update();
+
+        let mut system_state: bevy::ecs::system::SystemState<GuidResolver> =
+            bevy::ecs::system::SystemState::new(app.world_mut());
+        let resolver = system_state.get(app.world());
+
+        assert_eq!(
+            resolver.resolve(&GuidLink::in_instance(instance_b.0, target.0)),
+            Some(targets[1])
+        );
+        assert_eq!(
+            resolver.resolve(&GuidLink::in_instance(instance_a.0, target.0)),
+            Some(targets[0])
+        );
+        assert_eq!(resolver.resolve(&GuidLink::default()), None);
+    }
+}
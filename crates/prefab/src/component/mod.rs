@@ -18,6 +18,14 @@ pub use camera::*;
 pub mod player_start;
 pub use player_start::*;
 
+/// Module contatins persistent entity guids and links between them
+pub mod guid;
+pub use guid::*;
+
+/// Module contatins markers to exclude entities from saved scenes and game builds
+pub mod save_filter;
+pub use save_filter::*;
+
 /// NOT USED. Planned to be used in future for auto structs
 pub mod path;
 
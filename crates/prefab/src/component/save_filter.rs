@@ -0,0 +1,77 @@
+use bevy::{prelude::*, scene::scene_spawner_system};
+
+use crate::save::ChildrenPrefab;
+
+/// Entity is not written to saved scenes. With `recursive` its children are skipped too,
+/// otherwise they are saved as scene roots
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component, Default)]
+pub struct NoSave {
+    pub recursive: bool,
+}
+
+/// Entity is saved with the scene but exists only in the editor.
+/// Games remove it together with its children on load with [`StripEditorOnlyPlugin`]
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component, Default)]
+pub struct EditorOnly;
+
+/// Despawns [`EditorOnly`] entities of spawned scenes, for game builds
+pub struct StripEditorOnlyPlugin;
+
+impl Plugin for StripEditorOnlyPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.register_type::<EditorOnly>();
+        app.add_systems(SpawnScene, strip_editor_only.after(scene_spawner_system));
+    }
+}
+
+fn strip_editor_only(
+    mut commands: Commands,
+    query: Query<Entity, Added<EditorOnly>>,
+    saved_children: Query<&ChildrenPrefab>,
+) {
+    // Hierarchy of freshly spawned scene is restored from `ChildrenPrefab` later
+    let mut stack = query.iter().collect::<Vec<_>>();
+    while let Some(entity) = stack.pop() {
+        if let Ok(children) = saved_children.get(entity) {
+            stack.extend(children.0.iter().copied());
+        }
+        if let Some(entity) = commands.get_entity(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_only_entities_are_stripped() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            HierarchyPlugin,
+            bevy::scene::ScenePlugin,
+            StripEditorOnlyPlugin,
+        ));
+        let world = app.world_mut();
+        let gizmo_child = world.spawn_empty().id();
+        let pending_child = world.spawn_empty().id();
+        let gizmo = world
+            .spawn((EditorOnly, ChildrenPrefab(vec![pending_child])))
+            .add_child(gizmo_child)
+            .id();
+        let game = world.spawn(Name::new("game")).id();
+
+        app.update();
+        let world = app.world();
+        assert!(world.get_entity(gizmo).is_none());
+        assert!(world.get_entity(gizmo_child).is_none());
+        assert!(world.get_entity(pending_child).is_none());
+        assert!(world.get_entity(game).is_some());
+    }
+}
@@ -0,0 +1,569 @@
+use std::fmt;
+
+use bevy::{
+    prelude::*,
+    reflect::{serde::TypedReflectSerializer, TypePath, TypeRegistry},
+    scene::DynamicEntity,
+    utils::HashMap,
+};
+
+use crate::{
+    component::PrefabGuid,
+    format::{
+        ron_tree::RonValue,
+        unknown::{UnknownComponent, UnknownComponents},
+    },
+    save::ChildrenPrefab,
+};
+
+/// Differences between two versions of a scene
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SceneDiff {
+    /// Changed scene resources
+    pub resources: Vec<ComponentDiff>,
+    pub entities: Vec<EntityDiff>,
+}
+
+/// Change of one entity, identified by the path of names from its root entity
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityDiff {
+    pub path: String,
+    pub change: EntityChange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntityChange {
+    Added,
+    Removed,
+    Changed {
+        /// Previous path, when entity was renamed or moved to another parent
+        moved_from: Option<String>,
+        components: Vec<ComponentDiff>,
+    },
+}
+
+/// Change of a component or resource, identified by its type path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentDiff {
+    pub type_path: String,
+    pub change: ComponentChange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ComponentChange {
+    Added,
+    Removed,
+    Changed(Vec<FieldChange>),
+}
+
+/// Changed value of a field, `None` when the field is missing in that version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Path of the field inside of the component, like `translation.x`. Empty for the whole value.
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl SceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty() && self.entities.is_empty()
+    }
+}
+
+/// `bevy_transform::components::transform::Transform` -> `Transform`
+pub fn short_type_path(type_path: &str) -> &str {
+    let end = type_path.find('<').unwrap_or(type_path.len());
+    let start = type_path[..end].rfind("::").map_or(0, |i| i + 2);
+    &type_path[start..]
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = if self.field.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", self.field)
+        };
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{field}: {old} -> {new}"),
+            (None, Some(new)) => write!(f, "{field}: added {new}"),
+            (Some(old), None) => write!(f, "{field}: removed {old}"),
+            (None, None) => write!(f, "{field}"),
+        }
+    }
+}
+
+impl fmt::Display for ComponentDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = short_type_path(&self.type_path);
+        match &self.change {
+            ComponentChange::Added => writeln!(f, "    + {name}"),
+            ComponentChange::Removed => writeln!(f, "    - {name}"),
+            ComponentChange::Changed(fields) => {
+                for field in fields {
+                    writeln!(f, "    ~ {name}{field}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SceneDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.resources.is_empty() {
+            writeln!(f, "~ scene settings")?;
+            for resource in self.resources.iter() {
+                write!(f, "{resource}")?;
+            }
+        }
+        for entity in self.entities.iter() {
+            match &entity.change {
+                EntityChange::Added => writeln!(f, "+ {}", entity.path)?,
+                EntityChange::Removed => writeln!(f, "- {}", entity.path)?,
+                EntityChange::Changed {
+                    moved_from,
+                    components,
+                } => {
+                    match moved_from {
+                        Some(old) => writeln!(f, "~ {} (moved from {old})", entity.path)?,
+                        None => writeln!(f, "~ {}", entity.path)?,
+                    }
+                    for component in components {
+                        write!(f, "{component}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare two scenes by entity names and [`PrefabGuid`], component type paths and field values.
+///
+/// Entities with the same guid are matched even if they were renamed or moved,
+/// other entities are matched by their path of names. Components are compared by their
+/// serialized values, so components of types unknown to this build are compared too.
+pub fn diff_scenes(old: &DynamicScene, new: &DynamicScene, registry: &TypeRegistry) -> SceneDiff {
+    let old_entities = scene_entities(old, registry);
+    let new_entities = scene_entities(new, registry);
+
+    let mut entities = vec![];
+    let mut matched = vec![false; old_entities.len()];
+    for entity in new_entities.iter() {
+        let found = old_entities
+            .iter()
+            .enumerate()
+            .find(|(index, old)| !matched[*index] && old.key == entity.key)
+            .or_else(|| {
+                old_entities.iter().enumerate().find(|(index, old)| {
+                    !matched[*index] && old.guid.is_none() && old.path == entity.path
+                })
+            });
+        let Some((index, old)) = found else {
+            entities.push(EntityDiff {
+                path: entity.path.clone(),
+                change: EntityChange::Added,
+            });
+            continue;
+        };
+        matched[index] = true;
+        let components = diff_components(&old.components, &entity.components);
+        let moved_from = (old.path != entity.path).then(|| old.path.clone());
+        if moved_from.is_some() || !components.is_empty() {
+            entities.push(EntityDiff {
+                path: entity.path.clone(),
+                change: EntityChange::Changed {
+                    moved_from,
+                    components,
+                },
+            });
+        }
+    }
+    for (index, old) in old_entities.iter().enumerate() {
+        if !matched[index] {
+            entities.push(EntityDiff {
+                path: old.path.clone(),
+                change: EntityChange::Removed,
+            });
+        }
+    }
+    entities.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let resources = diff_components(
+        &values(old.resources.iter().map(AsRef::as_ref), registry),
+        &values(new.resources.iter().map(AsRef::as_ref), registry),
+    );
+    SceneDiff {
+        resources,
+        entities,
+    }
+}
+
+struct SceneEntity {
+    /// Guid or path, used to match entities of both scenes
+    key: String,
+    guid: Option<String>,
+    path: String,
+    components: Vec<(String, RonValue)>,
+}
+
+pub(crate) fn component<T: FromReflect + TypePath>(entity: &DynamicEntity) -> Option<T> {
+    entity
+        .components
+        .iter()
+        .find(|component| {
+            component
+                .get_represented_type_info()
+                .is_some_and(|info| info.type_path() == T::type_path())
+        })
+        .and_then(|component| T::from_reflect(component.as_ref()))
+}
+
+fn scene_entities(scene: &DynamicScene, registry: &TypeRegistry) -> Vec<SceneEntity> {
+    let mut parents = HashMap::new();
+    for entity in scene.entities.iter() {
+        if let Some(children) = component::<ChildrenPrefab>(entity) {
+            for child in children.0 {
+                parents.insert(child, entity.entity);
+            }
+        }
+    }
+    let names = scene
+        .entities
+        .iter()
+        .map(|entity| {
+            // Unnamed entities are named by guid, entity ids differ between saves
+            let name = component::<Name>(entity).map_or_else(
+                || {
+                    component::<PrefabGuid>(entity).map_or_else(
+                        || format!("Entity {}", entity.entity.index()),
+                        |guid| format!("Entity {}", &guid.0.simple().to_string()[..8]),
+                    )
+                },
+                |name| name.to_string(),
+            );
+            (entity.entity, name)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut paths = HashMap::<String, usize>::new();
+    scene
+        .entities
+        .iter()
+        .map(|entity| {
+            let mut path = vec![names[&entity.entity].clone()];
+            let mut current = entity.entity;
+            while let Some(parent) = parents.get(&current) {
+                if path.len() > scene.entities.len() {
+                    break;
+                }
+                path.push(names.get(parent).cloned().unwrap_or_default());
+                current = *parent;
+            }
+            path.reverse();
+            let mut path = path.join("/");
+            // Siblings with the same name get numbered paths
+            let count = paths.entry(path.clone()).or_default();
+            *count += 1;
+            if *count > 1 {
+                path = format!("{path} #{count}");
+            }
+
+            let guid = component::<PrefabGuid>(entity).map(|guid| guid.0.to_string());
+            let components = entity
+                .components
+                .iter()
+                .filter(|component| {
+                    let type_path = component
+                        .get_represented_type_info()
+                        .map(|info| info.type_path());
+                    type_path != Some(ChildrenPrefab::type_path())
+                        && type_path != Some(PrefabGuid::type_path())
+                })
+                .map(AsRef::as_ref);
+            SceneEntity {
+                key: guid.clone().unwrap_or_else(|| path.clone()),
+                guid,
+                path,
+                components: values(components, registry),
+            }
+        })
+        .collect()
+}
+
+/// Serialized values by type path. Unknown components are unpacked into their original type paths.
+fn values<'a>(
+    items: impl Iterator<Item = &'a dyn Reflect>,
+    registry: &TypeRegistry,
+) -> Vec<(String, RonValue)> {
+    let mut values = vec![];
+    for item in items {
+        let Some(type_path) = item
+            .get_represented_type_info()
+            .map(|info| info.type_path().to_string())
+        else {
+            continue;
+        };
+        if type_path == UnknownComponents::type_path() {
+            let unknown = UnknownComponents::from_reflect(item).unwrap_or_default();
+            for UnknownComponent { type_path, ron } in unknown.0 {
+                let value = RonValue::parse(&ron).unwrap_or(RonValue::Literal(ron));
+                values.push((type_path, value));
+            }
+            continue;
+        }
+        let value = ron::to_string(&TypedReflectSerializer::new(item, registry))
+            .ok()
+            .and_then(|text| RonValue::parse(&text).ok())
+            .unwrap_or_else(|| RonValue::Literal(format!("{item:?}")));
+        values.push((type_path, value));
+    }
+    values.sort_by(|(a, _), (b, _)| a.cmp(b));
+    values
+}
+
+fn diff_components(old: &[(String, RonValue)], new: &[(String, RonValue)]) -> Vec<ComponentDiff> {
+    let mut diffs = vec![];
+    for (type_path, value) in new.iter() {
+        let change = match old.iter().find(|(old_path, _)| old_path == type_path) {
+            None => ComponentChange::Added,
+            Some((_, old)) => {
+                let mut fields = vec![];
+                diff_values("", old, value, &mut fields);
+                if fields.is_empty() {
+                    continue;
+                }
+                ComponentChange::Changed(fields)
+            }
+        };
+        diffs.push(ComponentDiff {
+            type_path: type_path.clone(),
+            change,
+        });
+    }
+    for (type_path, _) in old.iter() {
+        if !new.iter().any(|(new_path, _)| new_path == type_path) {
+            diffs.push(ComponentDiff {
+                type_path: type_path.clone(),
+                change: ComponentChange::Removed,
+            });
+        }
+    }
+    diffs.sort_by(|a, b| a.type_path.cmp(&b.type_path));
+    diffs
+}
+
+fn join_field(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{path}.{field}")
+    }
+}
+
+fn diff_values(path: &str, old: &RonValue, new: &RonValue, out: &mut Vec<FieldChange>) {
+    let changed = |out: &mut Vec<FieldChange>, old: Option<&RonValue>, new: Option<&RonValue>| {
+        out.push(FieldChange {
+            field: path.to_string(),
+            old: old.map(RonValue::to_pretty_string),
+            new: new.map(RonValue::to_pretty_string),
+        });
+    };
+    match (old, new) {
+        (RonValue::Struct(old_name, old_fields), RonValue::Struct(new_name, new_fields))
+            if old_name == new_name =>
+        {
+            for (name, value) in new_fields.iter() {
+                let field = join_field(path, name);
+                match old_fields.iter().find(|(old_name, _)| old_name == name) {
+                    Some((_, old)) => diff_values(&field, old, value, out),
+                    None => out.push(FieldChange {
+                        field,
+                        old: None,
+                        new: Some(value.to_pretty_string()),
+                    }),
+                }
+            }
+            for (name, value) in old_fields.iter() {
+                if !new_fields.iter().any(|(new_name, _)| new_name == name) {
+                    out.push(FieldChange {
+                        field: join_field(path, name),
+                        old: Some(value.to_pretty_string()),
+                        new: None,
+                    });
+                }
+            }
+        }
+        (RonValue::Tuple(old_name, old_items), RonValue::Tuple(new_name, new_items))
+            if old_name == new_name && old_items.len() == new_items.len() =>
+        {
+            // Newtype wrappers like `Name("a")` are compared as their inner value
+            if old_items.len() == 1 && old_name.is_none() {
+                diff_values(path, &old_items[0], &new_items[0], out);
+                return;
+            }
+            for (index, (old, new)) in old_items.iter().zip(new_items.iter()).enumerate() {
+                diff_values(&join_field(path, &index.to_string()), old, new, out);
+            }
+        }
+        (RonValue::List(old_items), RonValue::List(new_items))
+            if old_items.len() == new_items.len() =>
+        {
+            for (index, (old, new)) in old_items.iter().zip(new_items.iter()).enumerate() {
+                diff_values(&format!("{path}[{index}]"), old, new, out);
+            }
+        }
+        (RonValue::Map(old_entries), RonValue::Map(new_entries)) => {
+            for (key, value) in new_entries.iter() {
+                let field = format!("{path}[{}]", key.to_pretty_string());
+                match old_entries.iter().find(|(old_key, _)| old_key == key) {
+                    Some((_, old)) => diff_values(&field, old, value, out),
+                    None => out.push(FieldChange {
+                        field,
+                        old: None,
+                        new: Some(value.to_pretty_string()),
+                    }),
+                }
+            }
+            for (key, value) in old_entries.iter() {
+                if !new_entries.iter().any(|(new_key, _)| new_key == key) {
+                    out.push(FieldChange {
+                        field: format!("{path}[{}]", key.to_pretty_string()),
+                        old: Some(value.to_pretty_string()),
+                        new: None,
+                    });
+                }
+            }
+        }
+        (old, new) if old != new => changed(out, Some(old), Some(new)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext::uuid::Uuid;
+
+    fn world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Name>();
+            registry.register::<Transform>();
+            registry.register::<ChildrenPrefab>();
+            registry.register::<PrefabGuid>();
+            registry.register::<Uuid>();
+            registry.register::<UnknownComponents>();
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    fn guid(n: u128) -> PrefabGuid {
+        PrefabGuid(Uuid::from_u128(n))
+    }
+
+    fn scene(world: &World) -> DynamicScene {
+        DynamicSceneBuilder::from_world(world)
+            .extract_entities(world.iter_entities().map(|entity| entity.id()))
+            .build()
+    }
+
+    #[test]
+    fn reports_entity_component_and_field_changes() {
+        let mut old = world();
+        let lamp = old
+            .spawn((guid(2), Name::new("lamp"), Transform::from_xyz(1., 0., 0.)))
+            .id();
+        old.spawn((guid(1), Name::new("room"), ChildrenPrefab(vec![lamp])));
+        old.spawn((guid(3), Name::new("tree")));
+
+        let mut new = world();
+        // shift entity ids, so matching does not depend on them
+        let placeholder = new.spawn_empty().id();
+        let lamp = new
+            .spawn((guid(2), Name::new("lamp"), Transform::from_xyz(2., 0., 0.)))
+            .id();
+        new.spawn((guid(1), Name::new("room"), ChildrenPrefab(vec![lamp])));
+        new.spawn((
+            guid(4),
+            Name::new("rock"),
+            UnknownComponents(vec![UnknownComponent {
+                type_path: "game::Loot".to_string(),
+                ron: "(gold: 5)".to_string(),
+            }]),
+        ));
+        new.despawn(placeholder);
+
+        let registry = old.resource::<AppTypeRegistry>().read();
+        let diff = diff_scenes(&scene(&old), &scene(&new), &registry);
+        assert_eq!(diff.entities.len(), 3, "{diff}");
+        assert_eq!(
+            diff.entities[0],
+            EntityDiff {
+                path: "rock".to_string(),
+                change: EntityChange::Added,
+            }
+        );
+        let EntityChange::Changed {
+            moved_from: None,
+            components,
+        } = &diff.entities[1].change
+        else {
+            panic!("{diff}");
+        };
+        assert_eq!(diff.entities[1].path, "room/lamp");
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].type_path, Transform::type_path());
+        let ComponentChange::Changed(fields) = &components[0].change else {
+            panic!("{diff}");
+        };
+        assert_eq!(fields.len(), 1, "{diff}");
+        assert!(fields[0].field.starts_with("translation"), "{diff}");
+        assert_eq!(diff.entities[2].change, EntityChange::Removed);
+        assert_eq!(diff.entities[2].path, "tree");
+
+        let text = diff.to_string();
+        assert!(text.contains("~ room/lamp"), "{text}");
+        assert!(text.contains("~ Transform.translation"), "{text}");
+        assert!(diff_scenes(&scene(&old), &scene(&old), &registry).is_empty());
+    }
+
+    #[test]
+    fn renamed_entity_is_matched_by_guid() {
+        let mut old = world();
+        old.spawn((guid(1), Name::new("door")));
+        let mut new = world();
+        new.spawn((guid(1), Name::new("gate")));
+
+        let registry = old.resource::<AppTypeRegistry>().read();
+        let diff = diff_scenes(&scene(&old), &scene(&new), &registry);
+        assert_eq!(diff.entities.len(), 1);
+        assert!(matches!(
+            &diff.entities[0].change,
+            EntityChange::Changed { moved_from: Some(old), components } if old == "door" && components.len() == 1
+        ));
+    }
+
+    #[test]
+    fn unknown_components_are_compared_by_value() {
+        let loot = |gold: u32| {
+            UnknownComponents(vec![UnknownComponent {
+                type_path: "game::Loot".to_string(),
+                ron: format!("(gold: {gold})"),
+            }])
+        };
+        let mut old = world();
+        old.spawn((guid(1), Name::new("chest"), loot(5)));
+        let mut new = world();
+        new.spawn((guid(1), Name::new("chest"), loot(7)));
+
+        let registry = old.resource::<AppTypeRegistry>().read();
+        let diff = diff_scenes(&scene(&old), &scene(&new), &registry);
+        assert_eq!(diff.to_string(), "~ chest\n    ~ Loot.gold: 5 -> 7\n");
+    }
+}
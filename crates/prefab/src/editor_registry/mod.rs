@@ -88,6 +88,22 @@ impl AddDefaultComponent {
     }
 }
 
+/// Container struct for function to reset resource to default in untyped style
+#[derive(Clone)]
+pub struct ResetResource {
+    func: Arc<dyn Fn(&mut World) + Send + Sync>,
+}
+
+impl ResetResource {
+    pub fn new<T: Default + Resource>() -> Self {
+        Self {
+            func: Arc::new(move |world| {
+                world.insert_resource(T::default());
+            }),
+        }
+    }
+}
+
 /// Container struct for function to send default event
 #[derive(Clone)]
 pub struct SendEvent {
@@ -135,7 +151,9 @@ pub struct EditorRegistry {
     pub clone_components: Vec<CloneComponent>,
     pub remove_components: HashMap<TypeId, RemoveComponent>,
     pub send_events: Vec<SendEvent>,
-    pub silent: HashSet<TypeId>, //skip in inspector ui
+    pub silent: HashSet<TypeId>,    //skip in inspector ui
+    pub transient: HashSet<TypeId>, //skip in saved scenes
+    pub scene_resources: HashMap<TypeId, ResetResource>,
 }
 
 impl EditorRegistry {
@@ -195,6 +213,40 @@ impl EditorRegistry {
         self.clone_components.push(CloneComponent::new::<T>());
     }
 
+    /// Mark registered component as runtime state, which is not saved in prefab
+    pub fn transient<T: Component>(&mut self) {
+        self.transient.insert(TypeId::of::<T>());
+    }
+
+    /// Register new resource, which will be saved in prefab and shown in scene settings
+    pub fn resource_register<T: Resource + Default>(&mut self) {
+        info!("Registering scene resource: {}", std::any::type_name::<T>());
+        self.scene_resources
+            .insert(TypeId::of::<T>(), ResetResource::new::<T>());
+    }
+
+    /// Types of resources written to saved scenes
+    pub fn saved_resources(&self) -> HashSet<TypeId> {
+        self.scene_resources.keys().copied().collect()
+    }
+
+    /// Reset scene resources to defaults before another scene is loaded
+    pub fn reset_scene_resources(&self, world: &mut World) {
+        for reset in self.scene_resources.values() {
+            (reset.func)(world);
+        }
+    }
+
+    /// Types of components written to saved scenes
+    pub fn saved_types(&self) -> HashSet<TypeId> {
+        self.registry
+            .read()
+            .iter()
+            .map(|registration| registration.type_id())
+            .filter(|type_id| !self.transient.contains(type_id))
+            .collect()
+    }
+
     /// Get spawn function for this component type
     pub fn get_spawn_command(&self, id: &TypeId) -> AddDefaultComponent {
         self.spawn_components.get(id).unwrap().clone()
@@ -247,6 +299,13 @@ pub trait EditorRegistryExt {
         &mut self,
     ) -> &mut Self;
 
+    /// register new component in editor UI, which is never saved in prefab (runtime or editor state)
+    fn editor_transient_registry<
+        T: Component + Default + Send + 'static + GetTypeRegistration + Reflect + FromReflect,
+    >(
+        &mut self,
+    ) -> &mut Self;
+
     /// Mark that if T component spawned, then Relation must be spawned too
     fn editor_relation<T, Relation>(&mut self) -> &mut Self
     where
@@ -278,6 +337,13 @@ pub trait EditorRegistryExt {
     >(
         &mut self,
     ) -> &mut Self;
+
+    /// register new resource which is saved with the scene and edited in scene settings
+    fn editor_resource_registry<
+        T: Resource + Default + Reflect + FromReflect + Send + 'static + GetTypeRegistration,
+    >(
+        &mut self,
+    ) -> &mut Self;
 }
 
 impl EditorRegistryExt for App {
@@ -316,6 +382,18 @@ impl EditorRegistryExt for App {
         self
     }
 
+    fn editor_transient_registry<
+        T: Component + Default + Send + 'static + GetTypeRegistration + Reflect + FromReflect,
+    >(
+        &mut self,
+    ) -> &mut Self {
+        self.editor_registry::<T>();
+        if let Some(mut registry) = self.world_mut().get_resource_mut::<EditorRegistry>() {
+            registry.transient::<T>();
+        }
+        self
+    }
+
     fn editor_silent_registry<
         T: Component + Reflect + FromReflect + Default + Send + 'static + GetTypeRegistration,
     >(
@@ -386,6 +464,30 @@ impl EditorRegistryExt for App {
         }
         self
     }
+
+    fn editor_resource_registry<
+        T: Resource + Default + Reflect + FromReflect + Send + 'static + GetTypeRegistration,
+    >(
+        &mut self,
+    ) -> &mut Self {
+        self.register_type::<T>();
+        if !self.world().contains_resource::<T>() {
+            self.init_resource::<T>();
+        }
+        if self
+            .world()
+            .resource::<AppTypeRegistry>()
+            .read()
+            .get_type_data::<ReflectResource>(TypeId::of::<T>())
+            .is_none()
+        {
+            warn!("Resource {} has no #[reflect(Resource)] attribute. It will not allow to be saved in prefab", std::any::type_name::<T>());
+        }
+        if let Some(mut registry) = self.world_mut().get_resource_mut::<EditorRegistry>() {
+            registry.resource_register::<T>();
+        }
+        self
+    }
 }
 
 fn into_sync_system<T: Component + Clone + Into<Target>, Target: Component>(
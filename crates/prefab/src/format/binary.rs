@@ -0,0 +1,241 @@
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use bevy::{
+    prelude::*,
+    reflect::TypeRegistry,
+    scene::serde::{SceneDeserializer, SceneSerializer},
+};
+use bincode::Options;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use super::{unknown::expand_unknown_components, SceneTree};
+
+/// First bytes of every binary scene file
+pub const BINARY_SCENE_MAGIC: &[u8; 6] = b"SPSCNB";
+/// Extension used for binary scene files
+pub const BINARY_SCENE_EXTENSION: &str = "scn.bin";
+
+/// Version 2 added the scene version to the header
+const BINARY_FORMAT_VERSION: u8 = 2;
+const FLAG_COMPRESSED: u8 = 1;
+
+/// Possible errors of binary scene encoding
+#[derive(Debug)]
+pub enum BinarySceneError {
+    /// File does not start with [`BINARY_SCENE_MAGIC`] or has unsupported format version
+    Header(String),
+    Compression(std::io::Error),
+    Encoding(bincode::Error),
+    Ron(ron::Error),
+}
+
+impl fmt::Display for BinarySceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header(e) => write!(f, "Invalid binary scene header: {e}"),
+            Self::Compression(e) => write!(f, "Failed to (de)compress binary scene: {e}"),
+            Self::Encoding(e) => write!(f, "Failed to encode binary scene: {e}"),
+            Self::Ron(e) => write!(f, "Failed to convert scene to RON: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BinarySceneError {}
+
+/// Check whether bytes contain a binary scene
+pub fn is_binary_scene(bytes: &[u8]) -> bool {
+    bytes.starts_with(BINARY_SCENE_MAGIC)
+}
+
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new().with_fixint_encoding()
+}
+
+/// Encode scene to binary format using reflection.
+/// `version` is the [`SceneVersion`](super::migration::SceneVersion) stored in the header.
+pub fn scene_to_binary(
+    scene: &DynamicScene,
+    registry: &TypeRegistry,
+    compressed: bool,
+    version: u32,
+) -> Result<Vec<u8>, BinarySceneError> {
+    let payload = bincode_options()
+        .serialize(&SceneSerializer::new(scene, registry))
+        .map_err(BinarySceneError::Encoding)?;
+
+    let mut bytes = BINARY_SCENE_MAGIC.to_vec();
+    bytes.push(BINARY_FORMAT_VERSION);
+    let flags = if compressed { FLAG_COMPRESSED } else { 0 };
+    bytes.push(flags);
+    bytes.extend(version.to_le_bytes());
+    if compressed {
+        let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+        encoder
+            .write_all(&payload)
+            .map_err(BinarySceneError::Compression)?;
+        encoder.finish().map_err(BinarySceneError::Compression)
+    } else {
+        bytes.extend(payload);
+        Ok(bytes)
+    }
+}
+
+/// Header fields of a binary scene: flags, scene version and the encoded payload
+fn read_header(bytes: &[u8]) -> Result<(u8, Option<u32>, &[u8]), BinarySceneError> {
+    let header_len = BINARY_SCENE_MAGIC.len() + 2;
+    if !is_binary_scene(bytes) || bytes.len() < header_len {
+        return Err(BinarySceneError::Header("missing magic bytes".to_string()));
+    }
+    let format_version = bytes[BINARY_SCENE_MAGIC.len()];
+    let flags = bytes[BINARY_SCENE_MAGIC.len() + 1];
+    match format_version {
+        // First format stored the version only as a scene resource
+        1 => Ok((flags, None, &bytes[header_len..])),
+        BINARY_FORMAT_VERSION => {
+            let version = bytes
+                .get(header_len..header_len + 4)
+                .and_then(|version| version.try_into().ok())
+                .map(u32::from_le_bytes)
+                .ok_or_else(|| BinarySceneError::Header("missing scene version".to_string()))?;
+            Ok((flags, Some(version), &bytes[header_len + 4..]))
+        }
+        version => Err(BinarySceneError::Header(format!(
+            "unsupported format version {version}"
+        ))),
+    }
+}
+
+/// [`SceneVersion`](super::migration::SceneVersion) stored in the header of a binary scene,
+/// `None` for files of the first binary format which only keep it as a scene resource
+pub fn binary_scene_version(bytes: &[u8]) -> Result<Option<u32>, BinarySceneError> {
+    read_header(bytes).map(|(_, version, _)| version)
+}
+
+/// Decode scene from binary format. Types must be registered with the same layout they were saved with.
+pub fn scene_from_binary(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+) -> Result<DynamicScene, BinarySceneError> {
+    let (flags, _, body) = read_header(bytes)?;
+
+    let decompressed;
+    let payload = if flags & FLAG_COMPRESSED != 0 {
+        let mut data = vec![];
+        DeflateDecoder::new(body)
+            .read_to_end(&mut data)
+            .map_err(BinarySceneError::Compression)?;
+        decompressed = data;
+        decompressed.as_slice()
+    } else {
+        body
+    };
+
+    bincode_options()
+        .deserialize_seed(
+            SceneDeserializer {
+                type_registry: registry,
+            },
+            payload,
+        )
+        .map_err(BinarySceneError::Encoding)
+}
+
+/// Convert binary scene file content to RON text.
+/// The scene version of the header is written as a scene resource, like in RON files.
+pub fn binary_to_ron(bytes: &[u8], registry: &TypeRegistry) -> Result<String, BinarySceneError> {
+    let version = binary_scene_version(bytes)?;
+    let scene = scene_from_binary(bytes, registry)?;
+    let text = scene.serialize(registry).map_err(BinarySceneError::Ron)?;
+    // Unknown components kept in binary file are restored with their original type paths
+    Ok(SceneTree::parse(&text).map_or(text, |mut tree| {
+        expand_unknown_components(&mut tree);
+        if let Some(version) = version {
+            tree.set_version(version);
+        }
+        tree.to_pretty_string()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::loader::PrefabSceneLoader;
+
+    #[derive(Component, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+        regen: Option<f32>,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(crate::prelude::EditorRegistryPlugin)
+            .add_plugins(crate::format::SceneFormatPlugin)
+            .register_type::<Health>()
+            .register_type::<Option<f32>>();
+        app
+    }
+
+    const SCENE: &str = r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "space_prefab::format::binary::tests::Health": (
+          value: 5,
+          regen: Some(0.5),
+        ),
+        "my_game::Unknown": (
+          a: 1,
+        ),
+      },
+    ),
+  },
+)"#;
+
+    #[test]
+    fn ron_binary_ron_roundtrip() {
+        let mut app = app();
+        let loader = PrefabSceneLoader::from_world(app.world_mut());
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+
+        for compressed in [false, true] {
+            let scene = loader.load_from_str(SCENE).unwrap();
+            let bytes = scene_to_binary(&scene, &registry, compressed, 0).unwrap();
+            assert!(is_binary_scene(&bytes));
+
+            let loaded = loader.load_from_bytes(&bytes).unwrap();
+            let health = loaded.entities[0]
+                .components
+                .iter()
+                .find_map(|c| Health::from_reflect(c.as_ref()))
+                .unwrap();
+            assert_eq!(
+                health,
+                Health {
+                    value: 5,
+                    regen: Some(0.5)
+                }
+            );
+
+            let text = binary_to_ron(&bytes, &registry).unwrap();
+            let mut expected = SceneTree::parse(SCENE).unwrap();
+            expected.set_version(0);
+            assert_eq!(SceneTree::parse(&text).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_header() {
+        let app = app();
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        assert!(matches!(
+            scene_from_binary(b"SPSCNB\x09\x00", &registry),
+            Err(BinarySceneError::Header(_))
+        ));
+    }
+}
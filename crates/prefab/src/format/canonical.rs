@@ -0,0 +1,387 @@
+use std::any::TypeId;
+
+use bevy::{
+    prelude::*,
+    reflect::{TypeInfo, TypeRegistry, VariantInfo},
+    utils::HashMap,
+};
+
+use super::{ron_tree::RonValue, SceneTree};
+use crate::component::PrefabGuid;
+
+/// Rewrite a scene into canonical form, so saving the same scene twice gives identical files.
+///
+/// Entities are ordered by [`PrefabGuid`] and renumbered, components and map keys are sorted
+/// and float literals are printed in a single format. References to renumbered entities
+/// are found with the `registry`, see [`remap_scene_entities`].
+pub fn canonicalize_scene(scene: &mut SceneTree, registry: &TypeRegistry) {
+    normalize_scene(scene);
+    let Some(entities) = scene.entities_mut() else {
+        return;
+    };
+
+    let mut keyed = std::mem::take(entities)
+        .into_iter()
+        .map(|(id, entity)| (entity_sort_key(&entity), id, entity))
+        .collect::<Vec<_>>();
+    // stable sort keeps file order for entities with equal keys
+    keyed.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    // Entity ids are transient, so they are replaced with ids in saved order
+    let remap = keyed
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, id, _))| match id {
+            RonValue::Literal(old) => Some((
+                old.clone(),
+                Entity::from_raw(index as u32).to_bits().to_string(),
+            )),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    entities.extend(keyed.into_iter().map(|(_, id, entity)| (id, entity)));
+
+    remap_scene_entities(scene, &remap, registry);
+    // maps with entity keys are sorted by the new ids
+    normalize_scene(scene);
+}
+
+/// Replace entity ids of a scene and references to them in resources and components.
+///
+/// Only values reflected as [`Entity`] are changed, so numbers that happen to equal an id
+/// are kept. Values of types missing from the `registry` are left as written.
+pub(crate) fn remap_scene_entities(
+    scene: &mut SceneTree,
+    remap: &HashMap<String, String>,
+    registry: &TypeRegistry,
+) {
+    if let Some(resources) = scene.resources_mut() {
+        remap_typed_entries(resources, remap, registry);
+    }
+    let Some(entities) = scene.entities_mut() else {
+        return;
+    };
+    for (id, entity) in entities.iter_mut() {
+        remap_literal(id, remap);
+        if let Some(components) = entity
+            .field_mut("components")
+            .and_then(RonValue::entries_mut)
+        {
+            remap_typed_entries(components, remap, registry);
+        }
+    }
+}
+
+/// Replace entity references in a map from type paths to values, like components of an entity
+pub(crate) fn remap_typed_entries(
+    entries: &mut [(RonValue, RonValue)],
+    remap: &HashMap<String, String>,
+    registry: &TypeRegistry,
+) {
+    for (key, value) in entries.iter_mut() {
+        let Some(registration) = key
+            .as_str()
+            .and_then(|type_path| registry.get_with_type_path(&type_path))
+        else {
+            continue;
+        };
+        remap_value(value, registration.type_id(), remap, registry);
+    }
+}
+
+fn remap_literal(value: &mut RonValue, remap: &HashMap<String, String>) {
+    if let RonValue::Literal(literal) = value {
+        if let Some(new) = remap.get(literal.as_str()) {
+            literal.clone_from(new);
+        }
+    }
+}
+
+fn remap_value(
+    value: &mut RonValue,
+    type_id: TypeId,
+    remap: &HashMap<String, String>,
+    registry: &TypeRegistry,
+) {
+    if type_id == TypeId::of::<Entity>() {
+        remap_literal(value, remap);
+        return;
+    }
+    let Some(info) = registry.get_type_info(type_id) else {
+        return;
+    };
+    match (info, value) {
+        (TypeInfo::Struct(info), RonValue::Struct(_, fields)) => {
+            for (name, value) in fields.iter_mut() {
+                if let Some(field) = info.field(name) {
+                    remap_value(value, field.type_id(), remap, registry);
+                }
+            }
+        }
+        (TypeInfo::TupleStruct(info), RonValue::Tuple(_, items)) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                if let Some(field) = info.field_at(index) {
+                    remap_value(item, field.type_id(), remap, registry);
+                }
+            }
+        }
+        (TypeInfo::Tuple(info), RonValue::Tuple(_, items)) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                if let Some(field) = info.field_at(index) {
+                    remap_value(item, field.type_id(), remap, registry);
+                }
+            }
+        }
+        (TypeInfo::List(info), RonValue::List(items)) => {
+            for item in items.iter_mut() {
+                remap_value(item, info.item_type_id(), remap, registry);
+            }
+        }
+        (TypeInfo::Array(info), RonValue::List(items) | RonValue::Tuple(None, items)) => {
+            for item in items.iter_mut() {
+                remap_value(item, info.item_type_id(), remap, registry);
+            }
+        }
+        (TypeInfo::Map(info), RonValue::Map(entries)) => {
+            for (key, value) in entries.iter_mut() {
+                remap_value(key, info.key_type_id(), remap, registry);
+                remap_value(value, info.value_type_id(), remap, registry);
+            }
+        }
+        (TypeInfo::Enum(info), RonValue::Tuple(Some(variant), items)) => {
+            if let Some(VariantInfo::Tuple(variant)) = info.variant(variant) {
+                for (index, item) in items.iter_mut().enumerate() {
+                    if let Some(field) = variant.field_at(index) {
+                        remap_value(item, field.type_id(), remap, registry);
+                    }
+                }
+            }
+        }
+        (TypeInfo::Enum(info), RonValue::Struct(Some(variant), fields)) => {
+            if let Some(VariantInfo::Struct(variant)) = info.variant(variant) {
+                for (name, value) in fields.iter_mut() {
+                    if let Some(field) = variant.field(name) {
+                        remap_value(value, field.type_id(), remap, registry);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Sort components and map keys and normalize floats, keeping entity ids and order
+pub(crate) fn normalize_scene(scene: &mut SceneTree) {
+    if let Some(resources) = scene.resources_mut() {
+        for (_, value) in resources.iter_mut() {
+            canonicalize_value(value);
+        }
+        sort_map(resources);
+    }
+    let Some(entities) = scene.entities_mut() else {
+        return;
+    };
+    for (_, entity) in entities.iter_mut() {
+        if let Some(components) = entity
+            .field_mut("components")
+            .and_then(RonValue::entries_mut)
+        {
+            for (_, value) in components.iter_mut() {
+                canonicalize_value(value);
+            }
+            sort_map(components);
+        }
+    }
+}
+
+fn entity_sort_key(entity: &RonValue) -> String {
+    let guid = entity
+        .field("components")
+        .and_then(RonValue::entries)
+        .and_then(|components| {
+            components
+                .iter()
+                .find(|(key, _)| key.as_str().as_deref() == Some(PrefabGuid::type_path()))
+        })
+        .and_then(|(_, value)| match value {
+            RonValue::Tuple(None, items) => items.first().and_then(RonValue::as_str),
+            _ => None,
+        });
+    guid.map_or_else(
+        || format!("1{}", entity.to_pretty_string()),
+        |guid| format!("0{guid}"),
+    )
+}
+
+fn sort_map(entries: &mut [(RonValue, RonValue)]) {
+    entries.sort_by_cached_key(|(key, _)| key.to_pretty_string());
+}
+
+/// Sort nested maps and normalize floats
+fn canonicalize_value(value: &mut RonValue) {
+    match value {
+        RonValue::Literal(literal) => {
+            if let Some(float) = normalize_float(literal) {
+                *literal = float;
+            }
+        }
+        RonValue::Ident(_) | RonValue::Unit => {}
+        RonValue::List(items) | RonValue::Tuple(_, items) => {
+            for item in items.iter_mut() {
+                canonicalize_value(item);
+            }
+        }
+        RonValue::Map(entries) => {
+            for (key, value) in entries.iter_mut() {
+                canonicalize_value(key);
+                canonicalize_value(value);
+            }
+            sort_map(entries);
+        }
+        RonValue::Struct(_, fields) => {
+            for (_, value) in fields.iter_mut() {
+                canonicalize_value(value);
+            }
+        }
+    }
+}
+
+/// Print float literal the same way ron does for `f32`/`f64` values
+fn normalize_float(literal: &str) -> Option<String> {
+    let first = literal.chars().next()?;
+    let is_number = first.is_ascii_digit() || matches!(first, '-' | '+' | '.');
+    let is_float = literal.contains(['.', 'e', 'E']) && !literal.contains("0x");
+    if !is_number || !is_float {
+        return None;
+    }
+    let value = literal.replace('_', "").parse::<f64>().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    // -0.0 and 0.0 are the same value for scene data
+    let value = if value == 0.0 { 0.0 } else { value };
+    let mut text = value.to_string();
+    if value.fract() == 0.0 {
+        text.push_str(".0");
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::ChildrenPrefab;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Counter {
+        count: u32,
+        target: Option<Entity>,
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<PrefabGuid>();
+        registry.register::<ChildrenPrefab>();
+        registry.register::<Counter>();
+        registry
+    }
+
+    #[test]
+    fn normalizes_floats() {
+        assert_eq!(normalize_float("1e-3").as_deref(), Some("0.001"));
+        assert_eq!(normalize_float("-0.0").as_deref(), Some("0.0"));
+        assert_eq!(normalize_float("2.").as_deref(), Some("2.0"));
+        assert_eq!(normalize_float("0.1").as_deref(), Some("0.1"));
+        assert_eq!(normalize_float("10"), None);
+        assert_eq!(normalize_float("\"1.5\""), None);
+    }
+
+    #[test]
+    fn orders_entities_and_components() {
+        let guid = PrefabGuid::type_path();
+        let src = format!(
+            r#"(
+  resources: {{}},
+  entities: {{
+    8589934600: (
+      components: {{
+        "b::B": {{"y": 1, "x": 2}},
+        "{guid}": ("b"),
+      }},
+    ),
+    4294967310: (
+      components: {{
+        "space_prefab::save::ChildrenPrefab": ([8589934600]),
+        "{guid}": ("a"),
+        "a::A": (value: -0.0),
+      }},
+    ),
+  }},
+)"#
+        );
+
+        let mut scene = SceneTree::parse(&src).unwrap();
+        canonicalize_scene(&mut scene, &registry());
+        let text = scene.to_pretty_string();
+
+        let expected = format!(
+            r#"(
+  resources: {{}},
+  entities: {{
+    4294967296: (
+      components: {{
+        "a::A": (
+          value: 0.0,
+        ),
+        "{guid}": ("a"),
+        "space_prefab::save::ChildrenPrefab": ([
+          4294967297,
+        ]),
+      }},
+    ),
+    4294967297: (
+      components: {{
+        "b::B": {{
+          "x": 2,
+          "y": 1,
+        }},
+        "{guid}": ("b"),
+      }},
+    ),
+  }},
+)"#
+        );
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn keeps_numbers_equal_to_entity_ids() {
+        let (guid, counter) = (PrefabGuid::type_path(), Counter::type_path());
+        let src = format!(
+            r#"(
+  resources: {{}},
+  entities: {{
+    10: (
+      components: {{
+        "{guid}": ("b"),
+        "{counter}": (count: 11, target: Some(11)),
+      }},
+    ),
+    11: (
+      components: {{
+        "{guid}": ("a"),
+      }},
+    ),
+  }},
+)"#
+        );
+
+        let mut scene = SceneTree::parse(&src).unwrap();
+        canonicalize_scene(&mut scene, &registry());
+        let text = scene.to_pretty_string();
+        assert!(text.contains("count: 11,"), "{text}");
+        assert!(text.contains("target: Some(4294967296)"), "{text}");
+        assert!(!text.contains("4294967298"), "{text}");
+    }
+}
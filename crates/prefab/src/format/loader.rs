@@ -0,0 +1,309 @@
+use std::{fmt, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError},
+    prelude::*,
+    reflect::TypeRegistryArc,
+    scene::serde::SceneDeserializer,
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use super::{
+    binary::{
+        binary_scene_version, binary_to_ron, is_binary_scene, scene_from_binary, scene_to_binary,
+        BinarySceneError,
+    },
+    migration::SceneMigrations,
+    ron_tree::RonParseError,
+    split::{join_scene_parts, part_path, read_scene_text, scene_part_names},
+    unknown::collapse_unknown_components,
+    SceneTree,
+};
+use crate::load::{resolve_prefab_variant, PrefabVariantError};
+
+/// Loader for `.scn.ron` and `.scn.bin` scene files.
+///
+/// RON files are upgraded with registered [`SceneMigrations`] and keep unregistered components
+/// before being handed to the reflection deserializer.
+/// Binary files are detected by their header and loaded directly when saved with the current
+/// scene version, older binary files are converted to RON and upgraded with the same migrations.
+/// Files with [`PrefabVariant`](crate::load::PrefabVariant) resource are resolved from their base prefab.
+pub struct PrefabSceneLoader {
+    type_registry: TypeRegistryArc,
+    migrations: SceneMigrations,
+}
+
+impl FromWorld for PrefabSceneLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+            migrations: world
+                .get_resource_or_insert_with(SceneMigrations::default)
+                .clone(),
+        }
+    }
+}
+
+/// Settings of [`PrefabSceneLoader`]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct PrefabSceneLoaderSettings {
+    /// Variants that are loading this file as their base, used to detect cycles
+    pub variant_chain: Vec<String>,
+}
+
+/// Possible errors of [`PrefabSceneLoader`]
+#[derive(Debug)]
+pub enum PrefabSceneLoaderError {
+    Io(std::io::Error),
+    Parse(RonParseError),
+    Deserialize(ron::error::SpannedError),
+    Binary(BinarySceneError),
+    Utf8(std::str::Utf8Error),
+    Variant(PrefabVariantError),
+    Part(ReadAssetBytesError),
+}
+
+impl fmt::Display for PrefabSceneLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Error while trying to read the scene file: {e}"),
+            Self::Parse(e) => write!(f, "Could not parse scene file: {e}"),
+            Self::Deserialize(e) => write!(f, "Could not deserialize scene: {e}"),
+            Self::Binary(e) => write!(f, "Could not load binary scene: {e}"),
+            Self::Utf8(e) => write!(f, "Scene file is not valid UTF-8: {e}"),
+            Self::Variant(e) => write!(f, "Could not resolve prefab variant: {e}"),
+            Self::Part(e) => write!(f, "Could not read part of split scene: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrefabSceneLoaderError {}
+
+impl PrefabSceneLoader {
+    /// Load scene file content in either RON or binary format
+    pub fn load_from_bytes(&self, bytes: &[u8]) -> Result<DynamicScene, PrefabSceneLoaderError> {
+        if is_binary_scene(bytes) {
+            let version = binary_scene_version(bytes).map_err(PrefabSceneLoaderError::Binary)?;
+            if version.is_some_and(|version| version >= self.migrations.current_version()) {
+                return scene_from_binary(bytes, &self.type_registry.read())
+                    .map_err(PrefabSceneLoaderError::Binary);
+            }
+            // Payload is decoded with current types, so only migrations that keep
+            // the binary layout of a type (like renamed fields or changed values) apply
+            let text = self.binary_to_ron(bytes)?;
+            return self.load_from_str(&text);
+        }
+        let text = std::str::from_utf8(bytes).map_err(PrefabSceneLoaderError::Utf8)?;
+        self.load_from_str(text)
+    }
+
+    /// Load scene file from disk without the asset server, joining parts of split scenes.
+    /// Prefab variants are not resolved.
+    pub fn load_from_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DynamicScene, PrefabSceneLoaderError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(PrefabSceneLoaderError::Io)?;
+        if is_binary_scene(&bytes) {
+            return self.load_from_bytes(&bytes);
+        }
+        let text = read_scene_text(path).map_err(PrefabSceneLoaderError::Io)?;
+        self.load_from_str(&text)
+    }
+
+    /// Convert RON scene file content to binary format. Migrations are applied before conversion.
+    pub fn ron_to_binary(
+        &self,
+        text: &str,
+        compressed: bool,
+    ) -> Result<Vec<u8>, PrefabSceneLoaderError> {
+        let scene = self.load_from_str(text)?;
+        scene_to_binary(
+            &scene,
+            &self.type_registry.read(),
+            compressed,
+            self.migrations.current_version(),
+        )
+        .map_err(PrefabSceneLoaderError::Binary)
+    }
+
+    /// Convert binary scene file content to RON text
+    pub fn binary_to_ron(&self, bytes: &[u8]) -> Result<String, PrefabSceneLoaderError> {
+        binary_to_ron(bytes, &self.type_registry.read()).map_err(PrefabSceneLoaderError::Binary)
+    }
+
+    /// Parse, migrate and deserialize scene file content
+    pub fn load_from_str(&self, text: &str) -> Result<DynamicScene, PrefabSceneLoaderError> {
+        let mut tree = SceneTree::parse(text).map_err(PrefabSceneLoaderError::Parse)?;
+        let version = self.migrations.migrate(&mut tree);
+        if version < self.migrations.current_version() {
+            info!(
+                "Upgraded scene from version {} to {}",
+                version,
+                self.migrations.current_version()
+            );
+        }
+        collapse_unknown_components(&mut tree, &self.type_registry.read());
+        let text = tree.to_pretty_string();
+
+        let mut deserializer =
+            ron::de::Deserializer::from_str(&text).map_err(PrefabSceneLoaderError::Deserialize)?;
+        let scene_deserializer = SceneDeserializer {
+            type_registry: &self.type_registry.read(),
+        };
+        scene_deserializer
+            .deserialize(&mut deserializer)
+            .map_err(|e| PrefabSceneLoaderError::Deserialize(deserializer.span_error(e)))
+    }
+}
+
+impl AssetLoader for PrefabSceneLoader {
+    type Asset = DynamicScene;
+    type Settings = PrefabSceneLoaderSettings;
+    type Error = PrefabSceneLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a PrefabSceneLoaderSettings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(PrefabSceneLoaderError::Io)?;
+        let bytes = join_split_scene(bytes, load_context).await?;
+        let scene = self.load_from_bytes(&bytes)?;
+        resolve_prefab_variant(scene, &self.type_registry, settings, load_context)
+            .await
+            .map_err(PrefabSceneLoaderError::Variant)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scn", "scn.ron", "scn.bin"]
+    }
+}
+
+/// Replace the index file of a split scene with the joined scene text, other files are returned as is.
+/// Parts are read as dependencies of the index, so editing a part reloads the scene.
+async fn join_split_scene(
+    bytes: Vec<u8>,
+    load_context: &mut LoadContext<'_>,
+) -> Result<Vec<u8>, PrefabSceneLoaderError> {
+    if is_binary_scene(&bytes) {
+        return Ok(bytes);
+    }
+    let Some(mut index) = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|text| SceneTree::parse(text).ok())
+    else {
+        return Ok(bytes);
+    };
+    let Some(names) = scene_part_names(&index) else {
+        return Ok(bytes);
+    };
+    let path = load_context.path().to_path_buf();
+    let mut parts = vec![];
+    for name in names {
+        let part = load_context
+            .read_asset_bytes(part_path(&path, &name))
+            .await
+            .map_err(PrefabSceneLoaderError::Part)?;
+        let text = std::str::from_utf8(&part).map_err(PrefabSceneLoaderError::Utf8)?;
+        parts.push(SceneTree::parse(text).map_err(PrefabSceneLoaderError::Parse)?);
+    }
+    join_scene_parts(&mut index, parts);
+    Ok(index.to_pretty_string().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{
+        migration::{SceneMigration, SceneMigrationExt},
+        ron_tree::RonValue,
+    };
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+    }
+
+    #[test]
+    fn loads_migrated_scene() {
+        let mut app = App::new();
+        app.register_type::<Health>()
+            .scene_migration(
+                1,
+                SceneMigration::rename_type("game::OldHealth", Health::type_path()),
+            )
+            .scene_migration(
+                1,
+                SceneMigration::rename_field(Health::type_path(), "hp", "value"),
+            );
+
+        let loader = PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader
+            .load_from_str(
+                r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "game::OldHealth": (hp: 7),
+      },
+    ),
+  },
+)"#,
+            )
+            .unwrap();
+
+        assert_eq!(scene.entities.len(), 1);
+        let health = Health::from_reflect(scene.entities[0].components[0].as_ref()).unwrap();
+        assert_eq!(health.value, 7);
+    }
+
+    #[test]
+    fn migrates_old_binary_scene() {
+        let mut app = App::new();
+        app.register_type::<Health>();
+        let loader = PrefabSceneLoader::from_world(app.world_mut());
+        let registry = app.world().resource::<AppTypeRegistry>().clone();
+        let scene = loader
+            .load_from_str(
+                r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "space_prefab::format::loader::tests::Health": (value: 7),
+      },
+    ),
+  },
+)"#,
+            )
+            .unwrap();
+        let bytes = scene_to_binary(&scene, &registry.read(), true, 0).unwrap();
+
+        app.scene_migration(
+            1,
+            SceneMigration::transform_value(Health::type_path(), |value| {
+                if let Some(RonValue::Literal(value)) = value.field_mut("value") {
+                    *value = "70".to_string();
+                }
+            }),
+        );
+        let scene = loader.load_from_bytes(&bytes).unwrap();
+        let health = Health::from_reflect(scene.entities[0].components[0].as_ref()).unwrap();
+        assert_eq!(health.value, 70);
+
+        // Scenes saved with the current version are decoded without migrations
+        let bytes = scene_to_binary(&scene, &registry.read(), false, 1).unwrap();
+        let scene = loader.load_from_bytes(&bytes).unwrap();
+        let health = Health::from_reflect(scene.entities[0].components[0].as_ref()).unwrap();
+        assert_eq!(health.value, 70);
+    }
+}
@@ -0,0 +1,240 @@
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+
+use super::{ron_tree::RonValue, SceneTree};
+
+/// Version of the scene format, saved as a resource in every scene file.
+/// Files without it are treated as version 0.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource, Default)]
+pub struct SceneVersion(pub u32);
+
+/// Single upgrade operation applied to a scene file before it is deserialized
+#[derive(Clone)]
+pub enum SceneMigration {
+    /// Component or resource type was renamed or moved to another module
+    RenameType { from: String, to: String },
+    /// Field of a struct component or resource was renamed
+    RenameField {
+        type_path: String,
+        from: String,
+        to: String,
+    },
+    /// Arbitrary rewrite of the serialized value of a component or resource
+    TransformValue {
+        type_path: String,
+        func: Arc<dyn Fn(&mut RonValue) + Send + Sync>,
+    },
+}
+
+impl SceneMigration {
+    pub fn rename_type(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::RenameType {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn rename_field(
+        type_path: impl Into<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        Self::RenameField {
+            type_path: type_path.into(),
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn transform_value(
+        type_path: impl Into<String>,
+        func: impl Fn(&mut RonValue) + Send + Sync + 'static,
+    ) -> Self {
+        Self::TransformValue {
+            type_path: type_path.into(),
+            func: Arc::new(func),
+        }
+    }
+
+    /// Apply migration to one `type path -> value` map (components of an entity or scene resources)
+    fn apply(&self, types: &mut [(RonValue, RonValue)]) {
+        for (key, value) in types.iter_mut() {
+            let Some(type_path) = key.as_str() else {
+                continue;
+            };
+            match self {
+                Self::RenameType { from, to } => {
+                    if &type_path == from {
+                        *key = RonValue::string(to);
+                    }
+                }
+                Self::RenameField {
+                    type_path: target,
+                    from,
+                    to,
+                } => {
+                    if &type_path == target {
+                        if let RonValue::Struct(_, fields) = value {
+                            for (name, _) in fields.iter_mut().filter(|(name, _)| name == from) {
+                                name.clone_from(to);
+                            }
+                        }
+                    }
+                }
+                Self::TransformValue {
+                    type_path: target,
+                    func,
+                } => {
+                    if &type_path == target {
+                        func(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Migration registered for a specific scene version
+#[derive(Clone)]
+pub struct MigrationStep {
+    /// Scenes saved with a lower version will get this migration applied
+    pub version: u32,
+    pub migration: SceneMigration,
+}
+
+/// All registered scene migrations. Shared with the scene asset loader,
+/// so migrations registered after the loader was created are still applied.
+#[derive(Resource, Clone, Default)]
+pub struct SceneMigrations {
+    steps: Arc<RwLock<Vec<MigrationStep>>>,
+}
+
+impl SceneMigrations {
+    pub fn add(&self, version: u32, migration: SceneMigration) {
+        if let Ok(mut steps) = self.steps.write() {
+            steps.push(MigrationStep { version, migration });
+            // stable sort keeps registration order for migrations of the same version
+            steps.sort_by_key(|step| step.version);
+        }
+    }
+
+    /// Version written to newly saved scenes
+    pub fn current_version(&self) -> u32 {
+        self.steps
+            .read()
+            .ok()
+            .and_then(|steps| steps.last().map(|step| step.version))
+            .unwrap_or_default()
+    }
+
+    /// Upgrade a parsed scene file in place. Returns the version the file was saved with.
+    pub fn migrate(&self, scene: &mut SceneTree) -> u32 {
+        let version = scene.take_version();
+        let Ok(steps) = self.steps.read() else {
+            return version;
+        };
+        for step in steps.iter().filter(|step| step.version > version) {
+            if let Some(resources) = scene.resources_mut() {
+                step.migration.apply(resources);
+            }
+            for components in scene.entity_components_mut() {
+                step.migration.apply(components);
+            }
+        }
+        version
+    }
+}
+
+pub trait SceneMigrationExt {
+    /// Register a migration that upgrades scenes saved before `version`
+    fn scene_migration(&mut self, version: u32, migration: SceneMigration) -> &mut Self;
+}
+
+impl SceneMigrationExt for App {
+    fn scene_migration(&mut self, version: u32, migration: SceneMigration) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(SceneMigrations::default)
+            .add(version, migration);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_SCENE: &str = r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "my_game::OldHealth": (
+          hp: 10,
+        ),
+        "my_game::Speed": (2.0),
+      },
+    ),
+  },
+)"#;
+
+    fn migrations() -> SceneMigrations {
+        let migrations = SceneMigrations::default();
+        migrations.add(
+            2,
+            SceneMigration::rename_field("my_game::Health", "hp", "value"),
+        );
+        migrations.add(
+            1,
+            SceneMigration::rename_type("my_game::OldHealth", "my_game::Health"),
+        );
+        migrations.add(
+            2,
+            SceneMigration::transform_value("my_game::Speed", |value| {
+                *value = RonValue::Tuple(None, vec![RonValue::Literal("4.0".to_string())]);
+            }),
+        );
+        migrations
+    }
+
+    #[test]
+    fn applies_migrations_in_version_order() {
+        let migrations = migrations();
+        assert_eq!(migrations.current_version(), 2);
+
+        let mut scene = SceneTree::parse(OLD_SCENE).unwrap();
+        assert_eq!(migrations.migrate(&mut scene), 0);
+
+        let text = scene.to_pretty_string();
+        assert!(text.contains("\"my_game::Health\": (\n          value: 10,"));
+        assert!(text.contains("\"my_game::Speed\": (4.0)"));
+    }
+
+    #[test]
+    fn skips_migrations_older_than_file() {
+        let migrations = migrations();
+        let versioned = OLD_SCENE.replacen(
+            "resources: {}",
+            "resources: {\"space_prefab::format::migration::SceneVersion\": (1)}",
+            1,
+        );
+
+        let mut scene = SceneTree::parse(&versioned).unwrap();
+        assert_eq!(migrations.migrate(&mut scene), 1);
+
+        let text = scene.to_pretty_string();
+        assert!(text.contains("my_game::OldHealth"));
+        assert!(!text.contains("SceneVersion"));
+    }
+
+    #[test]
+    fn app_extension_registers_migration() {
+        let mut app = App::new();
+        app.scene_migration(3, SceneMigration::rename_type("a::A", "b::B"));
+        assert_eq!(
+            app.world().resource::<SceneMigrations>().current_version(),
+            3
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::{prelude::*, reflect::TypePath};
+
+/// Compact binary scene files
+pub mod binary;
+/// Deterministic scene output for diff-friendly files
+pub mod canonical;
+/// Scene asset loader that upgrades old scene files before deserializing them
+pub mod loader;
+/// Registered upgrades between scene format versions
+pub mod migration;
+/// Lossless RON value tree used to rewrite scene files
+pub mod ron_tree;
+/// Scenes stored as an index file and one file per root entity
+pub mod split;
+/// Preservation of components with types unknown to this build
+pub mod unknown;
+
+use loader::PrefabSceneLoader;
+use migration::{SceneMigrations, SceneVersion};
+use ron_tree::{RonParseError, RonValue};
+use unknown::{UnknownComponent, UnknownComponents};
+
+use crate::editor_registry::EditorRegistryExt;
+
+/// Registers scene versioning and the migrating scene loader
+pub struct SceneFormatPlugin;
+
+impl Plugin for SceneFormatPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.register_type::<SceneVersion>();
+        app.register_type::<split::SceneParts>();
+        app.register_type::<UnknownComponent>();
+        app.register_type::<Vec<UnknownComponent>>();
+        app.editor_silent_registry::<UnknownComponents>();
+        if !app.world().contains_resource::<SceneMigrations>() {
+            app.init_resource::<SceneMigrations>();
+        }
+
+        // Registered after bevy ScenePlugin so it takes priority for scene extensions
+        if app.world().contains_resource::<AssetServer>() {
+            app.init_asset_loader::<PrefabSceneLoader>();
+        }
+    }
+}
+
+/// Parsed scene file (`(resources: {..}, entities: {..})`) that can be edited before deserialization
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneTree(pub RonValue);
+
+impl SceneTree {
+    pub fn parse(src: &str) -> Result<Self, RonParseError> {
+        RonValue::parse(src).map(Self)
+    }
+
+    pub fn to_pretty_string(&self) -> String {
+        self.0.to_pretty_string()
+    }
+
+    /// `type path -> value` map of scene resources
+    pub fn resources_mut(&mut self) -> Option<&mut Vec<(RonValue, RonValue)>> {
+        self.0
+            .field_mut("resources")
+            .and_then(RonValue::entries_mut)
+    }
+
+    /// `entity -> (components: {..})` map of scene entities
+    pub fn entities_mut(&mut self) -> Option<&mut Vec<(RonValue, RonValue)>> {
+        self.0.field_mut("entities").and_then(RonValue::entries_mut)
+    }
+
+    /// `type path -> value` maps of every entity
+    pub fn entity_components_mut(
+        &mut self,
+    ) -> impl Iterator<Item = &mut Vec<(RonValue, RonValue)>> {
+        self.entities_mut()
+            .into_iter()
+            .flat_map(|entities| entities.iter_mut())
+            .filter_map(|(_, entity)| {
+                entity
+                    .field_mut("components")
+                    .and_then(RonValue::entries_mut)
+            })
+    }
+
+    /// Replace the [`SceneVersion`] resource of the file
+    pub fn set_version(&mut self, version: u32) {
+        self.take_version();
+        if let Some(resources) = self.resources_mut() {
+            resources.push((
+                RonValue::string(SceneVersion::type_path()),
+                RonValue::Tuple(None, vec![RonValue::Literal(version.to_string())]),
+            ));
+        }
+    }
+
+    /// Remove the [`SceneVersion`] resource from the file and return its value
+    pub fn take_version(&mut self) -> u32 {
+        let Some(resources) = self.resources_mut() else {
+            return 0;
+        };
+        let Some(index) = resources
+            .iter()
+            .position(|(key, _)| key.as_str().as_deref() == Some(SceneVersion::type_path()))
+        else {
+            return 0;
+        };
+        let (_, value) = resources.remove(index);
+        match value {
+            RonValue::Tuple(None, items) => match items.as_slice() {
+                [RonValue::Literal(version)] => version.parse().unwrap_or_default(),
+                _ => 0,
+            },
+            RonValue::Literal(version) => version.parse().unwrap_or_default(),
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,568 @@
+use std::fmt;
+
+/// Untyped RON value that keeps struct names, enum variants and literals exactly as written.
+///
+/// Unlike [`ron::Value`] it can be printed back without losing information,
+/// so it is used to inspect and rewrite scene files before they reach the reflection deserializer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RonValue {
+    /// Number, string, char or byte string literal in its source form
+    Literal(String),
+    /// Bare identifier, like `true`, `None` or a unit enum variant
+    Ident(String),
+    /// `()`
+    Unit,
+    /// `[a, b]`
+    List(Vec<Self>),
+    /// `{key: value}`
+    Map(Vec<(Self, Self)>),
+    /// `Name(a, b)` or `(a, b)`
+    Tuple(Option<String>, Vec<Self>),
+    /// `Name(field: value)` or `(field: value)`
+    Struct(Option<String>, Vec<(String, Self)>),
+}
+
+/// Error produced when a RON document can not be parsed into [`RonValue`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RonParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RonParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for RonParseError {}
+
+impl RonValue {
+    /// Parse a full RON document
+    pub fn parse(src: &str) -> Result<Self, RonParseError> {
+        let mut parser = Parser { src, pos: 0 };
+        parser.skip_ws()?;
+        if parser.rest().starts_with("#!") {
+            return Err(parser.error("RON extensions are not supported in scene files"));
+        }
+        let value = parser.value()?;
+        parser.skip_ws()?;
+        if parser.pos != src.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// Quoted and escaped string literal
+    pub fn string(value: &str) -> Self {
+        Self::Literal(format!("{value:?}"))
+    }
+
+    /// Decoded content of a string literal
+    pub fn as_str(&self) -> Option<String> {
+        match self {
+            Self::Literal(lit) if lit.starts_with('"') || lit.starts_with('r') => {
+                ron::from_str::<String>(lit).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Value of a named struct field
+    pub fn field(&self, name: &str) -> Option<&Self> {
+        match self {
+            Self::Struct(_, fields) => fields.iter().find(|(k, _)| k == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Mutable value of a named struct field
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Self> {
+        match self {
+            Self::Struct(_, fields) => fields.iter_mut().find(|(k, _)| k == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Entries of a map value
+    pub const fn entries(&self) -> Option<&Vec<(Self, Self)>> {
+        match self {
+            Self::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Mutable entries of a map value
+    pub fn entries_mut(&mut self) -> Option<&mut Vec<(Self, Self)>> {
+        match self {
+            Self::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Pretty printed RON in the same layout bevy uses for scene files
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            Self::Literal(s) | Self::Ident(s) => out.push_str(s),
+            Self::Unit => out.push_str("()"),
+            Self::List(items) => {
+                if items.is_empty() {
+                    out.push_str("[]");
+                    return;
+                }
+                out.push_str("[\n");
+                for item in items {
+                    push_indent(out, indent + 1);
+                    item.write_pretty(out, indent + 1);
+                    out.push_str(",\n");
+                }
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Self::Map(entries) => {
+                if entries.is_empty() {
+                    out.push_str("{}");
+                    return;
+                }
+                out.push_str("{\n");
+                for (key, value) in entries {
+                    push_indent(out, indent + 1);
+                    key.write_pretty(out, indent + 1);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(",\n");
+                }
+                push_indent(out, indent);
+                out.push('}');
+            }
+            Self::Tuple(name, items) => {
+                if let Some(name) = name {
+                    out.push_str(name);
+                }
+                out.push('(');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write_pretty(out, indent);
+                }
+                out.push(')');
+            }
+            Self::Struct(name, fields) => {
+                if let Some(name) = name {
+                    out.push_str(name);
+                }
+                if fields.is_empty() {
+                    out.push_str("()");
+                    return;
+                }
+                out.push_str("(\n");
+                for (key, value) in fields {
+                    push_indent(out, indent + 1);
+                    out.push_str(key);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(",\n");
+                }
+                push_indent(out, indent);
+                out.push(')');
+            }
+        }
+    }
+}
+
+impl fmt::Display for RonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_pretty_string())
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, message: &str) -> RonParseError {
+        let consumed = &self.src[..self.pos];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed
+            .rsplit('\n')
+            .next()
+            .map_or(0, |l| l.chars().count())
+            + 1;
+        RonParseError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_ws(&mut self) -> Result<(), RonParseError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                let mut depth = 0usize;
+                loop {
+                    let rest = self.rest();
+                    if rest.starts_with("/*") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if rest.starts_with("*/") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if let Some(c) = rest.chars().next() {
+                        self.pos += c.len_utf8();
+                    } else {
+                        return Err(self.error("unterminated block comment"));
+                    }
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), RonParseError> {
+        self.skip_ws()?;
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{c}`")))
+        }
+    }
+
+    /// Consume `,` if present and report whether the closing delimiter follows
+    fn separator(&mut self, close: char) -> Result<bool, RonParseError> {
+        self.skip_ws()?;
+        if self.peek() == Some(',') {
+            self.pos += 1;
+            self.skip_ws()?;
+        } else if self.peek() != Some(close) {
+            return Err(self.error(&format!("expected `,` or `{close}`")));
+        }
+        Ok(self.peek() == Some(close))
+    }
+
+    fn value(&mut self) -> Result<RonValue, RonParseError> {
+        self.skip_ws()?;
+        let rest = self.rest();
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some('"') => self.string_literal(0).map(RonValue::Literal),
+            Some('\'') => self.char_literal().map(RonValue::Literal),
+            Some('[') => self.list(),
+            Some('{') => self.map(),
+            Some('(') => self.parenthesized(None),
+            Some(_) if rest.starts_with("b\"") => self.string_literal(1).map(RonValue::Literal),
+            Some(_)
+                if rest.starts_with("r\"")
+                    || rest.starts_with("r#\"")
+                    || rest.starts_with("r##") =>
+            {
+                self.raw_string().map(RonValue::Literal)
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                Ok(RonValue::Literal(self.number()))
+            }
+            Some(c) if is_ident_start(c) => {
+                let name = self.ident()?;
+                self.skip_ws()?;
+                if self.peek() == Some('(') {
+                    self.parenthesized(Some(name))
+                } else {
+                    Ok(RonValue::Ident(name))
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected character `{c}`"))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, RonParseError> {
+        let start = self.pos;
+        if self.rest().starts_with("r#") {
+            self.pos += 2;
+        }
+        let body = self
+            .rest()
+            .find(|c: char| !is_ident_continue(c))
+            .unwrap_or_else(|| self.rest().len());
+        if body == 0 {
+            return Err(self.error("expected identifier"));
+        }
+        self.pos += body;
+        Ok(self.src[start..self.pos].to_string())
+    }
+
+    fn number(&mut self) -> String {
+        let start = self.pos;
+        let mut prev = '\0';
+        let hex = self.rest().starts_with("0x") || self.rest().get(1..3) == Some("0x");
+        for c in self.rest().chars() {
+            let sign_ok = (c == '-' || c == '+')
+                && (self.pos == start || (!hex && (prev == 'e' || prev == 'E')));
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || sign_ok) {
+                break;
+            }
+            prev = c;
+            self.pos += c.len_utf8();
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    fn string_literal(&mut self, prefix: usize) -> Result<String, RonParseError> {
+        let start = self.pos;
+        self.pos += prefix + 1;
+        let mut escaped = false;
+        for c in self.rest().chars() {
+            self.pos += c.len_utf8();
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                return Ok(self.src[start..self.pos].to_string());
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn raw_string(&mut self) -> Result<String, RonParseError> {
+        let start = self.pos;
+        self.pos += 1;
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes;
+        if self.peek() != Some('"') {
+            return Err(self.error("expected `\"` in raw string"));
+        }
+        self.pos += 1;
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let Some(end) = self.rest().find(&terminator) else {
+            return Err(self.error("unterminated raw string"));
+        };
+        self.pos += end + terminator.len();
+        Ok(self.src[start..self.pos].to_string())
+    }
+
+    fn char_literal(&mut self) -> Result<String, RonParseError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        for c in self.rest().chars() {
+            self.pos += c.len_utf8();
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '\'' {
+                return Ok(self.src[start..self.pos].to_string());
+            }
+        }
+        Err(self.error("unterminated char"))
+    }
+
+    fn list(&mut self) -> Result<RonValue, RonParseError> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_ws()?;
+        while self.peek() != Some(']') {
+            items.push(self.value()?);
+            if self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(RonValue::List(items))
+    }
+
+    fn map(&mut self) -> Result<RonValue, RonParseError> {
+        self.expect('{')?;
+        let mut entries = vec![];
+        self.skip_ws()?;
+        while self.peek() != Some('}') {
+            let key = self.value()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+            if self.separator('}')? {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(RonValue::Map(entries))
+    }
+
+    /// Check whether the parenthesized body starts with `field:`
+    fn is_struct_body(&self) -> bool {
+        let mut probe = Parser {
+            src: self.src,
+            pos: self.pos,
+        };
+        if probe.skip_ws().is_err() || !probe.peek().is_some_and(is_ident_start) {
+            return false;
+        }
+        let rest = probe.rest();
+        if rest.starts_with("r\"") || rest.starts_with("r#\"") || rest.starts_with("b\"") {
+            return false;
+        }
+        if probe.ident().is_err() || probe.skip_ws().is_err() {
+            return false;
+        }
+        probe.rest().starts_with(':') && !probe.rest().starts_with("::")
+    }
+
+    fn parenthesized(&mut self, name: Option<String>) -> Result<RonValue, RonParseError> {
+        self.expect('(')?;
+        self.skip_ws()?;
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(name.map_or(RonValue::Unit, |name| RonValue::Tuple(Some(name), vec![])));
+        }
+
+        if self.is_struct_body() {
+            let mut fields = vec![];
+            while self.peek() != Some(')') {
+                let key = self.ident()?;
+                self.expect(':')?;
+                let value = self.value()?;
+                fields.push((key, value));
+                if self.separator(')')? {
+                    break;
+                }
+            }
+            self.expect(')')?;
+            Ok(RonValue::Struct(name, fields))
+        } else {
+            let mut items = vec![];
+            while self.peek() != Some(')') {
+                items.push(self.value()?);
+                if self.separator(')')? {
+                    break;
+                }
+            }
+            self.expect(')')?;
+            Ok(RonValue::Tuple(name, items))
+        }
+    }
+}
+
+const fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+const fn is_ident_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "bevy_transform::components::transform::Transform": (
+          translation: (
+            x: 0.0,
+            y: -1.5e-3,
+            z: 2.0,
+          ),
+          rotation: (0.0, 0.0, 0.0, 1.0),
+          scale: (
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+          ),
+        ),
+        "bevy_core::name::Name": "cube \"one\"", // comment
+        "space_prefab::component::GltfPrefab": (
+          path: "models/cube.glb",
+          scene: "Scene0",
+        ),
+        "space_shared::PrefabMarker": (),
+        "bevy_render::view::visibility::Visibility": Inherited,
+        "bevy_pbr::light::point_light::PointLight": (
+          color: LinearRgba((
+            red: 1.0,
+            green: 1.0,
+            blue: 1.0,
+            alpha: 1.0,
+          )),
+          radius: None,
+          shadows_enabled: true,
+        ),
+      },
+    ),
+  },
+)"#;
+
+    #[test]
+    fn parse_and_print_roundtrip() {
+        let value = RonValue::parse(SCENE).unwrap();
+        let printed = value.to_pretty_string();
+        assert_eq!(RonValue::parse(&printed).unwrap(), value);
+        assert_eq!(
+            printed,
+            RonValue::parse(&printed).unwrap().to_pretty_string()
+        );
+    }
+
+    #[test]
+    fn keeps_names_and_literals() {
+        let value = RonValue::parse(SCENE).unwrap();
+        let entities = value.field("entities").and_then(RonValue::entries).unwrap();
+        assert_eq!(entities[0].0, RonValue::Literal("4294967296".to_string()));
+
+        let components = entities[0]
+            .1
+            .field("components")
+            .unwrap()
+            .entries()
+            .unwrap();
+        assert_eq!(components[1].1.as_str(), Some("cube \"one\"".to_string()));
+        assert!(
+            matches!(&components[5].1.field("color"), Some(RonValue::Tuple(Some(name), _)) if name == "LinearRgba")
+        );
+        assert_eq!(
+            components[0].1.field("translation").unwrap().field("y"),
+            Some(&RonValue::Literal("-1.5e-3".to_string()))
+        );
+    }
+
+    #[test]
+    fn reports_error_position() {
+        let err = RonValue::parse("(\n  a: [1, 2\n)").unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+}
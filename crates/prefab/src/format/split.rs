@@ -0,0 +1,566 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeRegistry},
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    canonical::{normalize_scene, remap_scene_entities},
+    ron_tree::RonValue,
+    SceneTree,
+};
+use crate::{
+    component::PrefabGuid,
+    save::{rotate_backups, write_scene_file, ChildrenPrefab},
+};
+
+/// Extension of the folder with part files, next to the index file
+pub const PARTS_DIR_EXTENSION: &str = "parts";
+
+/// Resource of the index file of a split scene. Lists part files in the parts folder,
+/// each part holds one root entity with its children.
+#[derive(Resource, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Resource, Default)]
+pub struct SceneParts {
+    pub parts: Vec<String>,
+}
+
+/// Folder with part files of the split scene at `path` (`level.scn.ron` -> `level.parts`)
+pub fn parts_dir(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = name
+        .strip_suffix(".scn.ron")
+        .or_else(|| name.strip_suffix(".scn"))
+        .unwrap_or(&name);
+    path.with_file_name(format!("{stem}.{PARTS_DIR_EXTENSION}"))
+}
+
+/// Path of the part file with `name` of the split scene at `path`
+pub fn part_path(path: impl AsRef<Path>, name: &str) -> PathBuf {
+    parts_dir(path).join(format!("{name}.scn.ron"))
+}
+
+/// Names of the part files listed in the index file, `None` for ordinary scene files
+pub fn scene_part_names(tree: &SceneTree) -> Option<Vec<String>> {
+    let (_, value) = tree
+        .0
+        .field("resources")
+        .and_then(RonValue::entries)?
+        .iter()
+        .find(|(key, _)| key.as_str().as_deref() == Some(SceneParts::type_path()))?;
+    let parts = match value.field("parts") {
+        Some(RonValue::List(parts)) => parts.iter().filter_map(RonValue::as_str).collect(),
+        _ => vec![],
+    };
+    Some(parts)
+}
+
+/// Whether the scene file at `path` is an index file of a split scene
+pub fn is_split_scene_file(path: impl AsRef<Path>) -> bool {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| SceneTree::parse(&text).ok())
+        .is_some_and(|tree| scene_part_names(&tree).is_some())
+}
+
+fn entity_components(entity: &RonValue) -> impl Iterator<Item = &(RonValue, RonValue)> {
+    entity
+        .field("components")
+        .and_then(RonValue::entries)
+        .into_iter()
+        .flatten()
+}
+
+fn component<'a>(entity: &'a RonValue, type_path: &str) -> Option<&'a RonValue> {
+    entity_components(entity)
+        .find(|(key, _)| key.as_str().as_deref() == Some(type_path))
+        .map(|(_, value)| value)
+}
+
+fn children_of(entity: &RonValue) -> Vec<String> {
+    match component(entity, ChildrenPrefab::type_path()) {
+        Some(RonValue::Tuple(None, items)) => match items.first() {
+            Some(RonValue::List(children)) => children
+                .iter()
+                .filter_map(|child| match child {
+                    RonValue::Literal(id) => Some(id.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+fn guid_of(entity: &RonValue) -> Option<String> {
+    match component(entity, PrefabGuid::type_path()) {
+        Some(RonValue::Tuple(None, items)) => items.first().and_then(RonValue::as_str),
+        _ => None,
+    }
+}
+
+fn part_name(entity: &RonValue, index: usize) -> String {
+    guid_of(entity).unwrap_or_else(|| format!("entity-{index}"))
+}
+
+fn empty_scene(resources: Vec<(RonValue, RonValue)>) -> SceneTree {
+    SceneTree(RonValue::Struct(
+        None,
+        vec![
+            ("resources".to_string(), RonValue::Map(resources)),
+            ("entities".to_string(), RonValue::Map(vec![])),
+        ],
+    ))
+}
+
+/// FNV-1a hash of a guid, the same on every platform and run
+fn guid_hash(guid: &str) -> u32 {
+    guid.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// New ids for all entities of a split scene, shared by the index and every part.
+///
+/// Entities with a [`PrefabGuid`] get an id from its hash, so adding or removing other entities
+/// does not change their part files. Entities without a guid are numbered in scene order
+/// above the hashed ids.
+fn stable_entity_ids(entities: &[(RonValue, RonValue)]) -> HashMap<String, String> {
+    const HASHED: u32 = 0x7fff_ffff;
+    let mut with_guid = vec![];
+    let mut without_guid = vec![];
+    for (id, entity) in entities.iter() {
+        let RonValue::Literal(id) = id else {
+            continue;
+        };
+        match guid_of(entity) {
+            Some(guid) => with_guid.push((guid, id.clone())),
+            None => without_guid.push(id.clone()),
+        }
+    }
+    // stable sort keeps scene order for duplicated guids
+    with_guid.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut used = HashSet::new();
+    let mut remap = HashMap::new();
+    for (guid, id) in with_guid {
+        let mut index = guid_hash(&guid) & HASHED;
+        while !used.insert(index) {
+            index = (index + 1) & HASHED;
+        }
+        remap.insert(id, Entity::from_raw(index).to_bits().to_string());
+    }
+    for (n, id) in without_guid.into_iter().enumerate() {
+        let index = (HASHED + 1).saturating_add(n as u32);
+        remap.insert(id, Entity::from_raw(index).to_bits().to_string());
+    }
+    remap
+}
+
+fn entity_id(id: &RonValue) -> u64 {
+    match id {
+        RonValue::Literal(id) => id.parse().unwrap_or(u64::MAX),
+        _ => u64::MAX,
+    }
+}
+
+/// Index file and part files of a split scene, made by [`split_scene`]
+#[derive(Clone, Debug)]
+pub struct SplitScene {
+    /// Scene resources with the [`SceneParts`] list
+    pub index: SceneTree,
+    /// Part name and scene with one root entity and its children, in name order
+    pub parts: Vec<(String, SceneTree)>,
+}
+
+/// Split scene into an index with scene resources and a part for every root entity with its children.
+///
+/// Parts are named by [`PrefabGuid`] of their root and written in canonical form,
+/// so changing one object only changes its part. The index and all parts share one entity id
+/// space, so references between entities of different parts are kept. Entity references
+/// are found with the `registry`.
+pub fn split_scene(scene: &SceneTree, registry: &TypeRegistry) -> SplitScene {
+    let mut scene = scene.clone();
+    let remap = scene
+        .0
+        .field("entities")
+        .and_then(RonValue::entries)
+        .map(|entities| stable_entity_ids(entities))
+        .unwrap_or_default();
+    remap_scene_entities(&mut scene, &remap, registry);
+    normalize_scene(&mut scene);
+
+    let resources = scene
+        .0
+        .field("resources")
+        .and_then(RonValue::entries)
+        .cloned()
+        .unwrap_or_default();
+    let entities = scene
+        .0
+        .field("entities")
+        .and_then(RonValue::entries)
+        .cloned()
+        .unwrap_or_default();
+
+    let ids = entities
+        .iter()
+        .filter_map(|(id, entity)| match id {
+            RonValue::Literal(id) => Some((id.clone(), entity)),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let children = entities
+        .iter()
+        .flat_map(|(_, entity)| children_of(entity))
+        .collect::<HashSet<_>>();
+
+    let mut used_names = HashSet::new();
+    let mut assigned = HashSet::new();
+    let mut parts = vec![];
+    for (index, (id, root)) in entities.iter().enumerate() {
+        let RonValue::Literal(id) = id else {
+            continue;
+        };
+        if children.contains(id) {
+            continue;
+        }
+        let mut part_entities = vec![];
+        let mut queue = vec![id.clone()];
+        while let Some(id) = queue.pop() {
+            let Some(entity) = ids.get(&id) else {
+                continue;
+            };
+            if !assigned.insert(id.clone()) {
+                continue;
+            }
+            queue.extend(children_of(entity));
+            part_entities.push((RonValue::Literal(id), (*entity).clone()));
+        }
+        part_entities.sort_by_key(|(id, _)| entity_id(id));
+
+        let mut name = part_name(root, index);
+        if !used_names.insert(name.clone()) {
+            let mut n = 2;
+            while !used_names.insert(format!("{name}-{n}")) {
+                n += 1;
+            }
+            name = format!("{name}-{n}");
+        }
+        let mut part = empty_scene(vec![]);
+        if let Some(entities) = part.entities_mut() {
+            *entities = part_entities;
+        }
+        parts.push((name, part));
+    }
+    parts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut resources = resources;
+    resources.retain(|(key, _)| key.as_str().as_deref() != Some(SceneParts::type_path()));
+    resources.push((
+        RonValue::string(SceneParts::type_path()),
+        RonValue::Struct(
+            None,
+            vec![(
+                "parts".to_string(),
+                RonValue::List(
+                    parts
+                        .iter()
+                        .map(|(name, _)| RonValue::string(name))
+                        .collect(),
+                ),
+            )],
+        ),
+    ));
+    let mut index = empty_scene(resources);
+    normalize_scene(&mut index);
+    SplitScene { index, parts }
+}
+
+/// Append entities of the parts to the index and remove the [`SceneParts`] resource.
+/// Parts share the entity id space of the index, so entities are appended unchanged.
+pub fn join_scene_parts(index: &mut SceneTree, parts: Vec<SceneTree>) {
+    if let Some(resources) = index.resources_mut() {
+        resources.retain(|(key, _)| key.as_str().as_deref() != Some(SceneParts::type_path()));
+    }
+    for mut part in parts {
+        let Some(entities) = part.entities_mut() else {
+            continue;
+        };
+        let entities = std::mem::take(entities);
+        if let Some(index_entities) = index.entities_mut() {
+            index_entities.extend(entities);
+        }
+    }
+}
+
+/// Write scene text as an index file at `path` and part files in its parts folder.
+/// Entity references are found with the `registry`, see [`split_scene`].
+pub fn write_split_scene(
+    path: impl AsRef<Path>,
+    text: &str,
+    registry: &TypeRegistry,
+    backups: usize,
+) -> io::Result<()> {
+    let scene =
+        SceneTree::parse(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_scene_parts(path, &split_scene(&scene, registry), backups)
+}
+
+/// Write a split scene as an index file at `path` and part files in its parts folder.
+///
+/// Changed parts are written first and the index last, each through a temporary file,
+/// so an interrupted save never lists a part that is missing. Parts of removed entities
+/// are deleted after the index. Backups of a split scene are whole ordinary scene files
+/// with all parts joined, so [`restore_newest_backup`](crate::save::restore_newest_backup)
+/// turns it back into a single file.
+pub fn write_scene_parts(
+    path: impl AsRef<Path>,
+    scene: &SplitScene,
+    backups: usize,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let previous = if backups > 0 && path.is_file() {
+        Some(read_scene_text(path)?)
+    } else {
+        None
+    };
+
+    let dir = parts_dir(path);
+    fs::create_dir_all(&dir)?;
+    let mut written = HashSet::new();
+    for (name, part) in scene.parts.iter() {
+        let file = part_path(path, name);
+        let text = part.to_pretty_string();
+        written.insert(file.clone());
+        if fs::read_to_string(&file).is_ok_and(|old| old == text) {
+            continue;
+        }
+        write_scene_file(&file, text.as_bytes(), 0)?;
+    }
+
+    if let Some(previous) = previous {
+        rotate_backups(path, backups, previous.as_bytes())?;
+    }
+    write_scene_file(path, scene.index.to_pretty_string().as_bytes(), 0)?;
+
+    for entry in fs::read_dir(&dir)? {
+        let file = entry?.path();
+        let is_part = file.to_string_lossy().ends_with(".scn.ron");
+        if is_part && !written.contains(&file) {
+            fs::remove_file(&file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read scene file at `path`, joining its parts if it is the index of a split scene
+pub fn read_scene_text(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let Ok(mut index) = SceneTree::parse(&text) else {
+        return Ok(text);
+    };
+    let Some(names) = scene_part_names(&index) else {
+        return Ok(text);
+    };
+    let mut parts = vec![];
+    for name in names {
+        let part = fs::read_to_string(part_path(path, &name))?;
+        parts.push(
+            SceneTree::parse(&part).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+    }
+    join_scene_parts(&mut index, parts);
+    Ok(index.to_pretty_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Target {
+        entity: Option<Entity>,
+        count: u32,
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<PrefabGuid>();
+        registry.register::<ChildrenPrefab>();
+        registry.register::<Target>();
+        registry
+    }
+
+    fn scene() -> String {
+        let (guid, children) = (PrefabGuid::type_path(), ChildrenPrefab::type_path());
+        format!(
+            r#"(
+  resources: {{
+    "game::Gravity": (-9.8),
+  }},
+  entities: {{
+    10: (
+      components: {{
+        "{guid}": ("b"),
+        "{children}": ([12]),
+      }},
+    ),
+    11: (
+      components: {{
+        "{guid}": ("a"),
+      }},
+    ),
+    12: (
+      components: {{
+        "{guid}": ("c"),
+        "game::Target": (12),
+      }},
+    ),
+  }},
+)"#
+        )
+    }
+
+    #[test]
+    fn splits_scene_by_root_entities() {
+        let SplitScene { index, parts } =
+            split_scene(&SceneTree::parse(&scene()).unwrap(), &registry());
+        assert_eq!(
+            scene_part_names(&index),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert!(index.to_pretty_string().contains("game::Gravity"));
+        assert_eq!(parts.len(), 2);
+        let (_, b) = &parts[1];
+        let text = b.to_pretty_string();
+        assert!(text.contains("\"c\""));
+        assert!(!text.contains("\"a\""));
+
+        let mut joined = index;
+        join_scene_parts(
+            &mut joined,
+            parts.into_iter().map(|(_, part)| part).collect(),
+        );
+        assert_eq!(scene_part_names(&joined), None);
+        let mut ids = joined
+            .entities_mut()
+            .unwrap()
+            .iter()
+            .map(|(id, _)| id.to_pretty_string())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn changing_one_entity_changes_one_file() {
+        let dir = TempDir::new("split");
+        let file = dir.join("level.scn.ron");
+
+        write_split_scene(&file, &scene(), &registry(), 0).unwrap();
+        assert!(part_path(&file, "a").exists());
+        assert!(part_path(&file, "b").exists());
+        let a = fs::metadata(part_path(&file, "a"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        let b = fs::read_to_string(part_path(&file, "b")).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let changed = scene()
+            .replace("([12])", "([])")
+            .replace("(\"c\")", "(\"d\")");
+        write_split_scene(&file, &changed, &registry(), 0).unwrap();
+        assert_eq!(
+            fs::metadata(part_path(&file, "a"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            a
+        );
+        assert_ne!(fs::read_to_string(part_path(&file, "b")).unwrap(), b);
+        assert!(part_path(&file, "d").exists());
+
+        let text = read_scene_text(&file).unwrap();
+        let mut joined = SceneTree::parse(&text).unwrap();
+        assert_eq!(joined.entities_mut().unwrap().len(), 3);
+        assert!(!text.contains("SceneParts"));
+    }
+
+    #[test]
+    fn keeps_references_between_parts() {
+        let target = Target::type_path();
+        let src = scene().replace(
+            "\"game::Target\": (12),",
+            &format!("\"{target}\": (entity: Some(11), count: 11),"),
+        );
+        let SplitScene { index, parts } =
+            split_scene(&SceneTree::parse(&src).unwrap(), &registry());
+        let mut joined = index;
+        join_scene_parts(
+            &mut joined,
+            parts.into_iter().map(|(_, part)| part).collect(),
+        );
+        let joined = SceneTree::parse(&joined.to_pretty_string()).unwrap();
+        let entities = joined
+            .0
+            .field("entities")
+            .and_then(RonValue::entries)
+            .unwrap();
+        let find = |name: &str| {
+            entities
+                .iter()
+                .find(|(_, entity)| guid_of(entity).as_deref() == Some(name))
+                .unwrap()
+        };
+        let (a, _) = find("a");
+        let (_, c) = find("c");
+        let value = component(c, target).unwrap();
+        assert_eq!(
+            value.field("entity"),
+            Some(&RonValue::Tuple(Some("Some".to_string()), vec![a.clone()]))
+        );
+        assert_eq!(
+            value.field("count"),
+            Some(&RonValue::Literal("11".to_string())),
+            "numbers equal to an entity id are kept"
+        );
+    }
+
+    #[test]
+    fn backup_of_split_scene_is_whole_scene() {
+        let dir = TempDir::new("split_bak");
+        let file = dir.join("level.scn.ron");
+
+        write_split_scene(&file, &scene(), &registry(), 2).unwrap();
+        let changed = scene().replace("(\"a\")", "(\"e\")");
+        write_split_scene(&file, &changed, &registry(), 2).unwrap();
+        assert!(!part_path(&file, "a").exists());
+        assert!(part_path(&file, "e").exists());
+
+        let backup = fs::read_to_string(crate::save::backup_path(&file, 1)).unwrap();
+        let mut backup = SceneTree::parse(&backup).unwrap();
+        assert_eq!(scene_part_names(&backup), None);
+        assert_eq!(backup.entities_mut().unwrap().len(), 3);
+
+        crate::save::restore_newest_backup(&file).unwrap();
+        let text = read_scene_text(&file).unwrap();
+        assert!(text.contains("\"a\""));
+        assert!(!text.contains("\"e\""));
+    }
+}
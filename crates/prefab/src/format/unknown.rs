@@ -0,0 +1,164 @@
+use bevy::{prelude::*, reflect::TypeRegistry};
+
+use super::{ron_tree::RonValue, SceneTree};
+
+/// Components from a scene file whose types are not registered in this build.
+/// They are kept as RON text and written back unchanged on save.
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct UnknownComponents(pub Vec<UnknownComponent>);
+
+/// Single unknown component stored as RON text
+#[derive(Reflect, Default, Clone, Debug, PartialEq, Eq)]
+pub struct UnknownComponent {
+    pub type_path: String,
+    pub ron: String,
+}
+
+impl UnknownComponent {
+    fn to_ron_value(&self) -> RonValue {
+        RonValue::Struct(
+            None,
+            vec![
+                ("type_path".to_string(), RonValue::string(&self.type_path)),
+                ("ron".to_string(), RonValue::string(&self.ron)),
+            ],
+        )
+    }
+
+    fn from_ron_value(value: &RonValue) -> Option<Self> {
+        Some(Self {
+            type_path: value.field("type_path")?.as_str()?,
+            ron: value.field("ron")?.as_str()?,
+        })
+    }
+}
+
+/// Move components of unregistered types into [`UnknownComponents`], so the scene can be deserialized
+pub fn collapse_unknown_components(scene: &mut SceneTree, registry: &TypeRegistry) {
+    let unknown_path = UnknownComponents::type_path();
+    for components in scene.entity_components_mut() {
+        let mut unknown = vec![];
+        components.retain(|(key, value)| {
+            let Some(type_path) = key.as_str() else {
+                return true;
+            };
+            if registry.get_with_type_path(&type_path).is_some() {
+                return true;
+            }
+            warn!("Scene contains unregistered component {type_path}, it will be kept as is");
+            unknown.push(UnknownComponent {
+                type_path,
+                ron: value.to_pretty_string(),
+            });
+            false
+        });
+
+        if unknown.is_empty() {
+            continue;
+        }
+
+        let existing = components
+            .iter_mut()
+            .find(|(key, _)| key.as_str().as_deref() == Some(unknown_path));
+        let items = unknown.iter().map(UnknownComponent::to_ron_value);
+        match existing {
+            Some((_, RonValue::Tuple(None, fields)))
+                if matches!(fields.first(), Some(RonValue::List(_))) =>
+            {
+                if let Some(RonValue::List(list)) = fields.first_mut() {
+                    list.extend(items);
+                }
+            }
+            _ => components.push((
+                RonValue::string(unknown_path),
+                RonValue::Tuple(None, vec![RonValue::List(items.collect())]),
+            )),
+        }
+    }
+}
+
+/// Replace [`UnknownComponents`] entries with the original components they hold
+pub fn expand_unknown_components(scene: &mut SceneTree) {
+    let unknown_path = UnknownComponents::type_path();
+    for components in scene.entity_components_mut() {
+        let Some(index) = components
+            .iter()
+            .position(|(key, _)| key.as_str().as_deref() == Some(unknown_path))
+        else {
+            continue;
+        };
+        let (_, value) = components.remove(index);
+        let RonValue::Tuple(None, fields) = value else {
+            continue;
+        };
+        let Some(RonValue::List(list)) = fields.first() else {
+            continue;
+        };
+        for unknown in list.iter().filter_map(UnknownComponent::from_ron_value) {
+            match RonValue::parse(&unknown.ron) {
+                Ok(value) => components.push((RonValue::string(&unknown.type_path), value)),
+                Err(e) => error!(
+                    "Failed to restore unknown component {}: {}",
+                    unknown.type_path, e
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::loader::PrefabSceneLoader;
+
+    const SCENE: &str = r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "bevy_render::view::visibility::Visibility": Hidden,
+        "my_game::Health": (
+          value: 10,
+          regen: Some(0.5),
+        ),
+      },
+    ),
+  },
+)"#;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(crate::prelude::EditorRegistryPlugin)
+            .add_plugins(crate::format::SceneFormatPlugin)
+            .register_type::<Visibility>();
+        app
+    }
+
+    #[test]
+    fn loads_scene_with_unknown_component() {
+        let mut app = app();
+        let loader = PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_str(SCENE).unwrap();
+
+        let components = &scene.entities[0].components;
+        assert_eq!(components.len(), 2);
+        let unknown = components
+            .iter()
+            .find_map(|c| UnknownComponents::from_reflect(c.as_ref()))
+            .unwrap();
+        assert_eq!(unknown.0.len(), 1);
+        assert_eq!(unknown.0[0].type_path, "my_game::Health");
+    }
+
+    #[test]
+    fn unknown_components_are_written_back_unchanged() {
+        let app = app();
+        let mut tree = SceneTree::parse(SCENE).unwrap();
+        collapse_unknown_components(&mut tree, &app.world().resource::<AppTypeRegistry>().read());
+        assert!(!tree.to_pretty_string().contains("\"my_game::Health\": ("));
+
+        expand_unknown_components(&mut tree);
+        assert_eq!(tree.to_pretty_string(), SCENE);
+    }
+}
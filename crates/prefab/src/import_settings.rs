@@ -0,0 +1,412 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{
+        meta::{AssetAction, AssetMeta, AssetMetaDyn},
+        AssetLoader,
+    },
+    gltf::{GltfLoader, GltfLoaderSettings},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        texture::{
+            ImageAddressMode, ImageFilterMode, ImageFormat, ImageFormatSetting, ImageLoader,
+            ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+        },
+    },
+};
+
+use crate::save::write_file_atomic;
+
+/// Extensions of images loaded by [`ImageLoader`], which can have [`ImageImportSettings`]
+pub const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "tga", "webp", "dds", "ktx2", "basis",
+];
+
+/// Extension of sidecar files with import settings, the same files are read by the asset server
+pub const META_EXTENSION: &str = "meta";
+
+/// Texture filtering of an image
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Sampler of [`ImagePlugin`], its wrapping can't be changed per image
+    #[default]
+    Default,
+    /// Sharp pixels, for pixel art
+    Nearest,
+    Linear,
+}
+
+/// Texture wrapping of an image outside of `0..1` texture coordinates
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureWrap {
+    #[default]
+    ClampToEdge,
+    /// Tiling textures
+    Repeat,
+    MirrorRepeat,
+}
+
+/// Format of image file content
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFormatHint {
+    #[default]
+    FromExtension,
+    /// Detect format from the file content
+    Guess,
+    Png,
+    Jpeg,
+    Bmp,
+    Tga,
+    WebP,
+    Dds,
+    Ktx2,
+    Basis,
+}
+
+/// Loading options of an image, stored in the `.meta` file next to it
+#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+#[reflect(Default)]
+pub struct ImageImportSettings {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+    /// Color textures are sRGB, normal maps and other data textures are linear
+    pub is_srgb: bool,
+    pub format: ImageFormatHint,
+}
+
+impl Default for ImageImportSettings {
+    fn default() -> Self {
+        Self {
+            filter: TextureFilter::Default,
+            wrap: TextureWrap::ClampToEdge,
+            is_srgb: true,
+            format: ImageFormatHint::FromExtension,
+        }
+    }
+}
+
+impl ImageImportSettings {
+    /// Settings from [`ImageLoaderSettings`], options without a matching field are ignored
+    pub const fn from_loader_settings(settings: &ImageLoaderSettings) -> Self {
+        let (filter, wrap) = match &settings.sampler {
+            ImageSampler::Default => (TextureFilter::Default, TextureWrap::ClampToEdge),
+            ImageSampler::Descriptor(descriptor) => (
+                match descriptor.mag_filter {
+                    ImageFilterMode::Nearest => TextureFilter::Nearest,
+                    ImageFilterMode::Linear => TextureFilter::Linear,
+                },
+                match descriptor.address_mode_u {
+                    ImageAddressMode::Repeat => TextureWrap::Repeat,
+                    ImageAddressMode::MirrorRepeat => TextureWrap::MirrorRepeat,
+                    _ => TextureWrap::ClampToEdge,
+                },
+            ),
+        };
+        let format = match settings.format {
+            ImageFormatSetting::FromExtension => ImageFormatHint::FromExtension,
+            ImageFormatSetting::Guess => ImageFormatHint::Guess,
+            ImageFormatSetting::Format(format) => match format {
+                ImageFormat::Png => ImageFormatHint::Png,
+                ImageFormat::Jpeg => ImageFormatHint::Jpeg,
+                ImageFormat::Bmp => ImageFormatHint::Bmp,
+                ImageFormat::Tga => ImageFormatHint::Tga,
+                ImageFormat::WebP => ImageFormatHint::WebP,
+                ImageFormat::Dds => ImageFormatHint::Dds,
+                ImageFormat::Ktx2 => ImageFormatHint::Ktx2,
+                ImageFormat::Basis => ImageFormatHint::Basis,
+                _ => ImageFormatHint::Guess,
+            },
+        };
+        Self {
+            filter,
+            wrap,
+            is_srgb: settings.is_srgb,
+            format,
+        }
+    }
+
+    /// Wrapping replaces the whole sampler of the image, so it can't be combined with the default filter
+    pub fn needs_explicit_filter(&self) -> bool {
+        self.filter == TextureFilter::Default && self.wrap != TextureWrap::ClampToEdge
+    }
+
+    /// Write the settings into loader settings of the asset server.
+    /// Wrap is only applied with an explicit filter, see [`Self::needs_explicit_filter`].
+    pub fn apply(&self, settings: &mut ImageLoaderSettings) {
+        settings.is_srgb = self.is_srgb;
+        settings.format = match self.format {
+            ImageFormatHint::FromExtension => ImageFormatSetting::FromExtension,
+            ImageFormatHint::Guess => ImageFormatSetting::Guess,
+            ImageFormatHint::Png => ImageFormatSetting::Format(ImageFormat::Png),
+            ImageFormatHint::Jpeg => ImageFormatSetting::Format(ImageFormat::Jpeg),
+            ImageFormatHint::Bmp => ImageFormatSetting::Format(ImageFormat::Bmp),
+            ImageFormatHint::Tga => ImageFormatSetting::Format(ImageFormat::Tga),
+            ImageFormatHint::WebP => ImageFormatSetting::Format(ImageFormat::WebP),
+            ImageFormatHint::Dds => ImageFormatSetting::Format(ImageFormat::Dds),
+            ImageFormatHint::Ktx2 => ImageFormatSetting::Format(ImageFormat::Ktx2),
+            ImageFormatHint::Basis => ImageFormatSetting::Format(ImageFormat::Basis),
+        };
+        let mut descriptor = match self.filter {
+            TextureFilter::Default => {
+                settings.sampler = ImageSampler::Default;
+                return;
+            }
+            TextureFilter::Nearest => ImageSamplerDescriptor::nearest(),
+            TextureFilter::Linear => ImageSamplerDescriptor::linear(),
+        };
+        let address_mode = match self.wrap {
+            TextureWrap::ClampToEdge => ImageAddressMode::ClampToEdge,
+            TextureWrap::Repeat => ImageAddressMode::Repeat,
+            TextureWrap::MirrorRepeat => ImageAddressMode::MirrorRepeat,
+        };
+        descriptor.address_mode_u = address_mode;
+        descriptor.address_mode_v = address_mode;
+        descriptor.address_mode_w = address_mode;
+        settings.sampler = ImageSampler::Descriptor(descriptor);
+    }
+}
+
+/// Loading options of a glTF model, stored in the `.meta` file next to it
+#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+#[reflect(Default)]
+pub struct GltfImportSettings {
+    pub load_meshes: bool,
+    pub load_materials: bool,
+    pub load_cameras: bool,
+    pub load_lights: bool,
+    /// Include the source glTF document in the loaded asset
+    pub include_source: bool,
+}
+
+impl Default for GltfImportSettings {
+    fn default() -> Self {
+        Self::from_loader_settings(&GltfLoaderSettings::default())
+    }
+}
+
+impl GltfImportSettings {
+    pub const fn from_loader_settings(settings: &GltfLoaderSettings) -> Self {
+        Self {
+            load_meshes: !settings.load_meshes.is_empty(),
+            load_materials: !settings.load_materials.is_empty(),
+            load_cameras: settings.load_cameras,
+            load_lights: settings.load_lights,
+            include_source: settings.include_source,
+        }
+    }
+
+    /// Write the settings into loader settings of the asset server
+    pub fn apply(&self, settings: &mut GltfLoaderSettings) {
+        let usages = |load: bool| {
+            if load {
+                RenderAssetUsages::default()
+            } else {
+                RenderAssetUsages::empty()
+            }
+        };
+        settings.load_meshes = usages(self.load_meshes);
+        settings.load_materials = usages(self.load_materials);
+        settings.load_cameras = self.load_cameras;
+        settings.load_lights = self.load_lights;
+        settings.include_source = self.include_source;
+    }
+}
+
+/// Import settings of an image or a model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssetImportSettings {
+    Image(ImageImportSettings),
+    Gltf(GltfImportSettings),
+}
+
+impl AssetImportSettings {
+    /// Settings of the asset at `path` from its `.meta` file, or defaults when there is no file.
+    /// Returns `None` for assets without import settings.
+    pub fn load(asset_root: impl AsRef<Path>, path: &str) -> Option<Self> {
+        let asset_root = asset_root.as_ref();
+        let file = path.split('#').next().unwrap_or_default();
+        let ext = file
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        if ext == "gltf" || ext == "glb" {
+            Some(Self::Gltf(
+                gltf_import_settings(asset_root, file).unwrap_or_default(),
+            ))
+        } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Image(
+                image_import_settings(asset_root, file).unwrap_or_default(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Write the settings to the `.meta` file of the asset at `path`
+    pub fn save(&self, asset_root: impl AsRef<Path>, path: &str) -> io::Result<()> {
+        let file = path.split('#').next().unwrap_or_default();
+        match self {
+            Self::Image(settings) if settings.needs_explicit_filter() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "choose Nearest or Linear filter to change the texture wrap",
+            )),
+            Self::Image(settings) => update_loader_settings::<ImageLoader>(
+                asset_root.as_ref(),
+                file,
+                |loader_settings| settings.apply(loader_settings),
+            ),
+            Self::Gltf(settings) => {
+                update_loader_settings::<GltfLoader>(asset_root.as_ref(), file, |loader_settings| {
+                    settings.apply(loader_settings)
+                })
+            }
+        }
+    }
+}
+
+/// Sidecar file with import settings of the asset at `path`
+pub fn meta_path(asset_root: impl AsRef<Path>, path: &str) -> PathBuf {
+    let file = path.split('#').next().unwrap_or_default();
+    asset_root.as_ref().join(format!("{file}.{META_EXTENSION}"))
+}
+
+/// Image settings from the `.meta` file of the image at `path`, if it has one
+pub fn image_import_settings(
+    asset_root: impl AsRef<Path>,
+    path: &str,
+) -> Option<ImageImportSettings> {
+    read_loader_settings::<ImageLoader>(asset_root.as_ref(), path)
+        .map(|settings| ImageImportSettings::from_loader_settings(&settings))
+}
+
+/// Model settings from the `.meta` file of the glTF model at `path`, if it has one
+pub fn gltf_import_settings(
+    asset_root: impl AsRef<Path>,
+    path: &str,
+) -> Option<GltfImportSettings> {
+    read_loader_settings::<GltfLoader>(asset_root.as_ref(), path)
+        .map(|settings| GltfImportSettings::from_loader_settings(&settings))
+}
+
+fn read_loader_settings<L: AssetLoader>(asset_root: &Path, path: &str) -> Option<L::Settings> {
+    let bytes = fs::read(meta_path(asset_root, path)).ok()?;
+    match AssetMeta::<L, ()>::deserialize(&bytes).ok()?.asset {
+        AssetAction::Load { settings, .. } => Some(settings),
+        AssetAction::Process { .. } | AssetAction::Ignore => None,
+    }
+}
+
+/// Change loader settings in the `.meta` file of `path`, keeping settings that are not changed
+fn update_loader_settings<L: AssetLoader>(
+    asset_root: &Path,
+    path: &str,
+    update: impl FnOnce(&mut L::Settings),
+) -> io::Result<()> {
+    let file = meta_path(asset_root, path);
+    let mut meta = match fs::read(&file) {
+        Ok(bytes) => AssetMeta::<L, ()>::deserialize(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => AssetMeta::new(AssetAction::Load {
+            loader: std::any::type_name::<L>().to_string(),
+            settings: L::Settings::default(),
+        }),
+        Err(e) => return Err(e),
+    };
+    let AssetAction::Load { settings, .. } = &mut meta.asset else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not load the asset with a loader", file.display()),
+        ));
+    };
+    update(settings);
+    write_file_atomic(&file, &meta.serialize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn image_settings_are_saved_as_asset_meta() {
+        let dir = TempDir::new("meta");
+        fs::create_dir_all(dir.join("textures")).unwrap();
+
+        assert_eq!(
+            AssetImportSettings::load(&dir, "textures/tiles.png"),
+            Some(AssetImportSettings::Image(ImageImportSettings::default()))
+        );
+        assert_eq!(AssetImportSettings::load(&dir, "music.ogg"), None);
+
+        let settings = ImageImportSettings {
+            filter: TextureFilter::Nearest,
+            wrap: TextureWrap::Repeat,
+            is_srgb: false,
+            format: ImageFormatHint::FromExtension,
+        };
+        AssetImportSettings::Image(settings.clone())
+            .save(&dir, "textures/tiles.png")
+            .unwrap();
+        let meta = fs::read_to_string(dir.join("textures/tiles.png.meta")).unwrap();
+        assert!(
+            meta.contains(std::any::type_name::<ImageLoader>()),
+            "{meta}"
+        );
+        assert_eq!(
+            image_import_settings(&dir, "textures/tiles.png"),
+            Some(settings.clone())
+        );
+
+        let mut loader_settings = ImageLoaderSettings::default();
+        settings.apply(&mut loader_settings);
+        assert!(!loader_settings.is_srgb);
+        let ImageSampler::Descriptor(descriptor) = &loader_settings.sampler else {
+            panic!("sampler is not set");
+        };
+        assert!(matches!(descriptor.mag_filter, ImageFilterMode::Nearest));
+        assert!(matches!(
+            descriptor.address_mode_v,
+            ImageAddressMode::Repeat
+        ));
+    }
+
+    #[test]
+    fn wrap_needs_explicit_filter() {
+        let dir = TempDir::new("meta_wrap");
+        let settings = ImageImportSettings {
+            wrap: TextureWrap::Repeat,
+            ..default()
+        };
+        assert!(settings.needs_explicit_filter());
+        let err = AssetImportSettings::Image(settings.clone())
+            .save(&dir, "tiles.png")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!dir.join("tiles.png.meta").exists());
+
+        // Sampler of `ImagePlugin` is kept instead of a guessed filter
+        let mut loader_settings = ImageLoaderSettings::default();
+        settings.apply(&mut loader_settings);
+        assert!(matches!(loader_settings.sampler, ImageSampler::Default));
+    }
+
+    #[test]
+    fn gltf_settings_round_trip() {
+        let dir = TempDir::new("gltf_meta");
+
+        let settings = GltfImportSettings {
+            load_cameras: false,
+            load_lights: false,
+            ..default()
+        };
+        AssetImportSettings::Gltf(settings.clone())
+            .save(&dir, "level.glb#Scene0")
+            .unwrap();
+        assert_eq!(gltf_import_settings(&dir, "level.glb"), Some(settings));
+        assert_eq!(image_import_settings(&dir, "level.glb"), None);
+    }
+}
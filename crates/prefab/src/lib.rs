@@ -2,22 +2,49 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+/// Asset paths stored in string fields of components
+pub mod asset_paths;
+/// Moving asset files and rewriting their paths in scene files
+pub mod asset_rename;
+/// Command line tools for scene files
+pub mod cli;
 /// Contains all component for prefab logic
 pub mod component;
+/// Semantic comparison of two scenes
+pub mod diff;
+/// Per-asset import settings stored in sidecar `.meta` files
+pub mod import_settings;
 /// Contains systems for loading prefab from file
 pub mod load;
+/// Three-way merge of scene files for version control
+pub mod merge;
+/// Per-instance edits of spawned prefabs
+pub mod overrides;
+/// Creating prefabs from scene entities and unpacking prefab instances
+pub mod pack;
 /// Module contains all prefab plugin extensions
 pub mod plugins;
 /// Contains systems for saving prefab
 pub mod save;
 /// Contains systems for spawning prefabs
 pub mod spawn_system;
+/// Finding asset files that are not used by any scene
+pub mod unused_assets;
+/// Checks of scene files for CI
+pub mod validate;
 
 /// Module for saving subscene state (like edit gltf entities)
 pub mod sub_scene;
 
 pub mod editor_registry;
 
+/// Scene file format: versioning, migrations and asset loader
+pub mod format;
+
+/// Temporary directories for tests
+#[cfg(test)]
+pub(crate) mod test_dir;
+
 use bevy::prelude::*;
 
 use space_shared::EditorState;
@@ -25,13 +52,19 @@ use space_shared::EditorState;
 /// Public usage of packages that used in this crate
 pub mod ext {
     pub use bevy::prelude::*;
+    pub use uuid;
 }
 
 /// All useful structure from this crate
 pub mod prelude {
+    pub use crate::asset_paths::AssetPathsExt;
     pub use crate::component::*;
     pub use crate::editor_registry::*;
+    pub use crate::format::migration::*;
+    pub use crate::format::unknown::{UnknownComponent, UnknownComponents};
+    pub use crate::format::SceneFormatPlugin;
     pub use crate::load::PrefabBundle;
+    pub use crate::overrides::{PrefabChildPath, PrefabOverride, PrefabOverrides};
     pub use crate::plugins::*;
     pub use crate::save::*;
     pub use crate::sub_scene::*;
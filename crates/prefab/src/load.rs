@@ -1,8 +1,21 @@
-use bevy::prelude::*;
+use std::{any::TypeId, fmt};
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, LoadContext, LoadDirectError},
+    prelude::*,
+    reflect::TypeRegistryArc,
+};
 use bevy_scene_hook::SceneHook;
-use space_shared::PrefabMarker;
+use space_shared::{
+    toast::{ToastKind, ToastMessage},
+    PrefabMarker,
+};
 
-use crate::prelude::EditorRegistryExt;
+use crate::{
+    format::loader::PrefabSceneLoaderSettings,
+    overrides::{apply_override_to_scene, PrefabOverride, PrefabOverridesPending},
+    prelude::EditorRegistryExt,
+};
 
 use super::save::ChildrenPrefab;
 
@@ -45,11 +58,19 @@ impl Plugin for LoadPlugin {
     #[cfg(not(tarpaulin_include))]
     fn build(&self, app: &mut App) {
         app.editor_registry::<PrefabLoader>();
+        app.register_type::<PrefabVariant>();
+        app.add_event::<ToastMessage>();
 
         app.add_systems(
             Update,
             load_prefab.after(bevy_scene_hook::Systems::SceneHookRunner),
         );
+        app.add_systems(
+            Update,
+            reload_modified_prefabs
+                .after(bevy_scene_hook::Systems::SceneHookRunner)
+                .before(load_prefab),
+        );
         app.add_systems(
             Update,
             conflict_resolve
@@ -57,6 +78,7 @@ impl Plugin for LoadPlugin {
                 .before(load_prefab),
         );
         app.add_systems(Update, auto_children);
+        app.add_systems(Update, report_prefab_load_errors);
     }
 }
 
@@ -93,14 +115,13 @@ fn load_prefab(
             commands.entity(e).insert(VisibilityBundle::default());
         }
 
-        //remove old scene
+        //remove old scene, other children of the instance are kept
         if let Some(children) = children {
             for child in children {
                 if auto_children.contains(*child) {
                     commands.entity(*child).despawn_recursive();
                 }
             }
-            commands.entity(e).clear_children();
         }
 
         let scene: Handle<DynamicScene> = assets.load(&l.path);
@@ -108,12 +129,46 @@ fn load_prefab(
         let id = commands
             .spawn(DynamicSceneBundle { scene, ..default() })
             .insert(SceneHook::new(move |_e, cmd| {
-                cmd.insert(PrefabAutoChild);
+                // Content is not edited and undone by itself, only through the instance
+                cmd.insert(PrefabAutoChild).remove::<PrefabMarker>();
             }))
             .insert(PrefabAutoChild)
             .id();
 
-        commands.entity(e).push_children(&[id]);
+        commands
+            .entity(e)
+            .push_children(&[id])
+            .insert(PrefabOverridesPending);
+    }
+}
+
+/// Respawn instances whose prefab file was changed on disk.
+/// Instance entity is kept, so its transform, parent and [`PrefabOverrides`](crate::overrides::PrefabOverrides) survive the reload.
+fn reload_modified_prefabs(
+    mut events: EventReader<AssetEvent<DynamicScene>>,
+    mut instances: Query<(&mut PrefabLoader, &Children)>,
+    scenes: Query<&Handle<DynamicScene>, With<PrefabAutoChild>>,
+) {
+    let modified = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if modified.is_empty() {
+        return;
+    }
+    for (mut loader, children) in instances.iter_mut() {
+        let is_modified = children.iter().any(|child| {
+            scenes
+                .get(*child)
+                .is_ok_and(|scene| modified.contains(&scene.id()))
+        });
+        if is_modified {
+            info!("Reloading prefab {}", loader.path);
+            loader.set_changed();
+        }
     }
 }
 
@@ -126,7 +181,7 @@ fn conflict_resolve(
     }
 }
 
-fn auto_children(
+pub(crate) fn auto_children(
     mut commands: Commands,
     query: Query<(Entity, &ChildrenPrefab)>,
     existing_entity: Query<Entity>,
@@ -142,9 +197,110 @@ fn auto_children(
     }
 }
 
+/// Resource of a prefab file, which makes it a variant of another prefab.
+///
+/// Variant is loaded as its base prefab with the overrides applied on top,
+/// so changes of the base are picked up by all its variants.
+#[derive(Resource, Reflect, Default, Clone, Debug)]
+#[reflect(Resource, Default)]
+pub struct PrefabVariant {
+    /// Asset path of the base prefab
+    pub base: String,
+    pub overrides: Vec<PrefabOverride>,
+}
+
+/// Possible errors of prefab variant resolution
+#[derive(Debug)]
+pub enum PrefabVariantError {
+    /// Variant does not name its base prefab
+    MissingBase,
+    /// Variants chain which loops back to an already loading variant
+    Cycle(Vec<String>),
+    Base(Box<LoadDirectError>),
+}
+
+impl fmt::Display for PrefabVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBase => write!(f, "Prefab variant has no base prefab"),
+            Self::Cycle(chain) => write!(f, "Prefab variant cycle: {}", chain.join(" -> ")),
+            Self::Base(e) => write!(f, "Could not load base prefab: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrefabVariantError {}
+
+/// Replace variant scene with its base prefab and apply the variant overrides to it
+pub(crate) async fn resolve_prefab_variant(
+    mut scene: DynamicScene,
+    registry: &TypeRegistryArc,
+    settings: &PrefabSceneLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+) -> Result<DynamicScene, PrefabVariantError> {
+    let Some(index) = scene.resources.iter().position(|res| {
+        res.get_represented_type_info()
+            .is_some_and(|info| info.type_id() == TypeId::of::<PrefabVariant>())
+    }) else {
+        return Ok(scene);
+    };
+    let variant =
+        PrefabVariant::from_reflect(scene.resources.remove(index).as_ref()).unwrap_or_default();
+    if variant.base.is_empty() {
+        return Err(PrefabVariantError::MissingBase);
+    }
+
+    let path = load_context.asset_path().to_string();
+    let mut chain = settings.variant_chain.clone();
+    let is_cycle = chain.contains(&path);
+    chain.push(path.clone());
+    if is_cycle {
+        return Err(PrefabVariantError::Cycle(chain));
+    }
+    if !scene.entities.is_empty() {
+        warn!("Prefab variant {path} contains entities, they are ignored");
+    }
+
+    let mut base = load_context
+        .loader()
+        .with_settings(move |s: &mut PrefabSceneLoaderSettings| {
+            s.variant_chain.clone_from(&chain);
+        })
+        .direct()
+        .load::<DynamicScene>(variant.base.clone())
+        .await
+        .map_err(|e| PrefabVariantError::Base(Box::new(e)))?
+        .take();
+
+    let registry = registry.read();
+    for value in variant.overrides.iter() {
+        if let Err(e) = apply_override_to_scene(&mut base, value, &registry) {
+            warn!(
+                "Prefab variant {path} could not apply override of {} on {}: {e}",
+                value.component, value.target
+            );
+        }
+    }
+    Ok(base)
+}
+
+fn report_prefab_load_errors(
+    mut events: EventReader<AssetLoadFailedEvent<DynamicScene>>,
+    mut toast: EventWriter<ToastMessage>,
+) {
+    for event in events.read() {
+        error!("Failed to load prefab {}: {}", event.path, event.error);
+        toast.send(ToastMessage::new(
+            &format!("Failed to load prefab {}: {}", event.path, event.error),
+            ToastKind::Error,
+        ));
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_dir::TempDir;
 
     #[test]
     fn new_prefab_bundler() {
@@ -175,4 +331,226 @@ mod test {
             .query_filtered::<Entity, With<PrefabAutoChild>>();
         assert_eq!(query.iter(&app.world()).count(), 2);
     }
+
+    fn variant_file(app: &App, base: &str, overrides: Vec<PrefabOverride>) -> String {
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.insert_resource(PrefabVariant {
+            base: base.to_string(),
+            overrides,
+        });
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        DynamicScene::from_world(&world)
+            .serialize(&registry)
+            .unwrap()
+    }
+
+    fn transform_override(field: &str, value: &str) -> PrefabOverride {
+        PrefabOverride {
+            target: crate::overrides::PrefabChildPath::Names(vec!["enemy".to_string()]),
+            component: Transform::type_path().to_string(),
+            field: field.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn load(app: &mut App, path: &str) -> Result<Transform, ()> {
+        let handle: Handle<DynamicScene> =
+            app.world().resource::<AssetServer>().load(path.to_string());
+        for _ in 0..200 {
+            app.update();
+            let server = app.world().resource::<AssetServer>();
+            match server.load_state(&handle) {
+                bevy::asset::LoadState::Loaded => {
+                    let scenes = app.world().resource::<Assets<DynamicScene>>();
+                    let scene = scenes.get(&handle).unwrap();
+                    return Ok(scene.entities[0]
+                        .components
+                        .iter()
+                        .find(|c| {
+                            c.get_represented_type_info()
+                                .is_some_and(|i| i.type_path() == Transform::type_path())
+                        })
+                        .and_then(|c| Transform::from_reflect(c.as_ref()))
+                        .unwrap());
+                }
+                bevy::asset::LoadState::Failed(_) => return Err(()),
+                _ => std::thread::sleep(std::time::Duration::from_millis(5)),
+            }
+        }
+        panic!("{path} was not loaded");
+    }
+
+    #[test]
+    fn prefab_variants() {
+        let dir = TempDir::new("variant");
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..default()
+            },
+            bevy::scene::ScenePlugin,
+            crate::prelude::EditorRegistryPlugin,
+            crate::format::SceneFormatPlugin,
+            LoadPlugin,
+        ))
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>();
+
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.spawn((Name::new("enemy"), Transform::default()));
+        let base = DynamicScene::from_world(&world)
+            .serialize(&app.world().resource::<AppTypeRegistry>().read())
+            .unwrap();
+
+        let files = [
+            ("base.scn.ron", base),
+            (
+                "variant.scn.ron",
+                variant_file(
+                    &app,
+                    "base.scn.ron",
+                    vec![transform_override(
+                        "translation",
+                        "(x: 0.0, y: 2.0, z: 0.0)",
+                    )],
+                ),
+            ),
+            (
+                "nested.scn.ron",
+                variant_file(
+                    &app,
+                    "variant.scn.ron",
+                    vec![transform_override("scale", "(x: 3.0, y: 3.0, z: 3.0)")],
+                ),
+            ),
+            (
+                "loop_a.scn.ron",
+                variant_file(&app, "loop_b.scn.ron", vec![]),
+            ),
+            (
+                "loop_b.scn.ron",
+                variant_file(&app, "loop_a.scn.ron", vec![]),
+            ),
+        ];
+        for (name, text) in files {
+            std::fs::write(dir.join(name), text).unwrap();
+        }
+
+        let variant = load(&mut app, "variant.scn.ron");
+        let nested = load(&mut app, "nested.scn.ron");
+        let cycle = load(&mut app, "loop_a.scn.ron");
+        app.update();
+
+        assert_eq!(variant.unwrap().translation, Vec3::Y * 2.0);
+        let nested = nested.unwrap();
+        assert_eq!(nested.translation, Vec3::Y * 2.0);
+        assert_eq!(nested.scale, Vec3::splat(3.0));
+
+        assert!(cycle.is_err());
+        let toasts = app.world().resource::<Events<ToastMessage>>();
+        assert!(toasts
+            .get_reader()
+            .read(toasts)
+            .any(|t| t.text.contains("loop_a.scn.ron")));
+    }
+
+    #[test]
+    fn modified_prefab_respawns_instances() {
+        let dir = TempDir::new("reload");
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..default()
+            },
+            HierarchyPlugin,
+            bevy::scene::ScenePlugin,
+            crate::prelude::EditorRegistryPlugin,
+            crate::format::SceneFormatPlugin,
+            LoadPlugin,
+        ))
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>()
+        .add_systems(
+            Update,
+            crate::overrides::apply_pending_overrides.after(auto_children),
+        );
+
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.spawn((Name::new("enemy"), Transform::default()));
+        world.spawn((Name::new("extra"), Transform::default()));
+        let text = DynamicScene::from_world(&world)
+            .serialize(&app.world().resource::<AppTypeRegistry>().read())
+            .unwrap();
+        std::fs::write(dir.join("prefab.scn.ron"), text).unwrap();
+
+        let parent = app.world_mut().spawn(SpatialBundle::default()).id();
+        let instance = app
+            .world_mut()
+            .spawn(PrefabBundle::new("prefab.scn.ron"))
+            .insert((
+                Transform::from_xyz(1.0, 2.0, 3.0),
+                crate::overrides::PrefabOverrides(vec![transform_override(
+                    "scale",
+                    "(x: 2.0, y: 2.0, z: 2.0)",
+                )]),
+            ))
+            .set_parent(parent)
+            .id();
+
+        let names = |app: &mut App| {
+            let mut query = app.world_mut().query::<(&Name, &Transform)>();
+            query
+                .iter(app.world())
+                .map(|(name, tr)| (name.to_string(), *tr))
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..200 {
+            app.update();
+            if names(&mut app).len() == 2 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        app.update();
+        assert_eq!(names(&mut app).len(), 2);
+
+        // Prefab is re-saved with one entity removed and other one moved
+        let handle: Handle<DynamicScene> =
+            app.world().resource::<AssetServer>().load("prefab.scn.ron");
+        world.clear_entities();
+        world.spawn((Name::new("enemy"), Transform::from_xyz(4.0, 0.0, 0.0)));
+        let scene = DynamicScene::from_world(&world);
+        *app.world_mut()
+            .resource_mut::<Assets<DynamicScene>>()
+            .get_mut(&handle)
+            .unwrap() = scene;
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let names = names(&mut app);
+        assert_eq!(names.len(), 1);
+        assert_eq!(names[0].0, "enemy");
+        assert_eq!(names[0].1.translation, Vec3::X * 4.0);
+        assert_eq!(names[0].1.scale, Vec3::splat(2.0));
+
+        assert_eq!(
+            app.world().get::<Transform>(instance).unwrap().translation,
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(app.world().get::<Parent>(instance).unwrap().get(), parent);
+        assert!(app
+            .world()
+            .get::<PrefabOverridesPending>(instance)
+            .is_none());
+    }
 }
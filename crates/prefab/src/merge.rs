@@ -0,0 +1,1138 @@
+use std::fmt;
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        TypePath, TypeRegistration, TypeRegistry,
+    },
+    utils::HashMap,
+};
+use serde::de::DeserializeSeed;
+use uuid::Uuid;
+
+use crate::{
+    component::PrefabGuid,
+    diff::short_type_path,
+    format::{
+        canonical::{canonicalize_scene, remap_typed_entries},
+        loader::{PrefabSceneLoader, PrefabSceneLoaderError},
+        migration::{SceneMigrations, SceneVersion},
+        ron_tree::{RonParseError, RonValue},
+        split::SceneParts,
+        unknown::expand_unknown_components,
+        SceneTree,
+    },
+    save::ChildrenPrefab,
+};
+
+/// Registers types of merge conflicts, so conflicted scene files can be opened in the editor
+pub struct SceneMergePlugin;
+
+impl Plugin for SceneMergePlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.register_type::<MergeConflict>();
+        app.register_type::<ConflictTarget>();
+        app.register_type::<Vec<MergeConflict>>();
+        app.register_type::<Vec<String>>();
+        app.register_type::<Option<String>>();
+        app.register_type::<Option<Uuid>>();
+        app.register_type::<SceneMergeConflicts>();
+    }
+}
+
+/// Version of a conflicting value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeSide {
+    Ours,
+    Theirs,
+}
+
+/// Part of the scene a [`MergeConflict`] is about
+#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+pub enum ConflictTarget {
+    Resource,
+    Entity { path: String, guid: Option<Uuid> },
+}
+
+/// Value changed differently in both merged versions of a scene
+#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    pub target: ConflictTarget,
+    /// Component or resource type path, empty when one version removed the whole entity
+    pub type_path: String,
+    /// Path to the value inside the component: field names, indices and RON map keys
+    pub field: Vec<String>,
+    /// RON text of the value in each version, `None` when the value is missing
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+impl MergeConflict {
+    /// Short description like `room/lamp: Transform.translation.x`
+    pub fn label(&self) -> String {
+        let owner = match &self.target {
+            ConflictTarget::Resource => "Scene settings",
+            ConflictTarget::Entity { path, .. } => path,
+        };
+        if self.type_path.is_empty() {
+            return format!("{owner}: removed in one version");
+        }
+        let mut label = format!("{owner}: {}", short_type_path(&self.type_path));
+        for field in self.field.iter() {
+            label.push('.');
+            label.push_str(field);
+        }
+        label
+    }
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "<none>".to_string());
+        write!(
+            f,
+            "{}: ours {}, theirs {}",
+            self.label(),
+            value(&self.ours),
+            value(&self.theirs)
+        )
+    }
+}
+
+/// Conflicts left by [`merge_scenes`]. Stored in the merged scene file, so the editor
+/// can present them after the file is opened. Not saved with the scene.
+#[derive(Resource, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Resource, Default)]
+pub struct SceneMergeConflicts {
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Possible errors of [`merge_scenes`] and [`resolve_merge_conflict`]
+#[derive(Debug)]
+pub enum SceneMergeError {
+    Load(&'static str, PrefabSceneLoaderError),
+    Parse(RonParseError),
+    Serialize(String),
+    Result(PrefabSceneLoaderError),
+    UnknownType(String),
+    MissingEntity(String),
+    MissingValue(String),
+    Value(String),
+}
+
+impl fmt::Display for SceneMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(version, e) => write!(f, "Could not load {version} version: {e}"),
+            Self::Parse(e) => write!(f, "Could not parse serialized scene: {e}"),
+            Self::Serialize(e) => write!(f, "Could not serialize scene: {e}"),
+            Self::Result(e) => write!(f, "Merged scene can not be loaded: {e}"),
+            Self::UnknownType(type_path) => write!(f, "Type {type_path} is not registered"),
+            Self::MissingEntity(path) => write!(f, "Entity {path} is not found in the scene"),
+            Self::MissingValue(type_path) => write!(f, "{type_path} is not found in the scene"),
+            Self::Value(e) => write!(f, "Could not apply value: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneMergeError {}
+
+/// Result of [`merge_scenes`]
+#[derive(Clone, Debug)]
+pub struct SceneMerge {
+    /// Merged scene file content, with [`SceneMergeConflicts`] resource when there are conflicts
+    pub text: String,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Three-way merge of scene file contents: `base` is the common ancestor of `ours` and `theirs`.
+///
+/// Entities are matched by [`PrefabGuid`], components by type path and values are merged field by field,
+/// so changes to different entities, components or fields never conflict.
+/// Values changed differently in both versions keep `ours` and are listed as conflicts.
+/// Files are loaded with the scene loader, so old versions are migrated first
+/// and components of unregistered types are merged by their RON text.
+pub fn merge_scenes(
+    world: &mut World,
+    base: &str,
+    ours: &str,
+    theirs: &str,
+) -> Result<SceneMerge, SceneMergeError> {
+    let loader = PrefabSceneLoader::from_world(world);
+    let version = world
+        .get_resource::<SceneMigrations>()
+        .map(SceneMigrations::current_version)
+        .unwrap_or_default();
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    let mut ids = HashMap::new();
+    let base = MergeInput::load(&loader, &registry, base, &mut ids)
+        .map_err(|e| e.into_merge_error("base"))?;
+    let ours = MergeInput::load(&loader, &registry, ours, &mut ids)
+        .map_err(|e| e.into_merge_error("ours"))?;
+    let theirs = MergeInput::load(&loader, &registry, theirs, &mut ids)
+        .map_err(|e| e.into_merge_error("theirs"))?;
+
+    let mut conflicts = vec![];
+
+    let mut found = vec![];
+    let resources = merge_values(
+        &mut vec![],
+        Some(&base.resources),
+        Some(&ours.resources),
+        Some(&theirs.resources),
+        &mut found,
+    );
+    conflicts.extend(
+        found
+            .into_iter()
+            .map(|conflict| conflict.into_conflict(ConflictTarget::Resource)),
+    );
+
+    let mut keys = ours.order.clone();
+    keys.extend(
+        theirs
+            .order
+            .iter()
+            .filter(|key| !ours.entities.contains_key(*key))
+            .cloned(),
+    );
+    let mut entities = vec![];
+    for key in keys {
+        let base_entity = base.entities.get(&key);
+        let target = ConflictTarget::Entity {
+            path: ours
+                .names
+                .get(&key)
+                .or_else(|| theirs.names.get(&key))
+                .cloned()
+                .unwrap_or_else(|| key.clone()),
+            guid: Uuid::parse_str(&key).ok(),
+        };
+        let merged = match (ours.entities.get(&key), theirs.entities.get(&key)) {
+            (Some(ours), Some(theirs)) => {
+                let mut found = vec![];
+                let merged = merge_values(
+                    &mut vec![],
+                    base_entity,
+                    Some(ours),
+                    Some(theirs),
+                    &mut found,
+                );
+                conflicts.extend(
+                    found
+                        .into_iter()
+                        .map(|conflict| conflict.into_conflict(target.clone())),
+                );
+                merged
+            }
+            (Some(kept), None) | (None, Some(kept)) => match base_entity {
+                Some(base_entity) if base_entity == kept => None,
+                Some(base_entity) => {
+                    // Removed in one version and changed in the other, keep the changes
+                    let text = |value: Option<&RonValue>| value.map(|_| "entity".to_string());
+                    conflicts.push(MergeConflict {
+                        target,
+                        type_path: String::new(),
+                        field: vec![],
+                        base: text(Some(base_entity)),
+                        ours: text(ours.entities.get(&key)),
+                        theirs: text(theirs.entities.get(&key)),
+                    });
+                    Some(kept.clone())
+                }
+                None => Some(kept.clone()),
+            },
+            (None, None) => None,
+        };
+        if let Some(components) = merged {
+            entities.push((RonValue::Literal(ids[&key].clone()), components));
+        }
+    }
+
+    // Children removed in one version are dropped from parents kept by the merge
+    let kept = entities
+        .iter()
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+    for (_, components) in entities.iter_mut() {
+        let Some((_, RonValue::Tuple(None, items))) =
+            components.entries_mut().and_then(|entries| {
+                entries
+                    .iter_mut()
+                    .find(|(key, _)| key.as_str().as_deref() == Some(ChildrenPrefab::type_path()))
+            })
+        else {
+            continue;
+        };
+        if let Some(RonValue::List(children)) = items.first_mut() {
+            children.retain(|child| kept.contains(child));
+        }
+    }
+
+    let mut resources = match resources {
+        Some(RonValue::Map(resources)) => resources,
+        _ => vec![],
+    };
+    resources.push((
+        RonValue::string(SceneVersion::type_path()),
+        RonValue::Tuple(None, vec![RonValue::Literal(version.to_string())]),
+    ));
+    if !conflicts.is_empty() {
+        let list = SceneMergeConflicts {
+            conflicts: conflicts.clone(),
+        };
+        let text = ron::to_string(&TypedReflectSerializer::new(&list, &registry))
+            .map_err(|e| SceneMergeError::Serialize(e.to_string()))?;
+        resources.push((
+            RonValue::string(SceneMergeConflicts::type_path()),
+            RonValue::parse(&text).map_err(SceneMergeError::Parse)?,
+        ));
+    }
+
+    let mut tree = SceneTree(RonValue::Struct(
+        None,
+        vec![
+            ("resources".to_string(), RonValue::Map(resources)),
+            (
+                "entities".to_string(),
+                RonValue::Map(
+                    entities
+                        .into_iter()
+                        .map(|(id, components)| {
+                            (
+                                id,
+                                RonValue::Struct(
+                                    None,
+                                    vec![("components".to_string(), components)],
+                                ),
+                            )
+                        })
+                        .collect(),
+                ),
+            ),
+        ],
+    ));
+    canonicalize_scene(&mut tree, &registry);
+    let text = tree.to_pretty_string();
+    loader
+        .load_from_str(&text)
+        .map_err(SceneMergeError::Result)?;
+    Ok(SceneMerge { text, conflicts })
+}
+
+/// Scene file prepared for merging
+struct MergeInput {
+    resources: RonValue,
+    /// Components map of every entity by its key
+    entities: HashMap<String, RonValue>,
+    names: HashMap<String, String>,
+    order: Vec<String>,
+}
+
+enum InputError {
+    Load(PrefabSceneLoaderError),
+    Serialize(String),
+    Parse(RonParseError),
+}
+
+impl InputError {
+    fn into_merge_error(self, version: &'static str) -> SceneMergeError {
+        match self {
+            Self::Load(e) => SceneMergeError::Load(version, e),
+            Self::Serialize(e) => SceneMergeError::Serialize(e),
+            Self::Parse(e) => SceneMergeError::Parse(e),
+        }
+    }
+}
+
+impl MergeInput {
+    /// Load scene through the loader and write it back in canonical form.
+    /// Entity ids are replaced with ids shared by all merged versions, taken from `ids` by entity key.
+    fn load(
+        loader: &PrefabSceneLoader,
+        registry: &TypeRegistry,
+        text: &str,
+        ids: &mut HashMap<String, String>,
+    ) -> Result<Self, InputError> {
+        let scene = loader.load_from_str(text).map_err(InputError::Load)?;
+        let text = scene
+            .serialize(registry)
+            .map_err(|e| InputError::Serialize(e.to_string()))?;
+        let mut tree = SceneTree::parse(&text).map_err(InputError::Parse)?;
+        expand_unknown_components(&mut tree);
+        canonicalize_scene(&mut tree, registry);
+
+        let mut resources = tree.resources_mut().cloned().unwrap_or_default();
+        resources
+            .retain(|(key, _)| key.as_str().as_deref() != Some(SceneMergeConflicts::type_path()));
+
+        let entities = tree.entities_mut().cloned().unwrap_or_default();
+        let mut names = HashMap::new();
+        let mut order = vec![];
+        let mut remap = HashMap::new();
+        let mut unnamed = HashMap::<String, usize>::new();
+        for (id, entity) in entities.iter() {
+            let name = component(entity, Name::type_path()).map(|name| match name {
+                RonValue::Tuple(_, items) if items.len() == 1 => items[0].clone(),
+                RonValue::Struct(_, fields) => fields
+                    .iter()
+                    .find(|(field, _)| field == "name")
+                    .map_or_else(|| name.clone(), |(_, value)| value.clone()),
+                _ => name.clone(),
+            });
+            let name = name
+                .map(|name| name.as_str().unwrap_or_else(|| name.to_pretty_string()))
+                .unwrap_or_else(|| "Entity".to_string());
+            // Entities without guid can only be matched by name and order
+            let key = guid_of(entity).unwrap_or_else(|| {
+                let count = unnamed.entry(name.clone()).or_default();
+                *count += 1;
+                format!("{name} #{count}")
+            });
+            let next = ids.len() as u32;
+            let shared = ids
+                .entry(key.clone())
+                .or_insert_with(|| Entity::from_raw(next).to_bits().to_string());
+            if let RonValue::Literal(id) = id {
+                remap.insert(id.clone(), shared.clone());
+            }
+            names.insert(key.clone(), name);
+            order.push(key);
+        }
+
+        let mut by_key = HashMap::new();
+        for ((_, entity), key) in entities.into_iter().zip(order.iter()) {
+            let mut components = entity
+                .field("components")
+                .cloned()
+                .unwrap_or(RonValue::Map(vec![]));
+            if let Some(entries) = components.entries_mut() {
+                remap_typed_entries(entries, &remap, registry);
+            }
+            by_key.insert(key.clone(), components);
+        }
+        remap_typed_entries(&mut resources, &remap, registry);
+        let resources = RonValue::Map(resources);
+
+        // Names are shown as paths, like in scene diffs
+        let parents = by_key
+            .iter()
+            .flat_map(|(key, components)| {
+                children(components)
+                    .into_iter()
+                    .map(move |child| (child, key.clone()))
+            })
+            .collect::<HashMap<_, _>>();
+        let paths = order
+            .iter()
+            .map(|key| {
+                let mut path = vec![names[key].clone()];
+                let mut current = ids[key].clone();
+                while let Some(parent) = parents.get(&current) {
+                    if path.len() > order.len() {
+                        break;
+                    }
+                    path.push(names.get(parent).cloned().unwrap_or_default());
+                    current = ids[parent].clone();
+                }
+                path.reverse();
+                (key.clone(), path.join("/"))
+            })
+            .collect();
+
+        Ok(Self {
+            resources,
+            entities: by_key,
+            names: paths,
+            order,
+        })
+    }
+}
+
+fn component<'a>(entity: &'a RonValue, type_path: &str) -> Option<&'a RonValue> {
+    entity
+        .field("components")
+        .and_then(RonValue::entries)?
+        .iter()
+        .find(|(key, _)| key.as_str().as_deref() == Some(type_path))
+        .map(|(_, value)| value)
+}
+
+fn guid_of(entity: &RonValue) -> Option<String> {
+    match component(entity, PrefabGuid::type_path()) {
+        Some(RonValue::Tuple(None, items)) => items.first().and_then(RonValue::as_str),
+        _ => None,
+    }
+}
+
+/// Entity ids listed in [`ChildrenPrefab`] of a components map
+fn children(components: &RonValue) -> Vec<String> {
+    let children = components.entries().and_then(|entries| {
+        entries
+            .iter()
+            .find(|(key, _)| key.as_str().as_deref() == Some(ChildrenPrefab::type_path()))
+    });
+    match children {
+        Some((_, RonValue::Tuple(None, items))) => match items.first() {
+            Some(RonValue::List(children)) => children
+                .iter()
+                .filter_map(|child| match child {
+                    RonValue::Literal(id) => Some(id.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+/// Step of a path inside a merged value
+#[derive(Clone)]
+enum Step {
+    Field(String),
+    Index(usize),
+    Key(RonValue),
+}
+
+impl Step {
+    fn text(&self) -> String {
+        match self {
+            Self::Field(name) => name.clone(),
+            Self::Index(index) => index.to_string(),
+            Self::Key(key) => key.to_pretty_string(),
+        }
+    }
+}
+
+/// Conflict found inside a components or resources map
+struct FoundConflict {
+    path: Vec<Step>,
+    base: Option<RonValue>,
+    ours: Option<RonValue>,
+    theirs: Option<RonValue>,
+}
+
+impl FoundConflict {
+    fn into_conflict(self, target: ConflictTarget) -> MergeConflict {
+        let mut path = self.path.into_iter();
+        let type_path = match path.next() {
+            Some(Step::Key(key)) => key.as_str().unwrap_or_else(|| key.to_pretty_string()),
+            Some(step) => step.text(),
+            None => String::new(),
+        };
+        let text = |value: Option<RonValue>| value.map(|value| value.to_pretty_string());
+        MergeConflict {
+            target,
+            type_path,
+            field: path.map(|step| step.text()).collect(),
+            base: text(self.base),
+            ours: text(self.ours),
+            theirs: text(self.theirs),
+        }
+    }
+}
+
+/// Three-way merge of a value, `None` when it is missing or removed
+fn merge_values(
+    path: &mut Vec<Step>,
+    base: Option<&RonValue>,
+    ours: Option<&RonValue>,
+    theirs: Option<&RonValue>,
+    conflicts: &mut Vec<FoundConflict>,
+) -> Option<RonValue> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    if let (Some(ours), Some(theirs)) = (ours, theirs) {
+        if let Some(merged) = merge_nested(path, base, ours, theirs, conflicts) {
+            return Some(merged);
+        }
+    }
+    conflicts.push(FoundConflict {
+        path: path.clone(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.or(theirs).cloned()
+}
+
+/// Merge values of the same shape part by part, `None` when the shapes differ
+fn merge_nested(
+    path: &mut Vec<Step>,
+    base: Option<&RonValue>,
+    ours: &RonValue,
+    theirs: &RonValue,
+    conflicts: &mut Vec<FoundConflict>,
+) -> Option<RonValue> {
+    // Children and part lists are merged as sets, so both versions can add entries
+    let is_set = match path.as_slice() {
+        [Step::Key(key)] => key.as_str().is_some_and(|type_path| {
+            type_path == ChildrenPrefab::type_path() || type_path == SceneParts::type_path()
+        }),
+        _ => false,
+    };
+    if is_set {
+        if let Some(merged) = merge_as_set(base, ours, theirs) {
+            return Some(merged);
+        }
+    }
+
+    let mut merge_at = |step: Step, base: Option<&RonValue>, ours: Option<&RonValue>, theirs| {
+        path.push(step);
+        let merged = merge_values(path, base, ours, theirs, conflicts);
+        path.pop();
+        merged
+    };
+
+    match (ours, theirs) {
+        (RonValue::Struct(name, ours_fields), RonValue::Struct(theirs_name, theirs_fields))
+            if name == theirs_name =>
+        {
+            let base_fields = match base {
+                Some(RonValue::Struct(base_name, fields)) if base_name == name => Some(fields),
+                _ => None,
+            };
+            let names = ours_fields.iter().map(|(name, _)| name).chain(
+                theirs_fields
+                    .iter()
+                    .map(|(name, _)| name)
+                    .filter(|name| field_value(Some(ours_fields), name).is_none()),
+            );
+            let fields = names
+                .filter_map(|field| {
+                    merge_at(
+                        Step::Field(field.clone()),
+                        field_value(base_fields, field),
+                        field_value(Some(ours_fields), field),
+                        field_value(Some(theirs_fields), field),
+                    )
+                    .map(|value| (field.clone(), value))
+                })
+                .collect();
+            Some(RonValue::Struct(name.clone(), fields))
+        }
+        (RonValue::Tuple(name, ours_items), RonValue::Tuple(theirs_name, theirs_items))
+            if name == theirs_name && ours_items.len() == theirs_items.len() =>
+        {
+            let base_items = match base {
+                Some(RonValue::Tuple(base_name, items))
+                    if base_name == name && items.len() == ours_items.len() =>
+                {
+                    Some(items)
+                }
+                _ => None,
+            };
+            let items = ours_items
+                .iter()
+                .zip(theirs_items.iter())
+                .enumerate()
+                .map(|(index, (ours, theirs))| {
+                    let base = base_items.map(|items| &items[index]);
+                    merge_at(Step::Index(index), base, Some(ours), Some(theirs))
+                        .unwrap_or_else(|| ours.clone())
+                })
+                .collect();
+            Some(RonValue::Tuple(name.clone(), items))
+        }
+        (RonValue::List(ours_items), RonValue::List(theirs_items))
+            if ours_items.len() == theirs_items.len() =>
+        {
+            let base_items = match base {
+                Some(RonValue::List(items)) if items.len() == ours_items.len() => Some(items),
+                _ => None,
+            };
+            let items = ours_items
+                .iter()
+                .zip(theirs_items.iter())
+                .enumerate()
+                .map(|(index, (ours, theirs))| {
+                    let base = base_items.map(|items| &items[index]);
+                    merge_at(Step::Index(index), base, Some(ours), Some(theirs))
+                        .unwrap_or_else(|| ours.clone())
+                })
+                .collect();
+            Some(RonValue::List(items))
+        }
+        (RonValue::Map(ours_entries), RonValue::Map(theirs_entries)) => {
+            let base_entries = base.and_then(RonValue::entries);
+            let keys = ours_entries.iter().map(|(key, _)| key).chain(
+                theirs_entries
+                    .iter()
+                    .map(|(key, _)| key)
+                    .filter(|key| entry_value(Some(ours_entries), key).is_none()),
+            );
+            let entries = keys
+                .filter_map(|key| {
+                    merge_at(
+                        Step::Key(key.clone()),
+                        entry_value(base_entries, key),
+                        entry_value(Some(ours_entries), key),
+                        entry_value(Some(theirs_entries), key),
+                    )
+                    .map(|value| (key.clone(), value))
+                })
+                .collect();
+            Some(RonValue::Map(entries))
+        }
+        _ => None,
+    }
+}
+
+fn field_value<'a>(
+    fields: Option<&'a Vec<(String, RonValue)>>,
+    field: &str,
+) -> Option<&'a RonValue> {
+    fields?
+        .iter()
+        .find(|(name, _)| name == field)
+        .map(|(_, value)| value)
+}
+
+fn entry_value<'a>(
+    entries: Option<&'a Vec<(RonValue, RonValue)>>,
+    key: &RonValue,
+) -> Option<&'a RonValue> {
+    entries?
+        .iter()
+        .find(|(entry_key, _)| entry_key == key)
+        .map(|(_, value)| value)
+}
+
+/// Merge lists inside `Name([..])` or `(field: [..])` as sets: entries added by either version are kept
+/// and entries removed by either version are dropped
+fn merge_as_set(base: Option<&RonValue>, ours: &RonValue, theirs: &RonValue) -> Option<RonValue> {
+    match (ours, theirs) {
+        (RonValue::List(ours_items), RonValue::List(theirs_items)) => {
+            let base_items = match base {
+                Some(RonValue::List(items)) => items.as_slice(),
+                _ => &[],
+            };
+            let mut items = ours_items
+                .iter()
+                .filter(|item| theirs_items.contains(item) || !base_items.contains(item))
+                .cloned()
+                .collect::<Vec<_>>();
+            items.extend(
+                theirs_items
+                    .iter()
+                    .filter(|item| !base_items.contains(item) && !ours_items.contains(item))
+                    .cloned(),
+            );
+            Some(RonValue::List(items))
+        }
+        (RonValue::Tuple(name, ours_items), RonValue::Tuple(theirs_name, theirs_items))
+            if name == theirs_name && ours_items.len() == 1 && theirs_items.len() == 1 =>
+        {
+            let base = match base {
+                Some(RonValue::Tuple(_, items)) => items.first(),
+                _ => None,
+            };
+            merge_as_set(base, &ours_items[0], &theirs_items[0])
+                .map(|items| RonValue::Tuple(name.clone(), vec![items]))
+        }
+        (RonValue::Struct(name, ours_fields), RonValue::Struct(theirs_name, theirs_fields))
+            if name == theirs_name
+                && ours_fields.len() == 1
+                && theirs_fields.len() == 1
+                && ours_fields[0].0 == theirs_fields[0].0 =>
+        {
+            let field = &ours_fields[0].0;
+            merge_as_set(
+                base.and_then(|base| base.field(field)),
+                &ours_fields[0].1,
+                &theirs_fields[0].1,
+            )
+            .map(|items| RonValue::Struct(name.clone(), vec![(field.clone(), items)]))
+        }
+        _ => None,
+    }
+}
+
+/// Apply `side` version of the conflict with `index` to the world
+/// and remove it from [`SceneMergeConflicts`]. The resource is removed after the last conflict.
+pub fn resolve_merge_conflict(
+    world: &mut World,
+    index: usize,
+    side: MergeSide,
+) -> Result<(), SceneMergeError> {
+    let Some(conflict) = world
+        .get_resource::<SceneMergeConflicts>()
+        .and_then(|list| list.conflicts.get(index).cloned())
+    else {
+        return Ok(());
+    };
+    let value = match side {
+        MergeSide::Ours => conflict.ours.clone(),
+        MergeSide::Theirs => conflict.theirs.clone(),
+    };
+    apply_conflict_value(world, &conflict, value)?;
+
+    let mut list = world.resource_mut::<SceneMergeConflicts>();
+    list.conflicts.remove(index);
+    if list.conflicts.is_empty() {
+        world.remove_resource::<SceneMergeConflicts>();
+    }
+    Ok(())
+}
+
+fn apply_conflict_value(
+    world: &mut World,
+    conflict: &MergeConflict,
+    value: Option<String>,
+) -> Result<(), SceneMergeError> {
+    let entity = match &conflict.target {
+        ConflictTarget::Resource => None,
+        ConflictTarget::Entity { path, guid } => {
+            let entity = guid.and_then(|guid| {
+                world
+                    .query::<(Entity, &PrefabGuid)>()
+                    .iter(world)
+                    .find(|(_, prefab_guid)| prefab_guid.0 == guid)
+                    .map(|(entity, _)| entity)
+            });
+            Some(entity.ok_or_else(|| SceneMergeError::MissingEntity(path.clone()))?)
+        }
+    };
+
+    if conflict.type_path.is_empty() {
+        // The merged scene keeps entities removed in one version, so only removal is applied
+        if let (Some(entity), None) = (entity, value) {
+            world.entity_mut(entity).despawn_recursive();
+        }
+        return Ok(());
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let registration = registry
+        .get_with_type_path(&conflict.type_path)
+        .ok_or_else(|| SceneMergeError::UnknownType(conflict.type_path.clone()))?;
+    let unknown = || SceneMergeError::UnknownType(conflict.type_path.clone());
+
+    let current = match entity {
+        Some(entity) => registration
+            .data::<ReflectComponent>()
+            .ok_or_else(unknown)?
+            .reflect(world.entity(entity)),
+        None => registration
+            .data::<ReflectResource>()
+            .ok_or_else(unknown)?
+            .reflect(world),
+    }
+    .map(|current| ron::to_string(&TypedReflectSerializer::new(current, &registry)))
+    .transpose()
+    .map_err(|e| SceneMergeError::Serialize(e.to_string()))?;
+
+    let value = value
+        .map(|value| RonValue::parse(&value))
+        .transpose()
+        .map_err(SceneMergeError::Parse)?;
+    let value = if conflict.field.is_empty() {
+        value
+    } else {
+        let current = current.ok_or_else(|| SceneMergeError::MissingValue(conflict.label()))?;
+        let mut current = RonValue::parse(&current).map_err(SceneMergeError::Parse)?;
+        if !set_value(&mut current, &conflict.field, value) {
+            return Err(SceneMergeError::MissingValue(conflict.label()));
+        }
+        Some(current)
+    };
+
+    let value = value
+        .map(|value| from_ron(&value.to_pretty_string(), registration, &registry))
+        .transpose()?;
+    match (entity, value) {
+        (Some(entity), Some(value)) => {
+            let reflect_component = registration
+                .data::<ReflectComponent>()
+                .ok_or_else(unknown)?;
+            reflect_component.apply_or_insert(
+                &mut world.entity_mut(entity),
+                value.as_ref(),
+                &registry,
+            );
+        }
+        (Some(entity), None) => {
+            let reflect_component = registration
+                .data::<ReflectComponent>()
+                .ok_or_else(unknown)?;
+            reflect_component.remove(&mut world.entity_mut(entity));
+        }
+        (None, Some(value)) => {
+            let reflect_resource = registration.data::<ReflectResource>().ok_or_else(unknown)?;
+            reflect_resource.apply_or_insert(world, value.as_ref(), &registry);
+        }
+        (None, None) => {
+            let reflect_resource = registration.data::<ReflectResource>().ok_or_else(unknown)?;
+            reflect_resource.remove(world);
+        }
+    }
+    Ok(())
+}
+
+fn from_ron(
+    text: &str,
+    registration: &TypeRegistration,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn Reflect>, SceneMergeError> {
+    let mut deserializer =
+        ron::Deserializer::from_str(text).map_err(|e| SceneMergeError::Value(e.to_string()))?;
+    TypedReflectDeserializer::new(registration, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| SceneMergeError::Value(e.to_string()))
+}
+
+/// Replace the value at `path` written by [`Step::text`], `None` removes it.
+/// Returns `false` when the path does not exist.
+fn set_value(value: &mut RonValue, path: &[String], new: Option<RonValue>) -> bool {
+    let Some((step, rest)) = path.split_first() else {
+        return false;
+    };
+    let last = rest.is_empty();
+    match value {
+        RonValue::Struct(_, fields) => {
+            let position = fields.iter().position(|(name, _)| name == step);
+            match (position, last, new) {
+                (Some(index), false, new) => set_value(&mut fields[index].1, rest, new),
+                (Some(index), true, Some(new)) => {
+                    fields[index].1 = new;
+                    true
+                }
+                (Some(index), true, None) => {
+                    fields.remove(index);
+                    true
+                }
+                (None, true, Some(new)) => {
+                    fields.push((step.clone(), new));
+                    true
+                }
+                (None, ..) => false,
+            }
+        }
+        RonValue::Tuple(_, items) | RonValue::List(items) => {
+            let Ok(index) = step.parse::<usize>() else {
+                return false;
+            };
+            match (index < items.len(), last, new) {
+                (true, false, new) => set_value(&mut items[index], rest, new),
+                (true, true, Some(new)) => {
+                    items[index] = new;
+                    true
+                }
+                (true, true, None) => {
+                    items.remove(index);
+                    true
+                }
+                (false, true, Some(new)) if index == items.len() => {
+                    items.push(new);
+                    true
+                }
+                _ => false,
+            }
+        }
+        RonValue::Map(entries) => {
+            let Ok(key) = RonValue::parse(step) else {
+                return false;
+            };
+            let position = entries.iter().position(|(entry_key, _)| {
+                entry_key == &key || entry_key.to_pretty_string() == *step
+            });
+            match (position, last, new) {
+                (Some(index), false, new) => set_value(&mut entries[index].1, rest, new),
+                (Some(index), true, Some(new)) => {
+                    entries[index].1 = new;
+                    true
+                }
+                (Some(index), true, None) => {
+                    entries.remove(index);
+                    true
+                }
+                (None, true, Some(new)) => {
+                    entries.push((key, new));
+                    true
+                }
+                (None, ..) => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::scene::serde::SceneDeserializer;
+
+    use super::*;
+
+    fn world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Name>();
+            registry.register::<Transform>();
+            registry.register::<ChildrenPrefab>();
+            registry.register::<PrefabGuid>();
+            registry.register::<Uuid>();
+            registry.register::<SceneVersion>();
+            registry.register::<SceneMergeConflicts>();
+            registry.register::<MergeConflict>();
+            registry.register::<ConflictTarget>();
+            registry.register::<Vec<MergeConflict>>();
+            registry.register::<Vec<String>>();
+            registry.register::<Option<String>>();
+            registry.register::<Option<Uuid>>();
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    fn guid(n: u128) -> PrefabGuid {
+        PrefabGuid(Uuid::from_u128(n))
+    }
+
+    /// Scene with `room` and its child `lamp` at `lamp_position`
+    fn room(lamp_position: Vec3) -> World {
+        let mut world = world();
+        let lamp = world
+            .spawn((
+                guid(2),
+                Name::new("lamp"),
+                Transform::from_translation(lamp_position),
+            ))
+            .id();
+        world.spawn((guid(1), Name::new("room"), ChildrenPrefab(vec![lamp])));
+        world
+    }
+
+    fn scene_text(world: &World) -> String {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        DynamicSceneBuilder::from_world(world)
+            .extract_entities(world.iter_entities().map(|entity| entity.id()))
+            .build()
+            .serialize(&registry)
+            .unwrap()
+    }
+
+    /// Load merged scene text into a new world
+    fn load(text: &str) -> World {
+        let mut world = world();
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut ron::Deserializer::from_str(text).unwrap())
+        .unwrap();
+        scene
+            .write_to_world(&mut world, &mut Default::default())
+            .unwrap();
+        world
+    }
+
+    fn find(world: &mut World, n: u128) -> Option<Entity> {
+        world
+            .query::<(Entity, &PrefabGuid)>()
+            .iter(world)
+            .find(|(_, prefab_guid)| **prefab_guid == guid(n))
+            .map(|(entity, _)| entity)
+    }
+
+    #[test]
+    fn merges_changes_of_different_fields_and_entities() {
+        let base = scene_text(&room(Vec3::X));
+        let ours = scene_text(&room(Vec3::new(2., 0., 0.)));
+        let mut theirs = room(Vec3::new(1., 3., 0.));
+        let shelf = theirs.spawn((guid(3), Name::new("shelf"))).id();
+        let room_entity = find(&mut theirs, 1).unwrap();
+        theirs
+            .get_mut::<ChildrenPrefab>(room_entity)
+            .unwrap()
+            .0
+            .push(shelf);
+        let theirs = scene_text(&theirs);
+
+        let merged = merge_scenes(&mut world(), &base, &ours, &theirs).unwrap();
+        assert!(merged.conflicts.is_empty(), "{:?}", merged.conflicts);
+
+        let mut world = load(&merged.text);
+        let lamp = find(&mut world, 2).unwrap();
+        assert_eq!(
+            world.get::<Transform>(lamp).unwrap().translation,
+            Vec3::new(2., 3., 0.)
+        );
+        let room_entity = find(&mut world, 1).unwrap();
+        assert_eq!(world.get::<ChildrenPrefab>(room_entity).unwrap().0.len(), 2);
+        assert!(!world.contains_resource::<SceneMergeConflicts>());
+    }
+
+    #[test]
+    fn conflicting_field_keeps_ours_and_resolves_to_theirs() {
+        let base = scene_text(&room(Vec3::X));
+        let ours = scene_text(&room(Vec3::new(2., 0., 0.)));
+        let theirs = scene_text(&room(Vec3::new(5., 0., 0.)));
+
+        let merged = merge_scenes(&mut world(), &base, &ours, &theirs).unwrap();
+        assert_eq!(merged.conflicts.len(), 1);
+        let conflict = &merged.conflicts[0];
+        assert_eq!(conflict.type_path, Transform::type_path());
+        assert_eq!(
+            conflict.field.first().map(String::as_str),
+            Some("translation")
+        );
+        assert_eq!(
+            conflict.target,
+            ConflictTarget::Entity {
+                path: "room/lamp".to_string(),
+                guid: Some(guid(2).0),
+            }
+        );
+
+        let mut world = load(&merged.text);
+        let lamp = find(&mut world, 2).unwrap();
+        assert_eq!(world.get::<Transform>(lamp).unwrap().translation.x, 2.);
+        assert_eq!(
+            world.resource::<SceneMergeConflicts>().conflicts,
+            merged.conflicts
+        );
+
+        resolve_merge_conflict(&mut world, 0, MergeSide::Theirs).unwrap();
+        assert_eq!(world.get::<Transform>(lamp).unwrap().translation.x, 5.);
+        assert!(!world.contains_resource::<SceneMergeConflicts>());
+    }
+
+    #[test]
+    fn entity_removed_in_one_version_and_changed_in_other_is_a_conflict() {
+        let base = scene_text(&room(Vec3::X));
+        let ours = scene_text(&room(Vec3::new(2., 0., 0.)));
+        let mut theirs = room(Vec3::X);
+        let lamp = find(&mut theirs, 2).unwrap();
+        theirs.despawn(lamp);
+        let room_entity = find(&mut theirs, 1).unwrap();
+        theirs
+            .get_mut::<ChildrenPrefab>(room_entity)
+            .unwrap()
+            .0
+            .clear();
+        let theirs = scene_text(&theirs);
+
+        let merged = merge_scenes(&mut world(), &base, &ours, &theirs).unwrap();
+        assert_eq!(merged.conflicts.len(), 1, "{:?}", merged.conflicts);
+        assert!(merged.conflicts[0].type_path.is_empty());
+        assert!(merged.conflicts[0].theirs.is_none());
+
+        let mut world = load(&merged.text);
+        assert!(find(&mut world, 2).is_some());
+        resolve_merge_conflict(&mut world, 0, MergeSide::Theirs).unwrap();
+        assert!(find(&mut world, 2).is_none());
+    }
+}
@@ -0,0 +1,1451 @@
+use std::{any::TypeId, fmt, path::Path};
+
+use bevy::{
+    ecs::{
+        component::{ComponentId, Tick},
+        event::ManualEventReader,
+        reflect::ReflectMapEntities,
+        removal_detection::RemovedComponentEntity,
+    },
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        ReflectFromReflect, ReflectMut, ReflectRef, TypeInfo, TypeRegistration, TypeRegistry,
+    },
+    scene::{scene_spawner_system, SceneInstance, SceneSpawner},
+    utils::{HashMap, HashSet},
+};
+use serde::de::DeserializeSeed;
+use space_shared::{
+    toast::{ToastKind, ToastMessage},
+    EditorState, PrefabMarker,
+};
+use uuid::Uuid;
+
+use crate::{
+    component::PrefabGuid,
+    editor_registry::{EditorRegistry, EditorRegistryExt},
+    format::loader::PrefabSceneLoader,
+    load::{auto_children, PrefabLoader, PrefabVariant},
+    save::{prefab_file_bytes, write_prefab_bytes, ChildrenPrefab, ASSETS_DIR},
+    PrefabSet,
+};
+
+/// Plugin to keep edits of spawned prefab content on [`PrefabLoader`] instances
+pub struct PrefabOverridesPlugin;
+
+impl Plugin for PrefabOverridesPlugin {
+    #[cfg(not(tarpaulin_include))]
+    fn build(&self, app: &mut App) {
+        app.register_type::<PrefabChildPath>();
+        app.register_type::<PrefabOverride>();
+        app.register_type::<Vec<PrefabOverride>>();
+        app.register_type::<Vec<String>>();
+        app.editor_silent_registry::<PrefabOverrides>();
+        app.init_resource::<ChangedPrefabContent>();
+
+        app.add_systems(
+            Update,
+            (
+                collect_hierarchy_changes,
+                apply_pending_overrides.after(auto_children),
+                track_prefab_overrides.run_if(in_state(EditorState::Editor)),
+            )
+                .chain()
+                .in_set(PrefabSet::DetectPrefabChange),
+        );
+        app.add_systems(
+            bevy::app::SpawnScene,
+            mark_modified_instances.before(scene_spawner_system),
+        );
+    }
+}
+
+/// Stable path of an entity inside a prefab file
+#[derive(Reflect, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrefabChildPath {
+    /// Entity with [`PrefabGuid`]
+    Guid(Uuid),
+    /// Names of the entity and its ancestors, starting from the prefab root
+    Names(Vec<String>),
+}
+
+impl Default for PrefabChildPath {
+    fn default() -> Self {
+        Self::Names(vec![])
+    }
+}
+
+impl fmt::Display for PrefabChildPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Guid(guid) => write!(f, "{guid}"),
+            Self::Names(names) => write!(f, "{}", names.join("/")),
+        }
+    }
+}
+
+/// Single value that differs from the source prefab
+#[derive(Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Default)]
+pub struct PrefabOverride {
+    /// Overridden entity of the prefab
+    pub target: PrefabChildPath,
+    /// Type path of the overridden component
+    pub component: String,
+    /// Overridden field of the component. Empty if the whole component is overridden
+    pub field: String,
+    /// Overridden value in RON. Empty if the component is removed from the entity
+    pub value: String,
+}
+
+impl PrefabOverride {
+    /// Override removes the whole component from the entity
+    pub fn is_removed(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+/// Edits of spawned prefab content, stored on the [`PrefabLoader`] entity and re-applied after the prefab is spawned
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct PrefabOverrides(pub Vec<PrefabOverride>);
+
+/// Marks [`PrefabLoader`] whose content was (re)spawned and still waits for its [`PrefabOverrides`]
+#[derive(Component)]
+pub struct PrefabOverridesPending;
+
+/// Spawned entities whose name, guid or place in the hierarchy changed since the last [`track_prefab_overrides`] run
+#[derive(Resource, Default)]
+pub struct ChangedPrefabContent {
+    hierarchy: HashSet<Entity>,
+    /// Read positions of removed editor components
+    removed: HashMap<ComponentId, ManualEventReader<RemovedComponentEntity>>,
+}
+
+/// Prefab paths of the spawned instance content, kept until the instance hierarchy changes
+#[derive(Component)]
+struct PrefabInstancePaths {
+    scene_root: Entity,
+    scene_id: AssetId<DynamicScene>,
+    /// Spawned entities with their path and index in the source scene, in hierarchy order
+    entities: Vec<(Entity, PrefabChildPath, usize)>,
+    live: HashMap<PrefabChildPath, Entity>,
+}
+
+impl PrefabInstancePaths {
+    fn new(world: &World, source: &InstanceSource) -> Self {
+        let entities = instance_entities(world, source.scene_root)
+            .into_iter()
+            .filter_map(|entity| {
+                source
+                    .find(world, entity)
+                    .map(|(path, index)| (entity, path, index))
+            })
+            .collect::<Vec<_>>();
+        let mut live = HashMap::new();
+        for (entity, path, _) in entities.iter() {
+            live.entry(path.clone()).or_insert(*entity);
+        }
+        Self {
+            scene_root: source.scene_root,
+            scene_id: source.scene_id,
+            entities,
+            live,
+        }
+    }
+
+    fn is_valid(&self, source: &InstanceSource) -> bool {
+        self.scene_root == source.scene_root && self.scene_id == source.scene_id
+    }
+}
+
+/// Possible errors of reverting and applying overrides
+#[derive(Debug)]
+pub enum PrefabOverrideError {
+    /// Entity is not a part of spawned prefab
+    NotInstance,
+    /// Prefab has no entity with this path
+    MissingTarget(String),
+    UnknownType(String),
+    MissingField(String),
+    Value(String),
+    Io(std::io::Error),
+    /// Entity added to the spawned content, which overrides can not keep
+    AddedEntity(String),
+    /// Entity of the prefab removed from the spawned content, which overrides can not keep
+    RemovedEntity(String),
+}
+
+impl fmt::Display for PrefabOverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInstance => write!(f, "Entity is not a part of spawned prefab"),
+            Self::MissingTarget(e) => write!(f, "Prefab has no entity {e}"),
+            Self::UnknownType(e) => write!(f, "Type {e} is not a registered component"),
+            Self::MissingField(e) => write!(f, "Component has no field {e}"),
+            Self::Value(e) => write!(f, "Failed to set overridden value: {e}"),
+            Self::Io(e) => write!(f, "Failed to write prefab file: {e}"),
+            Self::AddedEntity(e) => write!(
+                f,
+                "Entity {e} added to prefab instance is not saved, unpack the prefab to change its hierarchy"
+            ),
+            Self::RemovedEntity(e) => write!(
+                f,
+                "Entity {e} removed from prefab instance comes back on load, unpack the prefab to change its hierarchy"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PrefabOverrideError {}
+
+/// Source scene of a spawned prefab instance
+struct InstanceSource<'w> {
+    scene_root: Entity,
+    scene_id: AssetId<DynamicScene>,
+    scene: &'w DynamicScene,
+    paths: HashMap<PrefabChildPath, usize>,
+}
+
+impl<'w> InstanceSource<'w> {
+    /// Returns `None` until the prefab content is spawned
+    fn new(world: &'w World, instance: Entity) -> Option<Self> {
+        let spawner = world.get_resource::<SceneSpawner>()?;
+        let (scene_root, scene_id) = world.get::<Children>(instance)?.iter().find_map(|child| {
+            let entity = world.get_entity(*child)?;
+            let scene = entity.get::<Handle<DynamicScene>>()?;
+            let scene_instance = entity.get::<SceneInstance>()?;
+            spawner
+                .instance_is_ready(**scene_instance)
+                .then_some((*child, scene.id()))
+        })?;
+        let scene = world
+            .get_resource::<Assets<DynamicScene>>()?
+            .get(scene_id)?;
+
+        let mut paths = HashMap::new();
+        for (index, path) in source_paths(scene).into_iter().enumerate() {
+            paths.entry(path).or_insert(index);
+        }
+        Some(Self {
+            scene_root,
+            scene_id,
+            scene,
+            paths,
+        })
+    }
+
+    /// Path and index of the source entity for spawned entity
+    fn find(&self, world: &World, entity: Entity) -> Option<(PrefabChildPath, usize)> {
+        let names = live_names(world, self.scene_root, entity)?;
+        let guid = world
+            .get::<PrefabGuid>(entity)
+            .map(|guid| PrefabChildPath::Guid(guid.0));
+        guid.into_iter()
+            .chain(std::iter::once(PrefabChildPath::Names(names)))
+            .find_map(|path| self.paths.get(&path).map(|index| (path.clone(), *index)))
+    }
+}
+
+/// Paths of all entities of the prefab scene, in scene order
+fn source_paths(scene: &DynamicScene) -> Vec<PrefabChildPath> {
+    let mut parents = HashMap::new();
+    let mut names = HashMap::new();
+    for entity in scene.entities.iter() {
+        if let Some(children) = find_component::<ChildrenPrefab>(&entity.components) {
+            for child in children.0 {
+                parents.insert(child, entity.entity);
+            }
+        }
+        if let Some(name) = find_component::<Name>(&entity.components) {
+            names.insert(entity.entity, name.to_string());
+        }
+    }
+
+    scene
+        .entities
+        .iter()
+        .map(|entity| {
+            if let Some(guid) = find_component::<PrefabGuid>(&entity.components) {
+                return PrefabChildPath::Guid(guid.0);
+            }
+            let mut path = vec![];
+            let mut current = Some(entity.entity);
+            // depth limit protects from broken hierarchy in file
+            while let (Some(e), true) = (current, path.len() <= scene.entities.len()) {
+                path.push(names.get(&e).cloned().unwrap_or_default());
+                current = parents.get(&e).copied();
+            }
+            path.reverse();
+            PrefabChildPath::Names(path)
+        })
+        .collect()
+}
+
+fn find_component<T: FromReflect + TypePath>(components: &[Box<dyn Reflect>]) -> Option<T> {
+    components
+        .iter()
+        .find(|c| {
+            c.get_represented_type_info()
+                .is_some_and(|info| info.type_path() == T::type_path())
+        })
+        .and_then(|c| T::from_reflect(c.as_ref()))
+}
+
+/// Names of the spawned entity and its ancestors up to the scene root
+fn live_names(world: &World, scene_root: Entity, entity: Entity) -> Option<Vec<String>> {
+    let mut names = vec![];
+    let mut current = entity;
+    while current != scene_root {
+        names.push(
+            world
+                .get::<Name>(current)
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+        current = world.get::<Parent>(current)?.get();
+    }
+    names.reverse();
+    Some(names)
+}
+
+/// Root entity of the spawned prefab scene, `None` until the prefab content is spawned
+pub(crate) fn instance_scene_root(world: &World, instance: Entity) -> Option<Entity> {
+    InstanceSource::new(world, instance).map(|source| source.scene_root)
+}
+
+/// All spawned entities of the instance. Content of nested prefabs belongs to their own instance
+pub(crate) fn instance_entities(world: &World, scene_root: Entity) -> Vec<Entity> {
+    let mut entities = vec![];
+    let mut stack = vec![scene_root];
+    while let Some(entity) = stack.pop() {
+        if entity != scene_root {
+            entities.push(entity);
+            if world.get::<PrefabLoader>(entity).is_some() {
+                continue;
+            }
+        }
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+    entities
+}
+
+/// Nearest [`PrefabLoader`] ancestor of the entity
+pub fn prefab_instance_of(world: &World, entity: Entity) -> Option<Entity> {
+    let mut current = world.get::<Parent>(entity)?.get();
+    while world.get::<PrefabLoader>(current).is_none() {
+        current = world.get::<Parent>(current)?.get();
+    }
+    Some(current)
+}
+
+fn editor_types(world: &World) -> Vec<TypeId> {
+    world
+        .get_resource::<EditorRegistry>()
+        .map(|editor| {
+            editor
+                .registry
+                .read()
+                .iter()
+                .map(TypeRegistration::type_id)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn editor_component_ids(world: &World) -> Vec<ComponentId> {
+    editor_types(world)
+        .into_iter()
+        .filter_map(|type_id| world.components().get_id(type_id))
+        .collect()
+}
+
+/// Source component converted to its concrete type when possible, so it can be compared with spawned one
+fn source_component(
+    source: &DynamicScene,
+    index: usize,
+    registration: &TypeRegistration,
+) -> Option<Box<dyn Reflect>> {
+    let component = source.entities[index].components.iter().find(|c| {
+        c.get_represented_type_info()
+            .is_some_and(|info| info.type_id() == registration.type_id())
+    })?;
+    registration
+        .data::<ReflectFromReflect>()
+        .and_then(|from_reflect| from_reflect.from_reflect(component.as_ref()))
+        .or_else(|| Some(component.clone_value()))
+}
+
+fn to_ron(value: &dyn Reflect, registry: &TypeRegistry) -> Option<String> {
+    ron::to_string(&TypedReflectSerializer::new(value, registry))
+        .inspect_err(|e| warn!("Failed to serialize prefab override: {e}"))
+        .ok()
+}
+
+fn from_ron(
+    text: &str,
+    registration: &TypeRegistration,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn Reflect>, PrefabOverrideError> {
+    let mut deserializer =
+        ron::Deserializer::from_str(text).map_err(|e| PrefabOverrideError::Value(e.to_string()))?;
+    TypedReflectDeserializer::new(registration, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| PrefabOverrideError::Value(e.to_string()))
+}
+
+/// Differences of spawned entity from its source entity
+fn diff_entity(
+    world: &World,
+    entity: Entity,
+    target: &PrefabChildPath,
+    source: &DynamicScene,
+    index: usize,
+    editor_types: &[TypeId],
+    registry: &TypeRegistry,
+) -> Vec<PrefabOverride> {
+    let mut overrides = vec![];
+    for type_id in editor_types {
+        let Some(registration) = registry.get(*type_id) else {
+            continue;
+        };
+        // Entity references can not be compared between source and spawned prefab,
+        // spawned content never keeps its marker
+        if registration.data::<ReflectMapEntities>().is_some()
+            || registration.type_id() == TypeId::of::<PrefabMarker>()
+        {
+            continue;
+        }
+        let component = registration.type_info().type_path().to_string();
+        let Some(live) = registration
+            .data::<ReflectComponent>()
+            .and_then(|c| c.reflect(world.entity(entity)))
+        else {
+            if source_component(source, index, registration).is_some() {
+                overrides.push(PrefabOverride {
+                    target: target.clone(),
+                    component,
+                    field: String::new(),
+                    value: String::new(),
+                });
+            }
+            continue;
+        };
+        let mut push = |field: &str, value: &dyn Reflect| {
+            if let Some(value) = to_ron(value, registry) {
+                overrides.push(PrefabOverride {
+                    target: target.clone(),
+                    component: component.clone(),
+                    field: field.to_string(),
+                    value,
+                });
+            }
+        };
+
+        let Some(src) = source_component(source, index, registration) else {
+            push("", live);
+            continue;
+        };
+        if let (ReflectRef::Struct(live_struct), ReflectRef::Struct(src_struct)) =
+            (live.reflect_ref(), src.reflect_ref())
+        {
+            for (i, live_field) in live_struct.iter_fields().enumerate() {
+                let Some(name) = live_struct.name_at(i) else {
+                    continue;
+                };
+                let differs = src_struct.field(name).map_or(true, |src_field| {
+                    live_field.reflect_partial_eq(src_field) == Some(false)
+                });
+                if differs {
+                    push(name, live_field);
+                }
+            }
+        } else if live.reflect_partial_eq(src.as_ref()) == Some(false) {
+            push("", live);
+        }
+    }
+    overrides
+}
+
+/// Differences of the spawned entity from the source prefab
+pub fn entity_overrides(world: &World, entity: Entity) -> Vec<PrefabOverride> {
+    let Some(instance) = prefab_instance_of(world, entity) else {
+        return vec![];
+    };
+    let Some(source) = InstanceSource::new(world, instance) else {
+        return vec![];
+    };
+    let Some((target, index)) = source.find(world, entity) else {
+        return vec![];
+    };
+    let registry = world.resource::<AppTypeRegistry>().read();
+    diff_entity(
+        world,
+        entity,
+        &target,
+        source.scene,
+        index,
+        &editor_types(world),
+        &registry,
+    )
+}
+
+/// Differences of all spawned content from the source prefab. Returns `None` until the content is spawned
+pub fn instance_overrides(world: &World, instance: Entity) -> Option<Vec<PrefabOverride>> {
+    let source = InstanceSource::new(world, instance)?;
+    let cached = world
+        .get::<PrefabInstancePaths>(instance)
+        .filter(|paths| paths.is_valid(&source));
+    let built = cached
+        .is_none()
+        .then(|| PrefabInstancePaths::new(world, &source));
+    let paths = cached.or(built.as_ref())?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let editor_types = editor_types(world);
+    let mut overrides = vec![];
+    for (entity, target, index) in paths.entities.iter() {
+        overrides.extend(diff_entity(
+            world,
+            *entity,
+            target,
+            source.scene,
+            *index,
+            &editor_types,
+            &registry,
+        ));
+    }
+    Some(overrides)
+}
+
+/// Build [`PrefabInstancePaths`] of the instance if it is missing or outdated.
+/// Returns `false` until the prefab content is spawned
+fn cache_instance_paths(world: &mut World, instance: Entity) -> bool {
+    let Some(source) = InstanceSource::new(world, instance) else {
+        return false;
+    };
+    let outdated = !world
+        .get::<PrefabInstancePaths>(instance)
+        .is_some_and(|paths| paths.is_valid(&source));
+    if outdated {
+        let paths = PrefabInstancePaths::new(world, &source);
+        world.entity_mut(instance).insert(paths);
+    }
+    true
+}
+
+/// Store current differences of the instance in its [`PrefabOverrides`]
+pub fn update_instance_overrides(world: &mut World, instance: Entity) {
+    if world.get::<PrefabOverridesPending>(instance).is_some()
+        || !cache_instance_paths(world, instance)
+    {
+        return;
+    }
+    let Some(overrides) = instance_overrides(world, instance) else {
+        return;
+    };
+    let current = world.get::<PrefabOverrides>(instance);
+    if overrides.is_empty() {
+        if current.is_some() {
+            world.entity_mut(instance).remove::<PrefabOverrides>();
+        }
+    } else if current.map(|c| &c.0) != Some(&overrides) {
+        world
+            .entity_mut(instance)
+            .insert(PrefabOverrides(overrides));
+    }
+}
+
+/// Store differences of all instances before saving
+pub fn refresh_prefab_overrides(world: &mut World) {
+    let instances = world
+        .query_filtered::<Entity, With<PrefabLoader>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for instance in instances {
+        update_instance_overrides(world, instance);
+    }
+}
+
+/// Instances with editor components of their spawned content changed between the ticks
+fn changed_instances(world: &mut World, last_run: Tick, this_run: Tick) -> Vec<Entity> {
+    let components = editor_component_ids(world);
+    let instances = world
+        .query_filtered::<Entity, With<PrefabLoader>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    instances
+        .into_iter()
+        .filter(|instance| {
+            let Some(children) = world.get::<Children>(*instance) else {
+                return false;
+            };
+            children
+                .iter()
+                .filter(|child| world.get::<Handle<DynamicScene>>(**child).is_some())
+                .flat_map(|scene_root| instance_entities(world, *scene_root))
+                .any(|entity| {
+                    let entity = world.entity(entity);
+                    components.iter().any(|id| {
+                        entity
+                            .get_change_ticks_by_id(*id)
+                            .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Collect entities whose prefab path may have changed
+fn collect_hierarchy_changes(
+    changed: Query<
+        Entity,
+        Or<(
+            Changed<Parent>,
+            Changed<Children>,
+            Changed<Name>,
+            Changed<PrefabGuid>,
+        )>,
+    >,
+    mut content: ResMut<ChangedPrefabContent>,
+) {
+    content.hierarchy.extend(changed.iter());
+}
+
+/// Update [`PrefabOverrides`] of instances whose spawned content was changed since the last run
+pub fn track_prefab_overrides(world: &mut World) {
+    let Some(mut content) = world.get_resource_mut::<ChangedPrefabContent>() else {
+        return;
+    };
+    let hierarchy = std::mem::take(&mut content.hierarchy);
+    // Exclusive system sees its previous run as the last change tick
+    let (last_run, this_run) = (world.last_change_tick(), world.change_tick());
+    let mut instances = changed_instances(world, last_run, this_run)
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let removed = removed_content(world);
+    instances.extend(
+        removed
+            .into_iter()
+            .filter_map(|entity| prefab_instance_of(world, entity)),
+    );
+    let mut reshaped = HashSet::new();
+    for entity in hierarchy {
+        // Children of the instance itself hold its scene root
+        let own = world.get::<PrefabLoader>(entity).map(|_| entity);
+        for instance in own.into_iter().chain(prefab_instance_of(world, entity)) {
+            if let Some(mut instance_mut) = world.get_entity_mut(instance) {
+                instance_mut.remove::<PrefabInstancePaths>();
+                reshaped.insert(instance);
+            }
+        }
+    }
+    instances.extend(reshaped.iter().copied());
+
+    for instance in instances {
+        if world.get::<PrefabLoader>(instance).is_some() {
+            update_instance_overrides(world, instance);
+        }
+    }
+    for instance in reshaped {
+        if world.get::<PrefabLoader>(instance).is_some()
+            && world.get::<PrefabOverridesPending>(instance).is_none()
+        {
+            report_hierarchy_edits(world, instance);
+        }
+    }
+}
+
+/// Entities which lost an editor component since the last [`track_prefab_overrides`] run
+fn removed_content(world: &mut World) -> Vec<Entity> {
+    let components = editor_component_ids(world);
+    world.resource_scope(|world, mut content: Mut<ChangedPrefabContent>| {
+        let mut removed = vec![];
+        for id in components {
+            if let Some(events) = world.removed_components().get(id) {
+                let reader = content.removed.entry(id).or_default();
+                removed.extend(reader.read(events).cloned().map(Entity::from));
+            }
+        }
+        removed
+    })
+}
+
+/// Entities added to or removed from the spawned content of the instance.
+/// Overrides keep only component values, so these edits are lost when the prefab is loaded again
+pub fn instance_hierarchy_edits(world: &World, instance: Entity) -> Vec<PrefabOverrideError> {
+    let Some(source) = InstanceSource::new(world, instance) else {
+        return vec![];
+    };
+    let mut found = HashSet::new();
+    let mut edits = vec![];
+    for entity in instance_entities(world, source.scene_root) {
+        match source.find(world, entity) {
+            Some((path, _)) => {
+                found.insert(path);
+            }
+            None => {
+                let names = live_names(world, source.scene_root, entity).unwrap_or_default();
+                edits.push(PrefabOverrideError::AddedEntity(names.join("/")));
+            }
+        }
+    }
+    let mut removed = source_paths(source.scene)
+        .into_iter()
+        .filter(|path| !found.contains(path))
+        .collect::<Vec<_>>();
+    removed.dedup();
+    edits.extend(
+        removed
+            .into_iter()
+            .map(|path| PrefabOverrideError::RemovedEntity(path.to_string())),
+    );
+    edits
+}
+
+fn report_hierarchy_edits(world: &mut World, instance: Entity) {
+    for edit in instance_hierarchy_edits(world, instance) {
+        warn!("{edit}");
+        if world.contains_resource::<Events<ToastMessage>>() {
+            world.send_event(ToastMessage::new(&edit.to_string(), ToastKind::Warning));
+        }
+    }
+}
+
+/// Re-apply [`PrefabOverrides`] once the prefab content is spawned
+pub fn apply_pending_overrides(world: &mut World) {
+    let pending = world
+        .query_filtered::<Entity, With<PrefabOverridesPending>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for instance in pending {
+        if world.get::<PrefabLoader>(instance).is_none() {
+            world
+                .entity_mut(instance)
+                .remove::<PrefabOverridesPending>();
+            continue;
+        }
+        if !cache_instance_paths(world, instance) {
+            continue;
+        }
+        let overrides = world
+            .get::<PrefabOverrides>(instance)
+            .cloned()
+            .unwrap_or_default();
+        for value in overrides.0.iter() {
+            let target = world
+                .get::<PrefabInstancePaths>(instance)
+                .and_then(|paths| paths.live.get(&value.target).copied());
+            let result = target.map_or(Err(PrefabOverrideError::NotInstance), |entity| {
+                write_override(world, entity, value)
+            });
+            if let Err(e) = result {
+                warn!(
+                    "Failed to apply prefab override {}.{} on {}: {e}",
+                    value.component, value.field, value.target
+                );
+            }
+        }
+        world
+            .entity_mut(instance)
+            .remove::<PrefabOverridesPending>();
+    }
+}
+
+/// Mark instances whose source scene was changed, because scene spawner overwrites their content.
+/// Instances respawned by the prefab hot reload get their overrides back the same way.
+fn mark_modified_instances(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<DynamicScene>>,
+    instances: Query<(Entity, &Children), With<PrefabLoader>>,
+    scenes: Query<&Handle<DynamicScene>>,
+) {
+    let modified = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if modified.is_empty() {
+        return;
+    }
+    for (instance, children) in instances.iter() {
+        let is_modified = children.iter().any(|child| {
+            scenes
+                .get(*child)
+                .is_ok_and(|scene| modified.contains(&scene.id()))
+        });
+        if is_modified {
+            commands
+                .entity(instance)
+                .insert(PrefabOverridesPending)
+                .remove::<PrefabInstancePaths>();
+        }
+    }
+}
+
+fn write_override(
+    world: &mut World,
+    entity: Entity,
+    value: &PrefabOverride,
+) -> Result<(), PrefabOverrideError> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let registration = registry
+        .get_with_type_path(&value.component)
+        .ok_or_else(|| PrefabOverrideError::UnknownType(value.component.clone()))?;
+    let reflect_component = registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| PrefabOverrideError::UnknownType(value.component.clone()))?;
+
+    if value.is_removed() {
+        reflect_component.remove(&mut world.entity_mut(entity));
+        return Ok(());
+    }
+    if value.field.is_empty() {
+        let component = from_ron(&value.value, registration, &registry)?;
+        reflect_component.apply_or_insert(
+            &mut world.entity_mut(entity),
+            component.as_ref(),
+            &registry,
+        );
+        return Ok(());
+    }
+
+    let mut entity_mut = world.entity_mut(entity);
+    let mut component = reflect_component
+        .reflect_mut(&mut entity_mut)
+        .ok_or_else(|| PrefabOverrideError::MissingField(value.field.clone()))?;
+    let field = match component.reflect_mut() {
+        ReflectMut::Struct(s) => s.field_mut(&value.field),
+        _ => None,
+    }
+    .ok_or_else(|| PrefabOverrideError::MissingField(value.field.clone()))?;
+    let field_registration = field
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| PrefabOverrideError::UnknownType(value.field.clone()))?;
+    let field_value = from_ron(&value.value, field_registration, &registry)?;
+    field
+        .try_apply(field_value.as_ref())
+        .map_err(|e| PrefabOverrideError::Value(e.to_string()))
+}
+
+/// Apply override to the prefab scene itself, used to build prefab variants
+pub fn apply_override_to_scene(
+    scene: &mut DynamicScene,
+    value: &PrefabOverride,
+    registry: &TypeRegistry,
+) -> Result<(), PrefabOverrideError> {
+    let index = source_paths(scene)
+        .iter()
+        .position(|path| *path == value.target)
+        .ok_or_else(|| PrefabOverrideError::MissingTarget(value.target.to_string()))?;
+    let registration = registry
+        .get_with_type_path(&value.component)
+        .ok_or_else(|| PrefabOverrideError::UnknownType(value.component.clone()))?;
+
+    let components = &mut scene.entities[index].components;
+    if value.is_removed() {
+        components.retain(|c| {
+            c.get_represented_type_info().map(TypeInfo::type_id) != Some(registration.type_id())
+        });
+        return Ok(());
+    }
+    let existing = components.iter_mut().find(|c| {
+        c.get_represented_type_info()
+            .is_some_and(|info| info.type_id() == registration.type_id())
+    });
+    if value.field.is_empty() {
+        let component = from_ron(&value.value, registration, registry)?;
+        match existing {
+            Some(existing) => *existing = component,
+            None => components.push(component),
+        }
+        return Ok(());
+    }
+
+    let field = existing
+        .and_then(|existing| match existing.reflect_mut() {
+            ReflectMut::Struct(s) => s.field_mut(&value.field),
+            _ => None,
+        })
+        .ok_or_else(|| PrefabOverrideError::MissingField(value.field.clone()))?;
+    let field_registration = field
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| PrefabOverrideError::UnknownType(value.field.clone()))?;
+    let field_value = from_ron(&value.value, field_registration, registry)?;
+    field
+        .try_apply(field_value.as_ref())
+        .map_err(|e| PrefabOverrideError::Value(e.to_string()))
+}
+
+/// Set the component (or its field) of the spawned entity back to the prefab value
+pub fn revert_override(
+    world: &mut World,
+    entity: Entity,
+    component: &str,
+    field: &str,
+) -> Result<(), PrefabOverrideError> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let registration = registry
+        .get_with_type_path(component)
+        .ok_or_else(|| PrefabOverrideError::UnknownType(component.to_string()))?;
+    let reflect_component = registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| PrefabOverrideError::UnknownType(component.to_string()))?;
+
+    let instance = prefab_instance_of(world, entity).ok_or(PrefabOverrideError::NotInstance)?;
+    let source_value = {
+        let source =
+            InstanceSource::new(world, instance).ok_or(PrefabOverrideError::NotInstance)?;
+        let (_, index) = source
+            .find(world, entity)
+            .ok_or(PrefabOverrideError::NotInstance)?;
+        source_component(source.scene, index, registration)
+    };
+
+    let mut entity_mut = world.entity_mut(entity);
+    match source_value {
+        None => reflect_component.remove(&mut entity_mut),
+        Some(value) if field.is_empty() => {
+            reflect_component.apply_or_insert(&mut entity_mut, value.as_ref(), &registry);
+        }
+        Some(value) => {
+            let src_field = match value.reflect_ref() {
+                ReflectRef::Struct(s) => s.field(field),
+                _ => None,
+            }
+            .ok_or_else(|| PrefabOverrideError::MissingField(field.to_string()))?;
+            let mut live = reflect_component
+                .reflect_mut(&mut entity_mut)
+                .ok_or_else(|| PrefabOverrideError::MissingField(field.to_string()))?;
+            let live_field = match live.reflect_mut() {
+                ReflectMut::Struct(s) => s.field_mut(field),
+                _ => None,
+            }
+            .ok_or_else(|| PrefabOverrideError::MissingField(field.to_string()))?;
+            live_field
+                .try_apply(src_field)
+                .map_err(|e| PrefabOverrideError::Value(e.to_string()))?;
+        }
+    }
+
+    update_instance_overrides(world, instance);
+    Ok(())
+}
+
+/// Write the overridden component (or its field) of the spawned entity into the source prefab file.
+/// All instances of the prefab are updated and keep their own overrides.
+///
+/// For a prefab variant the value is stored in its [`PrefabVariant`] overrides,
+/// so the variant keeps inheriting other changes of its base.
+pub fn apply_override_to_prefab(
+    world: &mut World,
+    entity: Entity,
+    component: &str,
+    field: &str,
+) -> Result<(), PrefabOverrideError> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let registration = registry
+        .get_with_type_path(component)
+        .ok_or_else(|| PrefabOverrideError::UnknownType(component.to_string()))?;
+    // Component removed from the spawned entity is removed from the prefab too
+    let live = registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| PrefabOverrideError::UnknownType(component.to_string()))?
+        .reflect(world.entity(entity));
+    let live_value = match live {
+        _ if field.is_empty() => None,
+        Some(live) => Some(
+            match live.reflect_ref() {
+                ReflectRef::Struct(s) => s.field(field).map(Reflect::clone_value),
+                _ => None,
+            }
+            .ok_or_else(|| PrefabOverrideError::MissingField(field.to_string()))?,
+        ),
+        None => return Err(PrefabOverrideError::MissingField(field.to_string())),
+    };
+    let ron = match live {
+        Some(live) => live_value
+            .as_deref()
+            .map_or_else(|| to_ron(live, &registry), |value| to_ron(value, &registry))
+            .ok_or_else(|| PrefabOverrideError::Value(component.to_string()))?,
+        None => String::new(),
+    };
+    let live = live.map(Reflect::clone_value);
+
+    let instance = prefab_instance_of(world, entity).ok_or(PrefabOverrideError::NotInstance)?;
+    let (scene_id, index, target) = {
+        let source =
+            InstanceSource::new(world, instance).ok_or(PrefabOverrideError::NotInstance)?;
+        let (target, index) = source
+            .find(world, entity)
+            .ok_or(PrefabOverrideError::NotInstance)?;
+        (source.scene_id, index, target)
+    };
+    let path = world
+        .get::<PrefabLoader>(instance)
+        .map(|loader| loader.path.clone())
+        .ok_or(PrefabOverrideError::NotInstance)?;
+
+    // Keep edits of other instances, they are re-applied after the scene is updated
+    refresh_prefab_overrides(world);
+
+    {
+        let mut scenes = world.resource_mut::<Assets<DynamicScene>>();
+        let scene = scenes
+            .get_mut(scene_id)
+            .ok_or(PrefabOverrideError::NotInstance)?;
+        let components = &mut scene.entities[index].components;
+        let is_component = |c: &dyn Reflect| {
+            c.get_represented_type_info().map(TypeInfo::type_id) == Some(registration.type_id())
+        };
+        let existing = components.iter_mut().find(|c| is_component(c.as_ref()));
+        match (live, existing, live_value) {
+            (None, ..) => components.retain(|c| !is_component(c.as_ref())),
+            (Some(live), Some(existing), Some(field_value)) => {
+                let src_field = match existing.reflect_mut() {
+                    ReflectMut::Struct(s) => s.field_mut(field),
+                    _ => None,
+                };
+                match src_field {
+                    Some(src_field) => src_field
+                        .try_apply(field_value.as_ref())
+                        .map_err(|e| PrefabOverrideError::Value(e.to_string()))?,
+                    None => *existing = live,
+                }
+            }
+            (Some(live), Some(existing), None) => *existing = live,
+            (Some(live), None, _) => components.push(live),
+        }
+    }
+
+    let value = PrefabOverride {
+        target,
+        component: component.to_string(),
+        field: field.to_string(),
+        value: ron,
+    };
+    match read_variant_file(world, &path) {
+        Some((file, variant)) => write_variant_file(world, file, variant, value, &path)?,
+        None => write_prefab_file(world, scene_id, &path)?,
+    }
+    update_instance_overrides(world, instance);
+    Ok(())
+}
+
+/// Prefab file at `path` without its base resolved, if it is a prefab variant
+fn read_variant_file(world: &mut World, path: &str) -> Option<(DynamicScene, PrefabVariant)> {
+    let loader = PrefabSceneLoader::from_world(world);
+    let file = loader
+        .load_from_file(Path::new(ASSETS_DIR).join(path))
+        .ok()?;
+    let variant = file.resources.iter().find_map(|resource| {
+        resource
+            .get_represented_type_info()
+            .is_some_and(|info| info.type_id() == TypeId::of::<PrefabVariant>())
+            .then(|| PrefabVariant::from_reflect(resource.as_ref()))
+            .flatten()
+    })?;
+    Some((file, variant))
+}
+
+/// Store the override in the variant file, replacing older overrides of the same value
+fn write_variant_file(
+    world: &World,
+    mut file: DynamicScene,
+    mut variant: PrefabVariant,
+    value: PrefabOverride,
+    path: &str,
+) -> Result<(), PrefabOverrideError> {
+    variant.overrides.retain(|old| {
+        old.target != value.target
+            || old.component != value.component
+            || (!value.field.is_empty() && old.field != value.field)
+    });
+    variant.overrides.push(value);
+    file.resources.retain(|resource| {
+        resource
+            .get_represented_type_info()
+            .map_or(true, |info| info.type_id() != TypeId::of::<PrefabVariant>())
+    });
+    file.resources.push(Box::new(variant));
+
+    let bytes = prefab_file_bytes(world, &file, path).map_err(PrefabOverrideError::Value)?;
+    write_prefab_bytes(world, &Path::new(ASSETS_DIR).join(path), &bytes)
+        .map_err(PrefabOverrideError::Io)?;
+    info!("Applied override to prefab variant {path}");
+    Ok(())
+}
+
+/// Save the source scene of prefab back to its file, keeping the file format
+fn write_prefab_file(
+    world: &World,
+    scene_id: AssetId<DynamicScene>,
+    path: &str,
+) -> Result<(), PrefabOverrideError> {
+    let scene = world
+        .resource::<Assets<DynamicScene>>()
+        .get(scene_id)
+        .ok_or(PrefabOverrideError::NotInstance)?;
+    let bytes = prefab_file_bytes(world, scene, path).map_err(PrefabOverrideError::Value)?;
+    write_prefab_bytes(world, &Path::new(ASSETS_DIR).join(path), &bytes)
+        .map_err(PrefabOverrideError::Io)?;
+    info!("Applied override to prefab {path}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        format::{
+            binary::is_binary_scene,
+            split::{is_split_scene_file, read_scene_text, write_split_scene},
+        },
+        test_dir::TempDir,
+    };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            crate::prelude::EditorRegistryPlugin,
+        ))
+        .register_type::<Uuid>()
+        .register_type::<PrefabVariant>()
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>()
+        .editor_silent_registry::<PrefabGuid>()
+        .init_resource::<ChangedPrefabContent>()
+        .add_systems(
+            Update,
+            (
+                collect_hierarchy_changes,
+                apply_pending_overrides,
+                track_prefab_overrides,
+            )
+                .chain(),
+        )
+        .add_systems(
+            bevy::app::SpawnScene,
+            mark_modified_instances.before(scene_spawner_system),
+        );
+        app
+    }
+
+    fn source_scene(app: &mut App) -> Handle<DynamicScene> {
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.spawn((Name::new("a"), Transform::from_xyz(1.0, 0.0, 0.0)));
+        world.spawn((Name::new("b"), Transform::default()));
+        let scene = DynamicScene::from_world(&world);
+        app.world_mut()
+            .resource_mut::<Assets<DynamicScene>>()
+            .add(scene)
+    }
+
+    fn spawn_instance(app: &mut App, scene: Handle<DynamicScene>, path: &str) -> Entity {
+        let instance = app
+            .world_mut()
+            .spawn((
+                PrefabLoader {
+                    path: path.to_string(),
+                },
+                PrefabOverridesPending,
+            ))
+            .id();
+        app.world_mut()
+            .spawn(DynamicSceneBundle { scene, ..default() })
+            .set_parent(instance);
+        app.update();
+        app.update();
+        instance
+    }
+
+    fn find_named(app: &mut App, instance: Entity, name: &str) -> Entity {
+        let world = app.world();
+        let root = world.get::<Children>(instance).unwrap()[0];
+        instance_entities(world, root)
+            .into_iter()
+            .find(|e| world.get::<Name>(*e).is_some_and(|n| n.as_str() == name))
+            .unwrap()
+    }
+
+    #[test]
+    fn records_and_restores_overrides() {
+        let mut app = app();
+        let scene = source_scene(&mut app);
+        let first = spawn_instance(&mut app, scene.clone(), "test.scn.ron");
+        assert!(app.world().get::<PrefabOverridesPending>(first).is_none());
+        assert!(app.world().get::<PrefabOverrides>(first).is_none());
+
+        let b = find_named(&mut app, first, "b");
+        app.world_mut()
+            .get_mut::<Transform>(b)
+            .unwrap()
+            .translation
+            .y = 5.0;
+        app.update();
+
+        let overrides = app.world().get::<PrefabOverrides>(first).unwrap().clone();
+        assert_eq!(overrides.0.len(), 1);
+        assert_eq!(
+            overrides.0[0].target,
+            PrefabChildPath::Names(vec!["b".to_string()])
+        );
+        assert_eq!(overrides.0[0].component, Transform::type_path());
+        assert_eq!(overrides.0[0].field, "translation");
+
+        // Instance loaded from file gets the same content
+        let instance = app
+            .world_mut()
+            .spawn((
+                PrefabLoader {
+                    path: "test.scn.ron".to_string(),
+                },
+                PrefabOverridesPending,
+                overrides,
+            ))
+            .id();
+        app.world_mut()
+            .spawn(DynamicSceneBundle { scene, ..default() })
+            .set_parent(instance);
+        app.update();
+        app.update();
+
+        let b = find_named(&mut app, instance, "b");
+        let a = find_named(&mut app, instance, "a");
+        assert_eq!(app.world().get::<Transform>(b).unwrap().translation.y, 5.0);
+        assert_eq!(
+            app.world().get::<Transform>(a).unwrap().translation,
+            Vec3::X
+        );
+    }
+
+    #[test]
+    fn apply_to_prefab_keeps_file_format() {
+        let dir = TempDir::new("override_format");
+        let split_file = dir.join("split.scn.ron").to_string_lossy().to_string();
+        let binary_file = dir.join("binary.scn.bin").to_string_lossy().to_string();
+
+        let mut app = app();
+        let scene = source_scene(&mut app);
+        {
+            let world = app.world();
+            let registry = world.resource::<AppTypeRegistry>().read();
+            let text = world
+                .resource::<Assets<DynamicScene>>()
+                .get(&scene)
+                .unwrap()
+                .serialize(&registry)
+                .unwrap();
+            write_split_scene(&split_file, &text, &registry, 0).unwrap();
+        }
+
+        for file in [&split_file, &binary_file] {
+            let instance = spawn_instance(&mut app, scene.clone(), file);
+            let a = find_named(&mut app, instance, "a");
+            app.world_mut().get_mut::<Transform>(a).unwrap().scale = Vec3::splat(3.0);
+            app.update();
+            apply_override_to_prefab(app.world_mut(), a, Transform::type_path(), "scale").unwrap();
+        }
+
+        assert!(is_split_scene_file(&split_file));
+        assert!(read_scene_text(&split_file).unwrap().contains("x: 3.0"));
+        assert!(is_binary_scene(&std::fs::read(&binary_file).unwrap()));
+    }
+
+    #[test]
+    fn revert_and_apply_to_prefab() {
+        let dir = TempDir::new("override");
+        let file = dir.join("prefab.scn.ron").to_string_lossy().to_string();
+
+        let mut app = app();
+        let scene = source_scene(&mut app);
+        let first = spawn_instance(&mut app, scene.clone(), &file);
+        let second = spawn_instance(&mut app, scene, &file);
+
+        let a = find_named(&mut app, first, "a");
+        app.world_mut().get_mut::<Transform>(a).unwrap().scale = Vec3::splat(2.0);
+        app.update();
+        assert_eq!(entity_overrides(app.world(), a).len(), 1);
+
+        revert_override(app.world_mut(), a, Transform::type_path(), "scale").unwrap();
+        assert_eq!(app.world().get::<Transform>(a).unwrap().scale, Vec3::ONE);
+        assert!(entity_overrides(app.world(), a).is_empty());
+        assert!(app.world().get::<PrefabOverrides>(first).is_none());
+
+        // Override of the other instance survives applying to prefab
+        let other_a = find_named(&mut app, second, "a");
+        app.world_mut()
+            .get_mut::<Transform>(other_a)
+            .unwrap()
+            .rotation = Quat::from_rotation_z(1.0);
+        app.world_mut().get_mut::<Transform>(a).unwrap().scale = Vec3::splat(3.0);
+        app.update();
+
+        apply_override_to_prefab(app.world_mut(), a, Transform::type_path(), "scale").unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+        let text = std::fs::read_to_string(&file).unwrap();
+
+        assert!(text.contains("x: 3.0"));
+        assert!(app.world().get::<PrefabOverrides>(first).is_none());
+        let other = app.world().get::<Transform>(other_a).unwrap();
+        assert_eq!(other.scale, Vec3::splat(3.0));
+        assert_eq!(other.rotation, Quat::from_rotation_z(1.0));
+        assert_eq!(
+            app.world().get::<PrefabOverrides>(second).unwrap().0.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn apply_to_prefab_variant_keeps_variant() {
+        let dir = TempDir::new("override_variant");
+        let file = dir.join("variant.scn.ron").to_string_lossy().to_string();
+
+        let mut app = app();
+        let variant_file = {
+            let mut world = World::new();
+            world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+            world.insert_resource(PrefabVariant {
+                base: "base.scn.ron".to_string(),
+                overrides: vec![PrefabOverride {
+                    target: PrefabChildPath::Names(vec!["a".to_string()]),
+                    component: Transform::type_path().to_string(),
+                    field: "scale".to_string(),
+                    value: "(x: 2.0, y: 2.0, z: 2.0)".to_string(),
+                }],
+            });
+            let registry = app.world().resource::<AppTypeRegistry>().read();
+            DynamicScene::from_world(&world)
+                .serialize(&registry)
+                .unwrap()
+        };
+        std::fs::write(&file, variant_file).unwrap();
+
+        // Scene asset of the variant is resolved from its base
+        let scene = source_scene(&mut app);
+        let instance = spawn_instance(&mut app, scene, &file);
+        let a = find_named(&mut app, instance, "a");
+        app.world_mut().get_mut::<Transform>(a).unwrap().scale = Vec3::splat(3.0);
+        app.update();
+
+        apply_override_to_prefab(app.world_mut(), a, Transform::type_path(), "scale").unwrap();
+        app.update();
+        let text = std::fs::read_to_string(&file).unwrap();
+
+        assert!(text.contains(PrefabVariant::type_path()), "{text}");
+        assert!(text.contains("base.scn.ron"), "{text}");
+        assert!(!text.contains(Name::type_path()), "{text}");
+        assert!(text.contains("(x:3.0,y:3.0,z:3.0)"), "{text}");
+        assert!(!text.contains("x:2.0"), "{text}");
+        assert!(app.world().get::<PrefabOverrides>(instance).is_none());
+    }
+
+    #[test]
+    fn tracks_only_changed_instances() {
+        let mut app = app();
+        let scene = source_scene(&mut app);
+        let first = spawn_instance(&mut app, scene.clone(), "test.scn.ron");
+        let second = spawn_instance(&mut app, scene, "test.scn.ron");
+        assert!(app.world().get::<PrefabInstancePaths>(first).is_some());
+
+        // Not diffed again while its content is untouched
+        let stale = PrefabOverrides(vec![PrefabOverride::default()]);
+        app.world_mut().entity_mut(second).insert(stale.clone());
+        let b = find_named(&mut app, first, "b");
+        app.world_mut().get_mut::<Transform>(b).unwrap().scale = Vec3::splat(2.0);
+        app.update();
+        assert_eq!(
+            app.world().get::<PrefabOverrides>(first).unwrap().0.len(),
+            1
+        );
+        assert_eq!(app.world().get::<PrefabOverrides>(second), Some(&stale));
+
+        // Renamed entity no longer matches the source prefab
+        app.world_mut().get_mut::<Name>(b).unwrap().set("c");
+        app.update();
+        assert!(app.world().get::<PrefabOverrides>(first).is_none());
+    }
+
+    #[test]
+    fn records_and_restores_removed_component() {
+        let mut app = app();
+        let scene = source_scene(&mut app);
+        let first = spawn_instance(&mut app, scene.clone(), "test.scn.ron");
+
+        let a = find_named(&mut app, first, "a");
+        app.world_mut().entity_mut(a).remove::<Transform>();
+        app.update();
+
+        let overrides = app.world().get::<PrefabOverrides>(first).unwrap().clone();
+        assert_eq!(overrides.0.len(), 1);
+        assert!(overrides.0[0].is_removed());
+        assert_eq!(overrides.0[0].component, Transform::type_path());
+        assert_eq!(
+            overrides.0[0].target,
+            PrefabChildPath::Names(vec!["a".to_string()])
+        );
+
+        // Instance loaded from file loses the component too
+        let instance = app
+            .world_mut()
+            .spawn((
+                PrefabLoader {
+                    path: "test.scn.ron".to_string(),
+                },
+                PrefabOverridesPending,
+                overrides,
+            ))
+            .id();
+        app.world_mut()
+            .spawn(DynamicSceneBundle { scene, ..default() })
+            .set_parent(instance);
+        app.update();
+        app.update();
+        let a = find_named(&mut app, instance, "a");
+        assert!(app.world().get::<Transform>(a).is_none());
+
+        revert_override(app.world_mut(), a, Transform::type_path(), "").unwrap();
+        assert_eq!(
+            app.world().get::<Transform>(a).unwrap().translation,
+            Vec3::X
+        );
+        assert!(app.world().get::<PrefabOverrides>(instance).is_none());
+    }
+
+    #[test]
+    fn reports_added_and_removed_entities() {
+        let mut app = app();
+        app.add_event::<ToastMessage>();
+        let scene = source_scene(&mut app);
+        let instance = spawn_instance(&mut app, scene, "test.scn.ron");
+
+        let a = find_named(&mut app, instance, "a");
+        let b = find_named(&mut app, instance, "b");
+        app.world_mut().spawn(Name::new("c")).set_parent(a);
+        app.world_mut().entity_mut(b).despawn_recursive();
+        app.update();
+
+        let edits = instance_hierarchy_edits(app.world(), instance);
+        assert_eq!(edits.len(), 2);
+        assert!(matches!(&edits[0], PrefabOverrideError::AddedEntity(e) if e == "a/c"));
+        assert!(matches!(&edits[1], PrefabOverrideError::RemovedEntity(e) if e == "b"));
+        assert_eq!(app.world().resource::<Events<ToastMessage>>().len(), 2);
+        // Edits of the hierarchy are not stored as overrides
+        assert!(app.world().get::<PrefabOverrides>(instance).is_none());
+    }
+}
@@ -0,0 +1,727 @@
+use std::{
+    fmt,
+    path::{Component, Path},
+    sync::Arc,
+};
+
+use bevy::{
+    ecs::{entity::EntityHashMap, world::CommandQueue},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use space_shared::PrefabMarker;
+use space_undo::{
+    get_entity_with_remap, ChangeResult, EditorChange, NewChange, OneFrameUndoIgnore,
+    UndoIgnoreStorage,
+};
+
+use crate::{
+    component::NoSave,
+    editor_registry::EditorRegistry,
+    load::{PrefabAutoChild, PrefabBundle, PrefabLoader},
+    overrides::{instance_entities, instance_scene_root, PrefabOverrides},
+    save::{is_saved, prefab_file_bytes, write_prefab_bytes, ChildrenPrefab, ASSETS_DIR},
+};
+
+/// Possible errors of creating and unpacking prefabs
+#[derive(Debug)]
+pub enum PrefabPackError {
+    NoEntities,
+    /// Prefab roots must have the same parent to be replaced by one instance
+    DifferentParents,
+    /// Entity is not a spawned prefab instance
+    NotInstance,
+    Serialize(String),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for PrefabPackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoEntities => write!(f, "No prefab entities selected"),
+            Self::DifferentParents => write!(f, "Selected entities have different parents"),
+            Self::NotInstance => write!(f, "Entity is not a loaded prefab instance"),
+            Self::Serialize(e) => write!(f, "Failed to serialize prefab: {e}"),
+            Self::Io(e) => write!(f, "Failed to write prefab file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrefabPackError {}
+
+/// Path of the file relative to the assets folder, as used by [`PrefabLoader`]
+pub fn asset_path_of(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let prefix = format!("{ASSETS_DIR}/");
+    path.rfind(&prefix)
+        .filter(|index| *index == 0 || path[..*index].ends_with('/'))
+        .map_or_else(
+            || path.trim_start_matches("./").to_string(),
+            |index| path[index + prefix.len()..].to_string(),
+        )
+}
+
+/// Path of a file picked on disk relative to the `assets` folder of the working directory.
+/// `None` for files outside of it, which [`PrefabLoader`] can never load.
+pub fn file_asset_path(file: impl AsRef<Path>) -> Option<String> {
+    let dir = std::env::current_dir().ok()?;
+    path_under(&dir.join(ASSETS_DIR), &dir.join(file))
+}
+
+fn path_under(dir: &Path, file: &Path) -> Option<String> {
+    let mut parts = vec![];
+    for component in file.strip_prefix(dir).ok()?.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn send_change(world: &mut World, change: impl EditorChange + Send + Sync + 'static) {
+    if let Some(mut events) = world.get_resource_mut::<Events<NewChange>>() {
+        events.send(NewChange {
+            change: Arc::new(change),
+        });
+    }
+}
+
+/// Saved components of the entities, with their hierarchy in [`ChildrenPrefab`]
+fn extract_entities(world: &World, entities: &[Entity]) -> DynamicScene {
+    let allow_types = world
+        .get_resource::<EditorRegistry>()
+        .map(EditorRegistry::saved_types)
+        .unwrap_or_default();
+    let mut scene = DynamicSceneBuilder::from_world(world)
+        .with_filter(SceneFilter::Allowlist(allow_types))
+        .extract_entities(entities.iter().copied())
+        .build();
+    for entity in scene.entities.iter_mut() {
+        if let Some(children) = world.get::<Children>(entity.entity) {
+            let children = children
+                .iter()
+                .copied()
+                .filter(|child| world.get::<NoSave>(*child).is_none())
+                .collect();
+            entity.components.push(Box::new(ChildrenPrefab(children)));
+        }
+    }
+    scene
+}
+
+/// Keep the undo system from recording the spawn, despawn or edit of the entity as separate changes
+fn ignore_undo(world: &mut World, entity: Entity) {
+    if let Some(mut storage) = world.get_resource_mut::<UndoIgnoreStorage>() {
+        storage
+            .storage
+            .insert(entity, OneFrameUndoIgnore::default());
+    }
+    if let Some(mut entity) = world.get_entity_mut(entity) {
+        entity.insert(OneFrameUndoIgnore::default());
+    }
+}
+
+fn despawn_entities(world: &mut World, entities: impl IntoIterator<Item = Entity>) {
+    for entity in entities {
+        let mut stack = vec![entity];
+        while let Some(entity) = stack.pop() {
+            if let Some(children) = world.get::<Children>(entity) {
+                stack.extend(children.iter());
+            }
+            ignore_undo(world, entity);
+        }
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+/// Spawn extracted entities again, with `roots` attached to `parent`. Returns old to new entity pairs
+fn respawn_entities(
+    world: &mut World,
+    scene: &DynamicScene,
+    roots: &[Entity],
+    parent: Option<Entity>,
+) -> Result<Vec<(Entity, Entity)>, String> {
+    let mut map = EntityHashMap::default();
+    scene
+        .write_to_world(world, &mut map)
+        .map_err(|e| e.to_string())?;
+    for entity in map.values() {
+        ignore_undo(world, *entity);
+    }
+    if let Some(parent) = parent.filter(|parent| world.get_entity(*parent).is_some()) {
+        for root in roots.iter().filter_map(|root| map.get(root)) {
+            world.entity_mut(*root).set_parent(parent);
+        }
+    }
+    Ok(map.into_iter().collect())
+}
+
+/// Undo step of [`create_prefab_from_entities`], which swaps the saved entities with the prefab instance
+struct CreatedPrefab {
+    /// Saved entities as they were before the prefab was created
+    entities: Arc<DynamicScene>,
+    roots: Vec<Entity>,
+    parent: Option<Entity>,
+    instance: Entity,
+    path: String,
+    name: Name,
+    transform: Transform,
+    /// Reverting brings back the saved entities, otherwise the instance
+    restores_entities: bool,
+}
+
+impl EditorChange for CreatedPrefab {
+    fn revert(
+        &self,
+        world: &mut World,
+        entity_remap: &bevy::utils::HashMap<Entity, Entity>,
+    ) -> Result<ChangeResult, String> {
+        let parent = self
+            .parent
+            .map(|parent| get_entity_with_remap(parent, entity_remap));
+        if self.restores_entities {
+            despawn_entities(world, [get_entity_with_remap(self.instance, entity_remap)]);
+            let remap = respawn_entities(world, &self.entities, &self.roots, parent)?;
+            info!("Reverted creation of prefab {}", self.path);
+            return Ok(ChangeResult::SuccessWithRemap(remap));
+        }
+
+        despawn_entities(
+            world,
+            self.roots
+                .iter()
+                .map(|root| get_entity_with_remap(*root, entity_remap)),
+        );
+        let parent = parent.filter(|parent| world.get_entity(*parent).is_some());
+        let mut instance = world.spawn((
+            PrefabBundle::new(&self.path),
+            PrefabMarker,
+            self.name.clone(),
+            OneFrameUndoIgnore::default(),
+        ));
+        instance.insert(self.transform);
+        if let Some(parent) = parent {
+            instance.set_parent(parent);
+        }
+        let instance = instance.id();
+        info!("Created prefab {} again", self.path);
+        Ok(ChangeResult::SuccessWithRemap(vec![(
+            self.instance,
+            instance,
+        )]))
+    }
+
+    fn debug_text(&self) -> String {
+        format!("Created prefab {}", self.path)
+    }
+
+    fn get_inverse(&self) -> Arc<dyn EditorChange + Send + Sync> {
+        Arc::new(Self {
+            entities: self.entities.clone(),
+            roots: self.roots.clone(),
+            parent: self.parent,
+            instance: self.instance,
+            path: self.path.clone(),
+            name: self.name.clone(),
+            transform: self.transform,
+            restores_entities: !self.restores_entities,
+        })
+    }
+}
+
+/// Undo step of [`unpack_prefab_instance`], which swaps the instance content with editable entities
+struct UnpackedPrefab {
+    instance: Entity,
+    path: String,
+    overrides: Option<PrefabOverrides>,
+    /// Unpacked entities as they were right after unpacking
+    entities: Arc<DynamicScene>,
+    roots: Vec<Entity>,
+    /// Reverting makes the entity an instance again, otherwise brings back the unpacked entities
+    restores_instance: bool,
+}
+
+impl EditorChange for UnpackedPrefab {
+    fn revert(
+        &self,
+        world: &mut World,
+        entity_remap: &bevy::utils::HashMap<Entity, Entity>,
+    ) -> Result<ChangeResult, String> {
+        let instance = get_entity_with_remap(self.instance, entity_remap);
+        if world.get_entity(instance).is_none() {
+            return Err(format!("Prefab instance {instance} does not exist"));
+        }
+        ignore_undo(world, instance);
+        if self.restores_instance {
+            despawn_entities(
+                world,
+                self.roots
+                    .iter()
+                    .map(|root| get_entity_with_remap(*root, entity_remap)),
+            );
+            let mut entity = world.entity_mut(instance);
+            entity.insert(PrefabLoader {
+                path: self.path.clone(),
+            });
+            if let Some(overrides) = &self.overrides {
+                entity.insert(overrides.clone());
+            }
+            info!("Reverted unpacking of prefab {}", self.path);
+            return Ok(ChangeResult::Success);
+        }
+
+        let content = world
+            .get::<Children>(instance)
+            .map(|children| {
+                children
+                    .iter()
+                    .copied()
+                    .filter(|child| world.get::<PrefabAutoChild>(*child).is_some())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        despawn_entities(world, content);
+        world
+            .entity_mut(instance)
+            .remove::<(PrefabLoader, PrefabOverrides)>();
+        let remap = respawn_entities(world, &self.entities, &self.roots, Some(instance))?;
+        info!("Unpacked prefab {} again", self.path);
+        Ok(ChangeResult::SuccessWithRemap(remap))
+    }
+
+    fn debug_text(&self) -> String {
+        format!("Unpacked prefab {}", self.path)
+    }
+
+    fn get_inverse(&self) -> Arc<dyn EditorChange + Send + Sync> {
+        Arc::new(Self {
+            instance: self.instance,
+            path: self.path.clone(),
+            overrides: self.overrides.clone(),
+            entities: self.entities.clone(),
+            roots: self.roots.clone(),
+            restores_instance: !self.restores_instance,
+        })
+    }
+}
+
+/// Save the entities with their children to a new prefab file at `path` (relative to assets folder)
+/// and replace them with an instance of this prefab. Returns the instance entity.
+pub fn create_prefab_from_entities(
+    world: &mut World,
+    entities: &[Entity],
+    path: &str,
+) -> Result<Entity, PrefabPackError> {
+    let selected = entities
+        .iter()
+        .copied()
+        .filter(|e| world.get::<PrefabMarker>(*e).is_some())
+        .collect::<HashSet<_>>();
+    // Selected children of selected entities are saved with their parents
+    let roots = entities
+        .iter()
+        .copied()
+        .filter(|e| selected.contains(e))
+        .filter(|e| {
+            let mut current = world.get::<Parent>(*e).map(Parent::get);
+            while let Some(parent) = current {
+                if selected.contains(&parent) {
+                    return false;
+                }
+                current = world.get::<Parent>(parent).map(Parent::get);
+            }
+            true
+        })
+        .collect::<Vec<_>>();
+    let Some(first) = roots.first().copied() else {
+        return Err(PrefabPackError::NoEntities);
+    };
+    let parent = world.get::<Parent>(first).map(Parent::get);
+    if roots
+        .iter()
+        .any(|e| world.get::<Parent>(*e).map(Parent::get) != parent)
+    {
+        return Err(PrefabPackError::DifferentParents);
+    }
+
+    let mut prefab_entities = vec![];
+    let mut stack = roots.iter().rev().copied().collect::<Vec<_>>();
+    while let Some(entity) = stack.pop() {
+        if world.get::<PrefabMarker>(entity).is_none() || !is_saved(world, entity) {
+            continue;
+        }
+        prefab_entities.push(entity);
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+
+    let entities = Arc::new(extract_entities(world, &prefab_entities));
+    let mut scene = extract_entities(world, &prefab_entities);
+
+    // Single root keeps its place through the instance transform
+    let transform = if roots.len() == 1 {
+        let transform = world.get::<Transform>(first).copied().unwrap_or_default();
+        if let Some(root) = scene.entities.iter_mut().find(|e| e.entity == first) {
+            for component in root.components.iter_mut() {
+                if component.represents::<Transform>() {
+                    *component = Box::new(Transform::IDENTITY);
+                }
+            }
+        }
+        transform
+    } else {
+        Transform::IDENTITY
+    };
+    let name = if roots.len() == 1 {
+        world.get::<Name>(first).cloned()
+    } else {
+        None
+    }
+    .unwrap_or_else(|| {
+        Name::new(
+            path.rsplit('/')
+                .next()
+                .and_then(|file| file.split('.').next())
+                .unwrap_or("Prefab")
+                .to_string(),
+        )
+    });
+
+    let bytes = prefab_file_bytes(world, &scene, path).map_err(PrefabPackError::Serialize)?;
+    let file = Path::new(ASSETS_DIR).join(path);
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(PrefabPackError::Io)?;
+    }
+    write_prefab_bytes(world, &file, &bytes).map_err(PrefabPackError::Io)?;
+    info!("Created prefab {path}");
+
+    despawn_entities(world, roots.iter().copied());
+    let mut instance = world.spawn((
+        PrefabBundle::new(path),
+        PrefabMarker,
+        name.clone(),
+        OneFrameUndoIgnore::default(),
+    ));
+    instance.insert(transform);
+    if let Some(parent) = parent {
+        instance.set_parent(parent);
+    }
+    let instance = instance.id();
+    // Swapping entities with the instance is one undo step
+    send_change(
+        world,
+        CreatedPrefab {
+            entities,
+            roots,
+            parent,
+            instance,
+            path: path.to_string(),
+            name,
+            transform,
+            restores_entities: true,
+        },
+    );
+    Ok(instance)
+}
+
+/// Replace the spawned content of prefab instance with ordinary editable entities.
+/// The instance entity is kept as their parent. Returns the new entities.
+pub fn unpack_prefab_instance(
+    world: &mut World,
+    instance: Entity,
+) -> Result<Vec<Entity>, PrefabPackError> {
+    if world.get::<PrefabLoader>(instance).is_none() {
+        return Err(PrefabPackError::NotInstance);
+    }
+    let scene_root = instance_scene_root(world, instance).ok_or(PrefabPackError::NotInstance)?;
+    let entities = instance_entities(world, scene_root);
+
+    let mut queue = CommandQueue::default();
+    let mut map = HashMap::new();
+    {
+        let mut commands = Commands::new(&mut queue, world);
+        let registry = world.resource::<EditorRegistry>();
+        for entity in entities.iter() {
+            let mut cmds = commands.spawn(PrefabMarker);
+            registry.clone_entity_flat(&mut cmds, &world.entity(*entity));
+            map.insert(*entity, cmds.id());
+        }
+        for entity in entities.iter() {
+            let parent = world
+                .get::<Parent>(*entity)
+                .map(Parent::get)
+                .and_then(|parent| map.get(&parent).copied())
+                .unwrap_or(instance);
+            commands.entity(parent).add_child(map[entity]);
+        }
+    }
+    queue.apply(world);
+
+    let path = world
+        .get::<PrefabLoader>(instance)
+        .map(|loader| loader.path.clone())
+        .unwrap_or_default();
+    let overrides = world.get::<PrefabOverrides>(instance).cloned();
+    ignore_undo(world, instance);
+    world
+        .entity_mut(instance)
+        .remove::<(PrefabLoader, PrefabOverrides)>();
+    despawn_entities(world, [scene_root]);
+
+    let new_entities = entities.iter().map(|e| map[e]).collect::<Vec<_>>();
+    for entity in new_entities.iter() {
+        ignore_undo(world, *entity);
+    }
+    let roots = new_entities
+        .iter()
+        .copied()
+        .filter(|e| world.get::<Parent>(*e).map(Parent::get) == Some(instance))
+        .collect();
+    send_change(
+        world,
+        UnpackedPrefab {
+            instance,
+            path,
+            overrides,
+            entities: Arc::new(extract_entities(world, &new_entities)),
+            roots,
+            restores_instance: true,
+        },
+    );
+    info!("Unpacked prefab instance {instance}");
+    Ok(new_entities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load::LoadPlugin, prelude::EditorRegistryExt, test_dir::TempDir};
+    use space_undo::{ChangeChain, SyncUndoMarkersPlugin, UndoPlugin, UndoRedo};
+
+    #[test]
+    fn asset_paths() {
+        assert_eq!(
+            asset_path_of("./assets/prefabs/a.scn.ron"),
+            "prefabs/a.scn.ron"
+        );
+        assert_eq!(asset_path_of("/home/me/game/assets/a.scn.ron"), "a.scn.ron");
+        assert_eq!(asset_path_of("prefabs/a.scn.ron"), "prefabs/a.scn.ron");
+        assert_eq!(asset_path_of("my_assets/a.scn.ron"), "my_assets/a.scn.ron");
+    }
+
+    #[test]
+    fn file_asset_paths() {
+        let dir = std::env::current_dir().unwrap();
+        assert_eq!(
+            file_asset_path(dir.join("assets/prefabs/a.scn.ron")).as_deref(),
+            Some("prefabs/a.scn.ron")
+        );
+        assert_eq!(
+            file_asset_path("./assets/a.scn.ron").as_deref(),
+            Some("a.scn.ron")
+        );
+        assert_eq!(file_asset_path(dir.join("a.scn.ron")), None);
+        assert_eq!(file_asset_path(dir.join("my_assets/a.scn.ron")), None);
+        assert_eq!(file_asset_path("assets/../a.scn.ron"), None);
+        assert_eq!(file_asset_path("assets"), None);
+    }
+
+    fn wait_instance_spawn(app: &mut App, instance: Entity) {
+        for _ in 0..200 {
+            app.update();
+            if instance_scene_root(app.world(), instance).is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        app.update();
+    }
+
+    fn names(world: &mut World) -> Vec<String> {
+        let mut names = world
+            .query_filtered::<&Name, With<PrefabMarker>>()
+            .iter(world)
+            .map(Name::to_string)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn create_and_unpack_prefab() {
+        let dir = TempDir::new("pack");
+        let path = dir.join("tree.scn.ron").to_string_lossy().to_string();
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            HierarchyPlugin,
+            bevy::scene::ScenePlugin,
+            crate::prelude::EditorRegistryPlugin,
+            crate::format::SceneFormatPlugin,
+            LoadPlugin,
+        ))
+        .add_event::<NewChange>()
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>()
+        .editor_registry::<PrefabMarker>()
+        .editor_registry::<ChildrenPrefab>();
+
+        let world = app.world_mut();
+        let group = world.spawn((PrefabMarker, Name::new("group"))).id();
+        let tree = world
+            .spawn((
+                PrefabMarker,
+                Name::new("tree"),
+                Transform::from_xyz(5.0, 0.0, 0.0),
+            ))
+            .set_parent(group)
+            .id();
+        let leaf = world
+            .spawn((
+                PrefabMarker,
+                Name::new("leaf"),
+                Transform::from_xyz(0.0, 1.0, 0.0),
+            ))
+            .set_parent(tree)
+            .id();
+
+        let instance = create_prefab_from_entities(world, &[leaf, tree], &path).unwrap();
+        assert!(world.get_entity(tree).is_none());
+        assert!(world.get_entity(leaf).is_none());
+        assert_eq!(world.get::<Parent>(instance).unwrap().get(), group);
+        assert_eq!(world.get::<Name>(instance).unwrap().as_str(), "tree");
+        assert_eq!(
+            world.get::<Transform>(instance).unwrap().translation,
+            Vec3::X * 5.0
+        );
+        assert_eq!(world.resource::<Events<NewChange>>().len(), 1);
+
+        wait_instance_spawn(&mut app, instance);
+
+        let world = app.world_mut();
+        let unpacked = unpack_prefab_instance(world, instance).unwrap();
+        assert_eq!(unpacked.len(), 2);
+        assert!(world.get::<PrefabLoader>(instance).is_none());
+        let names = unpacked
+            .iter()
+            .map(|e| world.get::<Name>(*e).unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["tree", "leaf"]);
+        let (tree, leaf) = (unpacked[0], unpacked[1]);
+        assert_eq!(world.get::<Parent>(tree).unwrap().get(), instance);
+        assert_eq!(world.get::<Parent>(leaf).unwrap().get(), tree);
+        assert_eq!(world.get::<Transform>(tree).unwrap(), &Transform::IDENTITY);
+        assert_eq!(world.get::<Transform>(leaf).unwrap().translation, Vec3::Y);
+        assert!(world.get::<PrefabMarker>(leaf).is_some());
+    }
+
+    #[test]
+    fn one_undo_reverts_create_and_unpack() {
+        let dir = TempDir::new("pack_undo");
+        let path = dir.join("tree.scn.ron").to_string_lossy().to_string();
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            HierarchyPlugin,
+            bevy::scene::ScenePlugin,
+            bevy_scene_hook::HookPlugin,
+            UndoPlugin,
+            SyncUndoMarkersPlugin::<PrefabMarker>::default(),
+            crate::prelude::EditorRegistryPlugin,
+            crate::format::SceneFormatPlugin,
+            LoadPlugin,
+        ))
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>()
+        .editor_registry::<PrefabMarker>()
+        .editor_registry::<ChildrenPrefab>();
+
+        let world = app.world_mut();
+        let group = world.spawn((PrefabMarker, Name::new("group"))).id();
+        let tree = world
+            .spawn((
+                PrefabMarker,
+                Name::new("tree"),
+                Transform::from_xyz(5.0, 0.0, 0.0),
+            ))
+            .set_parent(group)
+            .id();
+        world
+            .spawn((PrefabMarker, Name::new("leaf"), Transform::IDENTITY))
+            .set_parent(tree);
+        for _ in 0..5 {
+            app.update();
+        }
+        let changes = app.world().resource::<ChangeChain>().changes.len();
+
+        let instance = create_prefab_from_entities(app.world_mut(), &[tree], &path).unwrap();
+        wait_instance_spawn(&mut app, instance);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<ChangeChain>().changes.len(),
+            changes + 1
+        );
+
+        app.world_mut().send_event(UndoRedo::Undo);
+        for _ in 0..5 {
+            app.update();
+        }
+        let world = app.world_mut();
+        assert!(world.get_entity(instance).is_none());
+        assert_eq!(names(world), ["group", "leaf", "tree"]);
+        let (tree, tree_parent, transform) = world
+            .query::<(Entity, &Name, &Parent, &Transform)>()
+            .iter(world)
+            .find(|(_, name, ..)| name.as_str() == "tree")
+            .map(|(e, _, parent, transform)| (e, parent.get(), *transform))
+            .unwrap();
+        assert_eq!(tree_parent, group);
+        assert_eq!(transform.translation, Vec3::X * 5.0);
+        let leaf = world.get::<Children>(tree).unwrap()[0];
+        assert_eq!(world.get::<Name>(leaf).unwrap().as_str(), "leaf");
+
+        app.world_mut().send_event(UndoRedo::Redo);
+        for _ in 0..5 {
+            app.update();
+        }
+        let world = app.world_mut();
+        let (instance, path) = world
+            .query::<(Entity, &PrefabLoader)>()
+            .iter(world)
+            .map(|(e, loader)| (e, loader.path.clone()))
+            .next()
+            .unwrap();
+        assert!(world.get_entity(tree).is_none());
+        assert_eq!(world.get::<Parent>(instance).unwrap().get(), group);
+        wait_instance_spawn(&mut app, instance);
+        for _ in 0..5 {
+            app.update();
+        }
+        let changes = app.world().resource::<ChangeChain>().changes.len();
+
+        let unpacked = unpack_prefab_instance(app.world_mut(), instance).unwrap();
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<ChangeChain>().changes.len(),
+            changes + 1
+        );
+
+        app.world_mut().send_event(UndoRedo::Undo);
+        wait_instance_spawn(&mut app, instance);
+        let world = app.world_mut();
+        assert!(unpacked.iter().all(|e| world.get_entity(*e).is_none()));
+        assert_eq!(world.get::<PrefabLoader>(instance).unwrap().path, path);
+        assert_eq!(names(world), ["group", "tree"]);
+    }
+}
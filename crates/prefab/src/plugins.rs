@@ -15,9 +15,11 @@ use bevy_scene_hook::HookPlugin;
 use space_shared::toast::ToastMessage;
 use space_shared::{LightAreaToggle, PrefabMarker};
 
+use crate::import_settings::{GltfImportSettings, ImageImportSettings};
 use crate::{
-    component, editor_registry::EditorRegistryExt, load, prelude::EditorRegistryPlugin, save,
-    spawn_system, EditorState, PrefabSet,
+    asset_paths::AssetPathsExt, component, editor_registry::EditorRegistryExt,
+    format::SceneFormatPlugin, load, prelude::EditorRegistryPlugin, save, spawn_system,
+    EditorState, PrefabSet,
 };
 
 use component::*;
@@ -67,12 +69,28 @@ impl Plugin for BasePrefabPlugin {
 
         app.register_type::<EntityLink>();
 
+        app.register_type::<uuid::Uuid>();
+        app.register_type::<GuidLink>();
+        app.editor_silent_registry::<PrefabGuid>();
+        app.init_resource::<GuidIndex>();
+        app.add_systems(
+            Update,
+            (assign_prefab_guid, apply_deferred, update_guid_index)
+                .chain()
+                .in_set(PrefabSet::Relation),
+        );
+
         app.register_type::<Dir3>();
         app.register_type::<Dir2>();
 
+        app.register_type::<ImageImportSettings>();
+        app.register_type::<GltfImportSettings>();
+
         app.editor_registry::<Transform>();
         app.editor_registry::<Name>();
         app.editor_registry::<Visibility>();
+        app.editor_registry::<NoSave>();
+        app.editor_registry::<EditorOnly>();
 
         app.editor_registry::<GltfPrefab>();
         app.editor_registry::<MaterialPrefab>();
@@ -94,6 +112,20 @@ impl Plugin for BasePrefabPlugin {
         app.editor_registry::<AnimationTimerSpriteSheet>();
         app.editor_registry::<TextureAtlasPrefab>();
 
+        app.register_asset_paths::<GltfPrefab>(&["path"]);
+        app.register_asset_paths::<MaterialPrefab>(&[
+            "base_color_texture",
+            "emissive_texture",
+            "metallic_roughness_texture",
+            "normal_map_texture",
+            "occlusion_texture",
+            "depth_map",
+        ]);
+        app.register_asset_paths::<ColorMaterialPrefab>(&["texture"]);
+        app.register_asset_paths::<SpriteTexture>(&["texture"]);
+        app.register_asset_paths::<SpritesheetTexture>(&["texture"]);
+        app.register_asset_paths::<PrefabLoader>(&["path"]);
+
         app.editor_registry::<MeshPrimitive3dPrefab>();
         app.editor_relation::<MeshPrimitive3dPrefab, Transform>();
         app.editor_relation::<MeshPrimitive3dPrefab, Visibility>();
@@ -236,8 +268,11 @@ impl Plugin for BasePrefabPlugin {
         );
         app.add_systems(Update, animate_sprite);
 
+        app.add_plugins(SceneFormatPlugin);
         app.add_plugins(SavePrefabPlugin);
         app.add_plugins(LoadPlugin);
+        app.add_plugins(crate::overrides::PrefabOverridesPlugin);
+        app.add_plugins(crate::merge::SceneMergePlugin);
         app.add_plugins(crate::sub_scene::SceneUnpackPlugin);
     }
 }
@@ -1,13 +1,33 @@
 use bevy::{
-    ecs::{entity::MapEntities, reflect::ReflectMapEntities},
+    ecs::{entity::MapEntities, reflect::ReflectMapEntities, system::RunSystemOnce},
     prelude::*,
-    tasks::IoTaskPool,
-    utils::HashSet,
+    reflect::{serde::SerializationData, ReflectRef, ReflectSerialize, TypeRegistry},
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+    utils::{Duration, Instant},
 };
 use space_shared::{EditorPrefabPath, PrefabMarker, PrefabMemoryCache};
-use std::{any::TypeId, fs, io::Write};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    format::{
+        binary::{scene_to_binary, BINARY_SCENE_EXTENSION},
+        canonical::canonicalize_scene,
+        split::{is_split_scene_file, write_split_scene},
+        unknown::expand_unknown_components,
+        SceneTree,
+    },
+    prelude::{
+        EditorRegistry, EditorRegistryExt, NoSave, SceneAutoChild, SceneMigrations, SceneVersion,
+        UnknownComponents,
+    },
+};
 
-use crate::prelude::{EditorRegistry, EditorRegistryExt, SceneAutoChild};
+/// Folder from which [`PrefabLoader`](crate::load::PrefabLoader) paths are resolved
+pub const ASSETS_DIR: &str = "assets";
 
 #[derive(Reflect, Default, Component, Clone)]
 #[reflect(Component, MapEntities)]
@@ -37,8 +57,14 @@ struct SaveResourcesPrefabPlugin;
 impl Plugin for SaveResourcesPrefabPlugin {
     fn build(&self, app: &mut App) {
         app.editor_registry::<ChildrenPrefab>();
+        app.register_type::<SceneVersion>();
 
+        if !app.world().contains_resource::<SceneMigrations>() {
+            app.init_resource::<SceneMigrations>();
+        }
         app.init_resource::<SaveConfig>().init_state::<SaveState>();
+        app.init_resource::<PendingSaves>();
+        app.add_event::<SaveCompleted>();
     }
 }
 
@@ -54,19 +80,242 @@ impl Plugin for SavePrefabPlugin {
             (
                 prepare_children,
                 apply_deferred,
+                crate::overrides::refresh_prefab_overrides,
                 serialize_scene,
                 delete_prepared_children,
             )
                 .chain(),
         );
+        app.add_systems(Update, poll_pending_saves);
     }
 }
 
 /// This struct determine path to save prefab
 #[cfg(not(tarpaulin_include))]
-#[derive(Resource, Clone, Default)]
+#[derive(Resource, Clone)]
 pub struct SaveConfig {
     pub path: Option<EditorPrefabPath>,
+    /// Write scene files in canonical form (sorted entities, components and map keys), which keeps VCS diffs small
+    pub canonical: bool,
+    /// Encoding of scene files
+    pub format: SceneFileFormat,
+    /// Number of previous versions kept next to the scene file as `<file>.bak1`, `<file>.bak2`, ...
+    pub backups: usize,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            canonical: false,
+            format: SceneFileFormat::default(),
+            backups: 3,
+        }
+    }
+}
+
+/// Upper bound for searching backups on disk
+const MAX_BACKUPS: usize = 64;
+
+/// Path of the `n`-th backup of the scene file, `1` being the newest
+pub fn backup_path(path: impl AsRef<Path>, n: usize) -> PathBuf {
+    let mut file = path.as_ref().as_os_str().to_owned();
+    file.push(format!(".bak{n}"));
+    PathBuf::from(file)
+}
+
+/// Whether `name` is a file name of a backup made by [`backup_path`] or of an unfinished write
+pub fn is_backup_file(name: &str) -> bool {
+    name.ends_with(".tmp")
+        || name
+            .rsplit_once(".bak")
+            .is_some_and(|(_, n)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Newest existing backup of the scene file
+pub fn newest_backup(path: impl AsRef<Path>) -> Option<PathBuf> {
+    (1..=MAX_BACKUPS)
+        .map(|n| backup_path(&path, n))
+        .find(|backup| backup.is_file())
+}
+
+/// Write scene file atomically through a temporary file, so a failed save never leaves a truncated scene.
+///
+/// The replaced file is kept as `.bak1` and older backups are shifted up to `backups` copies.
+pub fn write_scene_file(path: impl AsRef<Path>, bytes: &[u8], backups: usize) -> io::Result<()> {
+    let path = path.as_ref();
+    if backups > 0 && path.is_file() {
+        let previous = fs::read(path)?;
+        return replace_file(path, bytes, || rotate_backups(path, backups, &previous));
+    }
+    replace_file(path, bytes, || Ok(()))
+}
+
+/// Write file atomically through a temporary file, so an interrupted write never leaves a truncated file
+pub fn write_file_atomic(path: impl AsRef<Path>, bytes: &[u8]) -> io::Result<()> {
+    replace_file(path.as_ref(), bytes, || Ok(()))
+}
+
+/// Move `bytes` over `path` through a temporary file.
+/// `before_replace` runs once the new content is on disk, right before the old file is replaced
+fn replace_file(
+    path: &Path,
+    bytes: &[u8],
+    before_replace: impl FnOnce() -> io::Result<()>,
+) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+
+    if let Err(e) = before_replace() {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Shift backups of the file at `path` up by one, keeping `backups` copies,
+/// and store `previous` file content as `.bak1`
+pub(crate) fn rotate_backups(path: &Path, backups: usize, previous: &[u8]) -> io::Result<()> {
+    let _ = fs::remove_file(backup_path(path, backups));
+    for n in (1..backups).rev() {
+        let backup = backup_path(path, n);
+        if backup.is_file() {
+            fs::rename(&backup, backup_path(path, n + 1))?;
+        }
+    }
+    fs::write(backup_path(path, 1), previous)
+}
+
+/// Replace scene file with its newest backup. The broken file is kept as `<file>.broken`.
+/// Returns the restored backup path.
+pub fn restore_newest_backup(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    let backup = newest_backup(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no backup of scene file"))?;
+    if path.is_file() {
+        let mut broken = path.as_os_str().to_owned();
+        broken.push(".broken");
+        fs::copy(path, broken)?;
+    }
+    write_scene_file(path, &fs::read(&backup)?, 0)?;
+    Ok(backup)
+}
+
+/// Folder inside assets where autosaved copies of open scenes are written
+pub const RECOVERY_DIR: &str = ".recovery";
+
+/// Recovery file name of a scene that was never saved
+const UNTITLED_SCENE: &str = "untitled.scn.ron";
+
+/// Asset path of the autosave file for the scene saved at `path`, `None` for a never saved scene
+pub fn recovery_asset_path(path: Option<&str>) -> String {
+    let file = path.map_or_else(
+        || UNTITLED_SCENE.to_string(),
+        |path| {
+            crate::pack::asset_path_of(path)
+                .split('/')
+                .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+                .collect::<Vec<_>>()
+                .join("/")
+                .replace(':', "_")
+        },
+    );
+    format!("{RECOVERY_DIR}/{file}")
+}
+
+/// Autosaved scene which is newer than its scene file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryFile {
+    /// Scene file the autosave was made for, `None` for a never saved scene
+    pub scene: Option<String>,
+    /// Asset path of the autosave file
+    pub asset_path: String,
+}
+
+/// Find autosave files in `assets_dir` that are newer than their scenes
+pub fn find_recovery_files(assets_dir: impl AsRef<Path>) -> Vec<RecoveryFile> {
+    let assets_dir = assets_dir.as_ref();
+    let mut found = vec![];
+    let mut dirs = vec![assets_dir.join(RECOVERY_DIR)];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(assets_dir.join(RECOVERY_DIR)) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if relative.ends_with(".tmp") {
+                continue;
+            }
+            let scene = (relative != UNTITLED_SCENE).then(|| {
+                assets_dir
+                    .join(&relative)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            });
+            let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+            let is_newer = match scene.as_deref().map(|scene| modified(Path::new(scene))) {
+                Some(Some(scene_modified)) => modified(&path).is_some_and(|m| m > scene_modified),
+                _ => true,
+            };
+            if is_newer {
+                found.push(RecoveryFile {
+                    scene,
+                    asset_path: format!("{RECOVERY_DIR}/{relative}"),
+                });
+            }
+        }
+    }
+    found.sort_by(|a, b| a.asset_path.cmp(&b.asset_path));
+    found
+}
+
+/// Encoding used when saving scene to file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SceneFileFormat {
+    /// Human readable `.scn.ron`
+    #[default]
+    Ron,
+    /// Compact `.scn.bin`, faster to load. A `.scn.ron` save path is replaced with `.scn.bin`
+    /// and the other formats replace a `.scn.bin` save path with `.scn.ron`.
+    /// Canonical form does not apply to binary files.
+    Binary { compressed: bool },
+    /// Index `.scn.ron` with scene resources and a `<name>.parts` folder with one canonical file
+    /// per root entity, named by its [`PrefabGuid`](crate::component::PrefabGuid). Keeps VCS conflicts local to edited objects.
+    Split,
+}
+
+impl SceneFileFormat {
+    /// Path of the saved file for the requested save path
+    pub fn file_path(&self, path: &str) -> String {
+        let (from, to) = match self {
+            Self::Ron | Self::Split => (BINARY_SCENE_EXTENSION, "scn.ron"),
+            Self::Binary { .. } => ("scn.ron", BINARY_SCENE_EXTENSION),
+        };
+        path.strip_suffix(from)
+            .and_then(|stem| stem.strip_suffix('.'))
+            .map_or_else(|| path.to_string(), |stem| format!("{stem}.{to}"))
+    }
 }
 
 /// State system using to enable slow logic of saving
@@ -81,35 +330,208 @@ pub enum SaveState {
 fn prepare_children(
     mut commands: Commands,
     query: Query<(Entity, &Children), (With<PrefabMarker>, Without<SceneAutoChild>)>,
+    not_saved: Query<(), With<NoSave>>,
 ) {
     for (entity, children) in query.iter() {
-        commands
-            .entity(entity)
-            .insert(ChildrenPrefab::from_children(children));
+        let children = children
+            .iter()
+            .copied()
+            .filter(|child| !not_saved.contains(*child))
+            .collect();
+        commands.entity(entity).insert(ChildrenPrefab(children));
     }
 }
 
+/// Entity is not excluded from saving by [`NoSave`] on itself or on a recursive [`NoSave`] parent
+pub(crate) fn is_saved(world: &World, entity: Entity) -> bool {
+    if world.get::<NoSave>(entity).is_some() {
+        return false;
+    }
+    let mut current = world.get::<Parent>(entity).map(Parent::get);
+    while let Some(parent) = current {
+        if world
+            .get::<NoSave>(parent)
+            .is_some_and(|no_save| no_save.recursive)
+        {
+            return false;
+        }
+        current = world.get::<Parent>(parent).map(Parent::get);
+    }
+    true
+}
+
 fn delete_prepared_children(mut commands: Commands, query: Query<Entity, With<ChildrenPrefab>>) {
     for entity in query.iter() {
         commands.entity(entity).remove::<ChildrenPrefab>();
     }
 }
 
-/// Convert world scene to prefab
-pub fn serialize_scene(world: &mut World) {
-    let Some(config) = world.get_resource::<SaveConfig>().cloned() else {
+/// Reason of failed scene save or load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneIoError {
+    /// Reading or writing the file failed
+    Io(String),
+    /// Scene could not be encoded or decoded
+    Serialization(String),
+    /// Scene contains a type that is not registered in the app
+    UnknownType(String),
+    /// Required registry or config resource is not initialized
+    MissingRegistry(&'static str),
+}
+
+impl std::fmt::Display for SceneIoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Error while writing scene to file: {e}"),
+            Self::Serialization(e) => write!(f, "failed to serialize prefab: {e}"),
+            Self::UnknownType(e) => write!(f, "Scene contains unregistered type: {e}"),
+            Self::MissingRegistry(name) => write!(f, "{name} not initialized"),
+        }
+    }
+}
+
+impl std::error::Error for SceneIoError {}
+
+/// First type in the scene that can not be written, because it or a type of its fields
+/// is missing from the `registry` or has no `ReflectSerialize`
+pub fn unserializable_type(scene: &DynamicScene, registry: &TypeRegistry) -> Option<String> {
+    scene
+        .resources
+        .iter()
+        .chain(
+            scene
+                .entities
+                .iter()
+                .flat_map(|entity| entity.components.iter()),
+        )
+        .find_map(|value| unserializable_value(value.as_ref(), registry))
+}
+
+fn unserializable_value(value: &dyn Reflect, registry: &TypeRegistry) -> Option<String> {
+    let Some(info) = value.get_represented_type_info() else {
+        return Some(value.reflect_type_path().to_string());
+    };
+    let registration = registry.get(info.type_id());
+    // Types with their own serde implementation are written as a whole
+    if registration.is_some_and(|registration| registration.data::<ReflectSerialize>().is_some()) {
+        return None;
+    }
+    let skipped = |index: usize| {
+        registration
+            .and_then(|registration| registration.data::<SerializationData>())
+            .is_some_and(|data| data.is_field_skipped(index))
+    };
+    let fields: Vec<&dyn Reflect> = match value.reflect_ref() {
+        ReflectRef::Struct(value) => value
+            .iter_fields()
+            .enumerate()
+            .filter(|(index, _)| !skipped(*index))
+            .map(|(_, field)| field)
+            .collect(),
+        ReflectRef::TupleStruct(value) => value
+            .iter_fields()
+            .enumerate()
+            .filter(|(index, _)| !skipped(*index))
+            .map(|(_, field)| field)
+            .collect(),
+        ReflectRef::Tuple(value) => value.iter_fields().collect(),
+        ReflectRef::List(value) => value.iter().collect(),
+        ReflectRef::Array(value) => value.iter().collect(),
+        ReflectRef::Map(value) => value.iter().flat_map(<[_; 2]>::from).collect(),
+        ReflectRef::Enum(value) => value.iter_fields().map(|field| field.value()).collect(),
+        ReflectRef::Value(_) => return Some(info.type_path().to_string()),
+    };
+    fields
+        .into_iter()
+        .find_map(|field| unserializable_value(field, registry))
+}
+
+/// Sent when [`serialize_scene`] finished, after the file is written
+#[derive(Event, Debug, Clone)]
+pub struct SaveCompleted {
+    /// Path of the written file, the `.scn.bin` path for binary scenes.
+    /// `None` if save config is missing
+    pub path: Option<EditorPrefabPath>,
+    /// Number of saved entities
+    pub entities: usize,
+    pub duration: Duration,
+    pub result: Result<(), SceneIoError>,
+}
+
+/// Scene file writes which have not finished yet
+#[derive(Resource, Default)]
+pub struct PendingSaves {
+    tasks: Vec<(SaveCompleted, Instant, Task<io::Result<()>>)>,
+}
+
+fn save_completed(world: &mut World, event: SaveCompleted) {
+    if let Err(e) = &event.result {
         #[cfg(feature = "editor")]
         world.send_event(space_shared::toast::ToastMessage::new(
-            "Save config resource not initialized",
+            &e.to_string(),
             space_shared::toast::ToastKind::Error,
         ));
-        error!("Save config resource not initialized");
+        error!("{e}");
+    }
+    world.send_event(event);
+}
+
+/// Send [`SaveCompleted`] for finished file writes
+pub fn poll_pending_saves(world: &mut World) {
+    let Some(mut pending) = world.get_resource_mut::<PendingSaves>() else {
         return;
     };
+    let mut finished = vec![];
+    pending.tasks.retain_mut(|(event, started, task)| {
+        let Some(result) = block_on(future::poll_once(task)) else {
+            return true;
+        };
+        let mut event = event.clone();
+        event.duration = started.elapsed();
+        event.result = result.map_err(|e| SceneIoError::Io(e.to_string()));
+        finished.push(event);
+        false
+    });
+    for event in finished {
+        save_completed(world, event);
+    }
+}
 
-    let mut prefab_query =
-        world.query_filtered::<Entity, (With<PrefabMarker>, Without<SceneAutoChild>)>();
-    let entities = prefab_query.iter(world).collect::<Vec<_>>();
+/// Convert world scene to prefab
+pub fn serialize_scene(world: &mut World) {
+    let started = Instant::now();
+    let Some(config) = world.get_resource::<SaveConfig>().cloned() else {
+        save_completed(
+            world,
+            SaveCompleted {
+                path: None,
+                entities: 0,
+                duration: started.elapsed(),
+                result: Err(SceneIoError::MissingRegistry("Save config resource")),
+            },
+        );
+        return;
+    };
+
+    // Binary scenes are written next to the requested `.scn.ron` path
+    let saved_path = match &config.path {
+        Some(EditorPrefabPath::File(path)) => {
+            Some(EditorPrefabPath::File(config.format.file_path(path)))
+        }
+        path => path.clone(),
+    };
+    let entities = saved_entities(world);
+    let failed = |world: &mut World, error: SceneIoError| {
+        save_completed(
+            world,
+            SaveCompleted {
+                path: saved_path.clone(),
+                entities: entities.len(),
+                duration: started.elapsed(),
+                result: Err(error),
+            },
+        );
+    };
 
     if entities.is_empty() {
         #[cfg(feature = "editor")]
@@ -120,84 +542,114 @@ pub fn serialize_scene(world: &mut World) {
         warn!("Saving empty scene");
     }
 
-    let Some(registry) = world.get_resource::<EditorRegistry>().cloned() else {
-        #[cfg(feature = "editor")]
-        world.send_event(space_shared::toast::ToastMessage::new(
-            "Editor Registry not initialized",
-            space_shared::toast::ToastKind::Error,
-        ));
-        error!("Editor Registry not initialized");
-        return;
+    let mut scene = match extract_saved_scene(world, &entities) {
+        Ok(scene) => scene,
+        Err(error) => {
+            failed(world, error);
+            return;
+        }
     };
-    let allow_types: Vec<TypeId> = registry
-        .registry
-        .read()
-        .iter()
-        .map(|a| a.type_info().type_id())
-        .collect();
 
-    let mut builder = DynamicSceneBuilder::from_world(world);
-    builder = builder
-        .allow_all()
-        .with_filter(SceneFilter::Allowlist(HashSet::from_iter(
-            allow_types.iter().cloned(),
-        )))
-        .extract_entities(entities.iter().copied());
-    let scene = builder.build();
+    // Scene files are stamped with the format version, so they can be migrated on load
+    let version = world
+        .get_resource::<SceneMigrations>()
+        .map(SceneMigrations::current_version)
+        .unwrap_or_default();
+    if matches!(config.path, Some(EditorPrefabPath::File(_))) {
+        scene.resources.push(Box::new(SceneVersion(version)));
+    }
 
     let Some(app_registry) = world.get_resource::<AppTypeRegistry>() else {
-        #[cfg(feature = "editor")]
-        world.send_event(space_shared::toast::ToastMessage::new(
-            "App Registry not initialized",
-            space_shared::toast::ToastKind::Error,
-        ));
-        error!("App Registry not initialized");
+        failed(world, SceneIoError::MissingRegistry("App Registry"));
         return;
     };
+    let app_registry = app_registry.clone();
+
+    let unserializable = unserializable_type(&scene, &app_registry.read());
+    if let Some(type_path) = unserializable {
+        failed(world, SceneIoError::UnknownType(type_path));
+        return;
+    }
+    let mut res = scene.serialize(&app_registry.read());
+
+    // Unknown components are written back under their original type paths
+    let is_file = matches!(config.path, Some(EditorPrefabPath::File(_)));
+    let has_unknown = is_file
+        && world
+            .query_filtered::<(), With<UnknownComponents>>()
+            .iter(world)
+            .next()
+            .is_some();
+    let is_binary = is_file && matches!(config.format, SceneFileFormat::Binary { .. });
+    if let (Ok(text), true) = (
+        &mut res,
+        !is_binary && (has_unknown || (is_file && config.canonical)),
+    ) {
+        match SceneTree::parse(text) {
+            Ok(mut tree) => {
+                expand_unknown_components(&mut tree);
+                if config.canonical {
+                    canonicalize_scene(&mut tree, &app_registry.read());
+                }
+                *text = tree.to_pretty_string();
+            }
+            Err(e) => error!("Failed to post-process saved scene: {e}"),
+        }
+    }
+
+    let res = match config.format {
+        SceneFileFormat::Binary { compressed } if is_binary => {
+            scene_to_binary(&scene, &app_registry.read(), compressed, version)
+                .map_err(|e| e.to_string())
+        }
+        _ => res.map(String::into_bytes).map_err(|e| e.to_string()),
+    };
 
-    let res = scene.serialize(&app_registry.read());
-
-    if let Ok(str) = res {
-        // Write the scene RON data to file
-        let path = config.path;
-        if let Some(path) = path {
-            match path {
-                EditorPrefabPath::File(path) => {
-                    IoTaskPool::get()
-                        .spawn(async move {
-                            fs::OpenOptions::new()
-                                .create(true)
-                                .truncate(true)
-                                .append(false)
-                                .write(true)
-                                .open(&path)
-                                .and_then(|mut file| file.write(str.as_bytes()))
-                                .inspect_err(|e| error!("Error while writing scene to file: {e}"))
-                                .expect("Error while writing scene to file");
-                            info!("Saved prefab to file {}", path);
-                        })
-                        .detach();
+    match res {
+        Ok(bytes) => {
+            let completed = SaveCompleted {
+                path: saved_path.clone(),
+                entities: entities.len(),
+                duration: started.elapsed(),
+                result: Ok(()),
+            };
+            // Write the scene data to file
+            match saved_path {
+                Some(EditorPrefabPath::File(path)) => {
+                    let backups = config.backups;
+                    let split = (config.format == SceneFileFormat::Split).then_some(app_registry);
+                    let task = IoTaskPool::get().spawn(async move {
+                        if let Some(registry) = split {
+                            write_split_scene(
+                                &path,
+                                &String::from_utf8_lossy(&bytes),
+                                &registry.read(),
+                                backups,
+                            )?;
+                        } else {
+                            write_scene_file(&path, &bytes, backups)?;
+                        }
+                        info!("Saved prefab to file {}", path);
+                        Ok(())
+                    });
+                    world
+                        .get_resource_or_insert_with(PendingSaves::default)
+                        .tasks
+                        .push((completed, started, task));
                 }
-                EditorPrefabPath::MemoryCache => {
+                Some(EditorPrefabPath::MemoryCache) => {
                     let handle = world
                         .get_resource_mut::<Assets<DynamicScene>>()
                         .map(|mut assets| assets.add(scene));
                     if let Some(mut cache) = world.get_resource_mut::<PrefabMemoryCache>() {
                         cache.scene = handle;
                     }
+                    save_completed(world, completed);
                 }
+                None => {}
             }
         }
-    } else if let Err(e) = res {
-        // Any ideas on how to test this error case?
-        #[cfg(not(tarpaulin_include))]
-        let err = format!("failed to serialize prefab: {:?}", e);
-        #[cfg(feature = "editor")]
-        world.send_event(space_shared::toast::ToastMessage::new(
-            &err,
-            space_shared::toast::ToastKind::Error,
-        ));
-        error!(err);
+        Err(e) => failed(world, SceneIoError::Serialization(e)),
     }
 
     if let Some(mut state) = world.get_resource_mut::<NextState<SaveState>>() {
@@ -205,16 +657,114 @@ pub fn serialize_scene(world: &mut World) {
     }
 }
 
+/// Scene entities which are written to scene files
+fn saved_entities(world: &mut World) -> Vec<Entity> {
+    let mut prefab_query =
+        world.query_filtered::<Entity, (With<PrefabMarker>, Without<SceneAutoChild>)>();
+    prefab_query
+        .iter(world)
+        .filter(|entity| is_saved(world, *entity))
+        .collect()
+}
+
+fn extract_saved_scene(world: &World, entities: &[Entity]) -> Result<DynamicScene, SceneIoError> {
+    let registry = world
+        .get_resource::<EditorRegistry>()
+        .ok_or(SceneIoError::MissingRegistry("Editor Registry"))?;
+    Ok(DynamicSceneBuilder::from_world(world)
+        .allow_all()
+        .with_filter(SceneFilter::Allowlist(registry.saved_types()))
+        .with_resource_filter(SceneFilter::Allowlist(registry.saved_resources()))
+        .extract_entities(entities.iter().copied())
+        .extract_resources()
+        .build())
+}
+
+/// Scene with the same entities, components and resources that saving the world would write now
+pub fn saved_scene(world: &mut World) -> Result<DynamicScene, SceneIoError> {
+    world.run_system_once(prepare_children);
+    crate::overrides::refresh_prefab_overrides(world);
+    let entities = saved_entities(world);
+    let scene = extract_saved_scene(world, &entities);
+    world.run_system_once(delete_prepared_children);
+    scene
+}
+
+/// Number of backups kept for scene and prefab files
+pub fn configured_backups(world: &World) -> usize {
+    world
+        .get_resource::<SaveConfig>()
+        .map_or_else(|| SaveConfig::default().backups, |config| config.backups)
+}
+
+/// Write prefab encoded by [`prefab_file_bytes`] to `file` in the format the file already has.
+/// Split scene stays split, new files are split when [`SaveConfig`] asks for it. `.scn.bin` files are always single files
+pub(crate) fn write_prefab_bytes(world: &World, file: &Path, bytes: &[u8]) -> io::Result<()> {
+    let backups = configured_backups(world);
+    let is_binary = file
+        .to_string_lossy()
+        .ends_with(&format!(".{BINARY_SCENE_EXTENSION}"));
+    let split_new = world
+        .get_resource::<SaveConfig>()
+        .is_some_and(|config| config.format == SceneFileFormat::Split)
+        && !file.exists();
+    if !is_binary && (split_new || is_split_scene_file(file)) {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        return write_split_scene(file, &String::from_utf8_lossy(bytes), &registry, backups);
+    }
+    write_scene_file(file, bytes, backups)
+}
+
+/// Encode prefab scene for the file at `path` (relative to assets folder),
+/// with the same version stamp and post-processing as saved scenes
+pub(crate) fn prefab_file_bytes(
+    world: &World,
+    scene: &DynamicScene,
+    path: &str,
+) -> Result<Vec<u8>, String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let config = world
+        .get_resource::<SaveConfig>()
+        .cloned()
+        .unwrap_or_default();
+
+    let version = world
+        .get_resource::<SceneMigrations>()
+        .map(SceneMigrations::current_version)
+        .unwrap_or_default();
+
+    if path.ends_with(BINARY_SCENE_EXTENSION) {
+        let compressed = match config.format {
+            SceneFileFormat::Binary { compressed } => compressed,
+            SceneFileFormat::Ron | SceneFileFormat::Split => true,
+        };
+        return scene_to_binary(scene, &registry, compressed, version).map_err(|e| e.to_string());
+    }
+
+    let text = scene.serialize(&registry).map_err(|e| e.to_string())?;
+    let mut tree = SceneTree::parse(&text).map_err(|e| e.to_string())?;
+    tree.set_version(version);
+    expand_unknown_components(&mut tree);
+    if config.canonical {
+        canonicalize_scene(&mut tree, &registry);
+    }
+    Ok(tree.to_pretty_string().into_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::prelude::*;
+    use crate::test_dir::TempDir;
 
     #[test]
     fn flaky_save_to_file() {
         let file = "test.ron";
         let save_config = SaveConfig {
             path: Some(EditorPrefabPath::File(String::from(file))),
+            // No backups left next to the crate sources
+            backups: 0,
+            ..default()
         };
         let mut app = App::new();
         app.add_plugins((
@@ -252,12 +802,14 @@ mod tests {
 
         assert!(contents.contains("my_name"));
         assert!(contents.contains("space_shared::PrefabMarker"));
+        assert!(contents.contains("SceneVersion"));
     }
 
     #[test]
     fn save_to_memory() {
         let save_config = SaveConfig {
             path: Some(EditorPrefabPath::MemoryCache),
+            ..default()
         };
         let mut app = App::new();
         app.add_plugins(MinimalPlugins);
@@ -348,6 +900,7 @@ mod tests {
     fn attempts_to_serialize_empty_scene() {
         let save_config = SaveConfig {
             path: Some(EditorPrefabPath::MemoryCache),
+            ..default()
         };
         let mut app = App::new();
         app.add_plugins((
@@ -374,6 +927,444 @@ mod tests {
         iter.for_each(|e| assert_eq!(e.text, "Saving empty scene"));
     }
 
+    fn canonical_app(file: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::state::app::StatesPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            EditorRegistryPlugin {},
+            SaveResourcesPrefabPlugin {},
+            crate::format::SceneFormatPlugin,
+        ))
+        .insert_resource(SaveConfig {
+            path: Some(EditorPrefabPath::File(file.to_string())),
+            canonical: true,
+            ..default()
+        })
+        .init_resource::<PrefabMemoryCache>()
+        .register_type::<crate::ext::uuid::Uuid>()
+        .editor_registry::<Name>()
+        .editor_registry::<Transform>()
+        .editor_registry::<PrefabMarker>()
+        .editor_silent_registry::<PrefabGuid>();
+        app
+    }
+
+    fn save_and_read(app: &mut App, file: &str) -> String {
+        let _ = fs::remove_file(file);
+        serialize_scene(app.world_mut());
+        for _ in 0..250 {
+            if let Ok(text) = fs::read_to_string(file) {
+                if SceneTree::parse(&text).is_ok() {
+                    return text;
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        panic!("Scene was not saved to {file}");
+    }
+
+    fn load_to_new_app(file: &str, text: &str) -> App {
+        let mut app = canonical_app(file);
+        // shift entity ids, so loaded entities get other ids than saved ones
+        for _ in 0..7 {
+            app.world_mut().spawn_empty();
+        }
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_str(text).unwrap();
+        scene
+            .write_to_world(app.world_mut(), &mut Default::default())
+            .unwrap();
+        app
+    }
+
+    #[test]
+    fn canonical_save_is_stable_after_reload() {
+        let dir = TempDir::new("canonical");
+        let file = dir.join("scene.scn.ron").to_string_lossy().to_string();
+
+        let mut app = canonical_app(&file);
+        for i in 0..5 {
+            let child = app
+                .world_mut()
+                .spawn((
+                    PrefabMarker,
+                    PrefabGuid::default(),
+                    Name::new(format!("child {i}")),
+                    Transform::from_xyz(i as f32 * 0.1, -0.0, 1.5),
+                ))
+                .id();
+            app.world_mut().spawn((
+                PrefabMarker,
+                PrefabGuid::default(),
+                Name::new(format!("root {i}")),
+                ChildrenPrefab(vec![child]),
+            ));
+        }
+
+        let first = save_and_read(&mut app, &file);
+        let mut app = load_to_new_app(&file, &first);
+        let second = save_and_read(&mut app, &file);
+        let mut app = load_to_new_app(&file, &second);
+        let third = save_and_read(&mut app, &file);
+
+        assert_eq!(first, second);
+        assert_eq!(second, third);
+    }
+
+    #[test]
+    fn binary_save_loads_back() {
+        let dir = TempDir::new("binary");
+        let file = dir.join("scene.scn.ron").to_string_lossy().to_string();
+        let bin_file = dir.join("scene.scn.bin");
+
+        let mut app = canonical_app(&file);
+        app.world_mut().resource_mut::<SaveConfig>().format =
+            SceneFileFormat::Binary { compressed: true };
+        app.world_mut().spawn((
+            PrefabMarker,
+            Name::new("binary"),
+            Transform::from_xyz(1.0, 2.0, 3.0),
+        ));
+
+        serialize_scene(app.world_mut());
+        let completed = wait_save_completed(&mut app);
+        assert!(completed.result.is_ok());
+        assert!(matches!(
+            &completed.path,
+            Some(EditorPrefabPath::File(path)) if *path == bin_file.to_string_lossy()
+        ));
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_file(&bin_file);
+
+        let scene = scene.expect("Binary scene was not saved");
+        let transform = scene.entities[0]
+            .components
+            .iter()
+            .find(|c| {
+                c.get_represented_type_info()
+                    .is_some_and(|info| info.type_path() == Transform::type_path())
+            })
+            .and_then(|c| Transform::from_reflect(c.as_ref()))
+            .unwrap();
+        assert_eq!(transform.translation, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    fn wait_save_completed(app: &mut App) -> SaveCompleted {
+        for _ in 0..250 {
+            poll_pending_saves(app.world_mut());
+            let events = app.world().resource::<Events<SaveCompleted>>();
+            if let Some(event) = events.get_reader().read(events).last() {
+                return event.clone();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        panic!("Save was not completed");
+    }
+
+    #[test]
+    fn save_completed_reports_result() {
+        let dir = TempDir::new("completed");
+        let file = dir.join("scene.scn.ron").to_string_lossy().to_string();
+
+        let mut app = canonical_app(&file);
+        app.world_mut().spawn((PrefabMarker, Name::new("saved")));
+        serialize_scene(app.world_mut());
+        let completed = wait_save_completed(&mut app);
+        assert!(fs::read_to_string(&file).unwrap().contains("saved"));
+        assert_eq!(completed.entities, 1);
+        assert_eq!(completed.result, Ok(()));
+        assert!(matches!(completed.path, Some(EditorPrefabPath::File(path)) if path == file));
+
+        let missing = dir
+            .join("missing/scene.scn.ron")
+            .to_string_lossy()
+            .to_string();
+        app.world_mut().resource_mut::<SaveConfig>().path = Some(EditorPrefabPath::File(missing));
+        app.world_mut()
+            .resource_mut::<Events<SaveCompleted>>()
+            .clear();
+        serialize_scene(app.world_mut());
+        let completed = wait_save_completed(&mut app);
+        assert!(matches!(completed.result, Err(SceneIoError::Io(_))));
+
+        app.world_mut().remove_resource::<EditorRegistry>();
+        serialize_scene(app.world_mut());
+        let completed = wait_save_completed(&mut app);
+        assert_eq!(
+            completed.result,
+            Err(SceneIoError::MissingRegistry("Editor Registry"))
+        );
+    }
+
+    #[derive(Reflect, Clone)]
+    #[reflect_value]
+    struct Opaque;
+
+    #[derive(Component, Reflect, Clone)]
+    #[reflect(Component)]
+    struct Holder {
+        name: String,
+        value: Opaque,
+    }
+
+    #[test]
+    fn finds_unserializable_type() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Holder>();
+        registry.register::<Name>();
+        let scene = |component: Box<dyn Reflect>| DynamicScene {
+            resources: vec![],
+            entities: vec![bevy::scene::DynamicEntity {
+                entity: Entity::from_raw(0),
+                components: vec![component],
+            }],
+        };
+
+        let holder = Holder {
+            name: "no registration found".to_string(),
+            value: Opaque,
+        };
+        assert_eq!(
+            unserializable_type(&scene(Box::new(holder)), &registry),
+            Some(Opaque::type_path().to_string())
+        );
+        assert_eq!(
+            unserializable_type(&scene(Box::new(Name::new("not registered"))), &registry),
+            None
+        );
+    }
+
+    #[test]
+    fn atomic_write_rotates_backups() {
+        let dir = TempDir::new("backups");
+        let file = dir.join("scene.scn.ron");
+
+        for version in ["v1", "v2", "v3", "v4"] {
+            write_scene_file(&file, version.as_bytes(), 2).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v4");
+        assert_eq!(fs::read_to_string(backup_path(&file, 1)).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(backup_path(&file, 2)).unwrap(), "v2");
+        assert!(!backup_path(&file, 3).exists());
+        assert!(!dir.join("scene.scn.ron.tmp").exists());
+
+        // Failed write keeps the previous file and backups untouched
+        assert!(write_scene_file(dir.join("missing/scene.scn.ron"), b"v5", 2).is_err());
+        fs::write(&file, "broken").unwrap();
+        assert_eq!(newest_backup(&file), Some(backup_path(&file, 1)));
+        assert_eq!(restore_newest_backup(&file).unwrap(), backup_path(&file, 1));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v3");
+        assert_eq!(
+            fs::read_to_string(dir.join("scene.scn.ron.broken")).unwrap(),
+            "broken"
+        );
+    }
+
+    #[test]
+    fn recovery_files_newer_than_scene() {
+        assert_eq!(
+            recovery_asset_path(Some("./assets/levels/a.scn.ron")),
+            ".recovery/levels/a.scn.ron"
+        );
+        assert_eq!(
+            recovery_asset_path(Some("../outside/b.scn.ron")),
+            ".recovery/outside/b.scn.ron"
+        );
+        assert_eq!(recovery_asset_path(None), ".recovery/untitled.scn.ron");
+
+        let dir = TempDir::new("recovery");
+        let recovery = dir.join(RECOVERY_DIR);
+        fs::create_dir_all(recovery.join("levels")).unwrap();
+        fs::create_dir_all(dir.join("levels")).unwrap();
+        fs::write(dir.join("old.scn.ron"), "scene").unwrap();
+        fs::write(dir.join("levels/new.scn.ron"), "scene").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(recovery.join("old.scn.ron"), "autosave").unwrap();
+        fs::write(recovery.join("levels/new.scn.ron"), "autosave").unwrap();
+        fs::write(recovery.join(UNTITLED_SCENE), "autosave").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(dir.join("levels/new.scn.ron"), "saved later").unwrap();
+
+        let found = find_recovery_files(&dir);
+        let scene = dir.join("old.scn.ron").to_string_lossy().replace('\\', "/");
+        assert_eq!(
+            found,
+            vec![
+                RecoveryFile {
+                    scene: Some(scene),
+                    asset_path: ".recovery/old.scn.ron".to_string(),
+                },
+                RecoveryFile {
+                    scene: None,
+                    asset_path: ".recovery/untitled.scn.ron".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Health {
+        value: f32,
+        #[reflect(skip_serializing)]
+        regen_timer: f32,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct RuntimeState(u32);
+
+    fn filtered_app(file: &str) -> App {
+        let mut app = canonical_app(file);
+        app.editor_registry::<Health>()
+            .editor_registry::<NoSave>()
+            .editor_transient_registry::<RuntimeState>();
+        app
+    }
+
+    #[test]
+    fn excluded_entities_types_and_fields_are_not_saved() {
+        let dir = TempDir::new("no_save");
+        let file = dir.join("scene.scn.ron").to_string_lossy().to_string();
+
+        let mut app = filtered_app(&file);
+        let world = app.world_mut();
+        let kept = world
+            .spawn((
+                PrefabMarker,
+                Name::new("kept"),
+                Health {
+                    value: 5.,
+                    regen_timer: 3.,
+                },
+                RuntimeState(7),
+            ))
+            .id();
+        world
+            .spawn((PrefabMarker, Name::new("kept_child")))
+            .set_parent(kept);
+        world
+            .spawn((PrefabMarker, Name::new("hidden_child"), NoSave::default()))
+            .set_parent(kept);
+        let tree = world
+            .spawn((
+                PrefabMarker,
+                Name::new("hidden_tree"),
+                NoSave { recursive: true },
+            ))
+            .id();
+        world
+            .spawn((PrefabMarker, Name::new("hidden_leaf")))
+            .set_parent(tree);
+        let flat = world
+            .spawn((PrefabMarker, Name::new("flat"), NoSave::default()))
+            .id();
+        world
+            .spawn((PrefabMarker, Name::new("orphan")))
+            .set_parent(flat);
+        world.run_system_once(prepare_children);
+
+        let text = save_and_read(&mut app, &file);
+        for name in ["kept", "kept_child", "orphan"] {
+            assert!(text.contains(&format!("\"{name}\"")), "{name} is not saved");
+        }
+        for name in ["hidden_child", "hidden_tree", "hidden_leaf", "flat"] {
+            assert!(!text.contains(&format!("\"{name}\"")), "{name} is saved");
+        }
+        assert!(!text.contains("RuntimeState"));
+        assert!(!text.contains("regen_timer"));
+
+        let mut app = filtered_app(&file);
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_str(&text).unwrap();
+        scene
+            .write_to_world(app.world_mut(), &mut Default::default())
+            .unwrap();
+        let mut query = app
+            .world_mut()
+            .query::<(&Name, &Health, Option<&ChildrenPrefab>)>();
+        let (name, health, children) = query.single(app.world());
+        assert_eq!(name.as_str(), "kept");
+        assert_eq!(health.value, 5.);
+        assert_eq!(health.regen_timer, 0.);
+        assert_eq!(children.unwrap().0.len(), 1);
+    }
+
+    #[derive(Resource, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Resource, Default)]
+    struct Gravity(f32);
+
+    #[test]
+    fn registered_scene_resources_are_saved_and_loaded() {
+        let dir = TempDir::new("resource");
+        let file = dir.join("scene.scn.ron").to_string_lossy().to_string();
+
+        let mut app = canonical_app(&file);
+        app.editor_resource_registry::<Gravity>();
+        app.world_mut().insert_resource(Gravity(-3.5));
+        app.world_mut().spawn((PrefabMarker, Name::new("ground")));
+
+        let text = save_and_read(&mut app, &file);
+        assert!(text.contains("Gravity"), "{text}");
+
+        let mut app = canonical_app(&file);
+        app.editor_resource_registry::<Gravity>();
+        assert_eq!(*app.world().resource::<Gravity>(), Gravity(0.));
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_str(&text).unwrap();
+        scene
+            .write_to_world(app.world_mut(), &mut Default::default())
+            .unwrap();
+        assert_eq!(*app.world().resource::<Gravity>(), Gravity(-3.5));
+    }
+
+    #[test]
+    fn split_scene_is_saved_per_root_entity_and_loaded_back() {
+        let dir = TempDir::new("split_save");
+        let file = dir.join("level.scn.ron").to_string_lossy().to_string();
+
+        let mut app = canonical_app(&file);
+        app.world_mut().resource_mut::<SaveConfig>().format = SceneFileFormat::Split;
+        let world = app.world_mut();
+        let room = world.spawn((PrefabMarker, Name::new("room"))).id();
+        world
+            .spawn((PrefabMarker, Name::new("lamp")))
+            .set_parent(room);
+        world.spawn((PrefabMarker, Name::new("tree")));
+        world.run_system_once(prepare_children);
+
+        save_and_read(&mut app, &file);
+        let parts = fs::read_dir(crate::format::split::parts_dir(&file))
+            .unwrap()
+            .count();
+        let text = crate::format::split::read_scene_text(&file).unwrap();
+        assert_eq!(parts, 2);
+
+        let mut app = canonical_app(&file);
+        let loader = crate::format::loader::PrefabSceneLoader::from_world(app.world_mut());
+        let scene = loader.load_from_str(&text).unwrap();
+        scene
+            .write_to_world(app.world_mut(), &mut Default::default())
+            .unwrap();
+        let mut query = app.world_mut().query::<(&Name, Option<&ChildrenPrefab>)>();
+        let mut names = query
+            .iter(app.world())
+            .map(|(name, children)| (name.to_string(), children.map_or(0, |c| c.0.len())))
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                ("lamp".to_string(), 0),
+                ("room".to_string(), 1),
+                ("tree".to_string(), 0)
+            ]
+        );
+    }
+
     #[test]
     fn prepared_children_ignores_scene_auto_child_component() {
         let mut app = App::new();
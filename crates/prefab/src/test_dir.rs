@@ -0,0 +1,39 @@
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// Empty directory in the system temp folder, removed with its content when dropped,
+/// so files of a failed test do not stay behind
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Directory unique for the test `name` and the test process
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("space_prefab_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    asset_paths::{scene_asset_references, CodeAssetPaths},
+    format::loader::PrefabSceneLoader,
+    import_settings::{meta_path, META_EXTENSION},
+    validate::scene_files,
+};
+
+/// Folder next to the asset folder where unused assets are moved to
+pub const QUARANTINE_DIR: &str = "unused_assets";
+
+/// Asset files used by scenes of the asset folder and by game code
+#[derive(Clone, Debug, Default)]
+pub struct UsedAssets {
+    files: HashSet<String>,
+    folders: Vec<String>,
+    /// Number of read scene files
+    pub scenes: usize,
+    /// Scene files that could not be loaded, assets used only by them are reported as unused
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl UsedAssets {
+    /// Whether the asset file at `path` (relative to the asset folder) is used.
+    /// Scene files, their parts and backups are always used, as they are opened by the editor or the game.
+    pub fn is_used(&self, path: &str) -> bool {
+        path.split('/').any(|part| part.contains(".scn"))
+            || self.files.contains(path)
+            || self.folders.iter().any(|folder| {
+                path.strip_prefix(folder.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+
+    /// Asset files from `files` that are not used.
+    /// Import settings files are skipped, they are moved together with their assets.
+    pub fn unused<'a>(&self, files: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        files
+            .into_iter()
+            .filter(|path| !path.ends_with(&format!(".{META_EXTENSION}")) && !self.is_used(path))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Collect asset paths referenced by every scene file in `asset_root`, read with types registered
+/// in the `world`, and asset paths from [`CodeAssetPaths`]. Files of used `.gltf` models are added too.
+pub fn used_assets(world: &mut World, asset_root: impl AsRef<Path>) -> io::Result<UsedAssets> {
+    let asset_root = asset_root.as_ref();
+    let loader = PrefabSceneLoader::from_world(world);
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let mut used = UsedAssets::default();
+    for file in scene_files(asset_root)? {
+        let scene = match loader.load_from_file(&file) {
+            Ok(scene) => scene,
+            Err(e) => {
+                used.errors.push((file, e.to_string()));
+                continue;
+            }
+        };
+        used.scenes += 1;
+        for reference in scene_asset_references(&scene, &registry) {
+            let path = reference.path.split('#').next().unwrap_or_default();
+            used.files.insert(path.to_string());
+        }
+    }
+    if let Some(code_paths) = world.get_resource::<CodeAssetPaths>() {
+        for path in code_paths.paths.iter() {
+            used.files.insert(path.clone());
+            used.folders.push(path.clone());
+        }
+    }
+
+    let models = used
+        .files
+        .iter()
+        .filter(|path| path.to_lowercase().ends_with(".gltf"))
+        .cloned()
+        .collect::<Vec<_>>();
+    for model in models {
+        used.files.extend(gltf_dependencies(asset_root, &model));
+    }
+    Ok(used)
+}
+
+/// Buffers and images stored next to a `.gltf` file, referenced by relative uris
+fn gltf_dependencies(asset_root: &Path, model: &str) -> Vec<String> {
+    let Ok(text) = fs::read_to_string(asset_root.join(model)) else {
+        return vec![];
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return vec![];
+    };
+    let dir = model.rsplit_once('/').map_or("", |(dir, _)| dir);
+    ["buffers", "images"]
+        .iter()
+        .filter_map(|key| json.get(key)?.as_array())
+        .flatten()
+        .filter_map(|item| item.get("uri")?.as_str())
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(|uri| {
+            let uri = uri.trim_start_matches("./");
+            if dir.is_empty() {
+                uri.to_string()
+            } else {
+                format!("{dir}/{uri}")
+            }
+        })
+        .collect()
+}
+
+/// Move assets at `paths` (relative to `asset_root`) to the same relative paths in `quarantine`,
+/// so they can be restored if they turn out to be used. Returns the number of moved files.
+pub fn quarantine_assets(
+    asset_root: impl AsRef<Path>,
+    quarantine: impl AsRef<Path>,
+    paths: &[String],
+) -> io::Result<usize> {
+    let mut moved = 0;
+    for path in paths {
+        let source = asset_root.as_ref().join(path);
+        if !source.is_file() {
+            continue;
+        }
+        let target = quarantine.as_ref().join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&source, &target)?;
+        let meta = meta_path(asset_root.as_ref(), path);
+        if meta.is_file() {
+            fs::rename(&meta, meta_path(quarantine.as_ref(), path))?;
+        }
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::{
+        asset_paths::{asset_files, AssetPathsExt},
+        component::SpriteTexture,
+    };
+
+    #[test]
+    fn reports_and_quarantines_unused_assets() {
+        let dir = TempDir::new("unused");
+        let assets = dir.join("assets");
+        for folder in ["textures", "models", "fonts", "scenes"] {
+            fs::create_dir_all(assets.join(folder)).unwrap();
+        }
+        for file in [
+            "textures/used.png",
+            "textures/unused.png",
+            "models/tree.bin",
+            "models/bark.png",
+            "fonts/main.ttf",
+            "textures/unused.png.meta",
+        ] {
+            fs::write(assets.join(file), "data").unwrap();
+        }
+        fs::write(
+            assets.join("models/tree.gltf"),
+            r#"{"buffers": [{"uri": "tree.bin"}], "images": [{"uri": "./bark.png"}]}"#,
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.register_asset_paths::<SpriteTexture>(&["texture"])
+            .register_code_asset_paths(&["fonts/", "models/tree.gltf"]);
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.spawn(SpriteTexture {
+            texture: "textures/used.png".to_string(),
+        });
+        let scene = {
+            let registry = world.resource::<AppTypeRegistry>().read();
+            DynamicScene::from_world(&world)
+                .serialize(&registry)
+                .unwrap()
+        };
+        fs::write(assets.join("scenes/level.scn.ron"), scene).unwrap();
+
+        let mut world = World::new();
+        world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        world.insert_resource(app.world().resource::<CodeAssetPaths>().clone());
+        let used = used_assets(&mut world, &assets).unwrap();
+        assert!(used.errors.is_empty(), "{:?}", used.errors);
+        assert_eq!(used.scenes, 1);
+        let files = asset_files(&assets);
+        let unused = used.unused(files.iter().map(String::as_str));
+        assert_eq!(unused, vec!["textures/unused.png".to_string()]);
+
+        let quarantine = dir.join(QUARANTINE_DIR);
+        assert_eq!(quarantine_assets(&assets, &quarantine, &unused).unwrap(), 1);
+        assert!(!assets.join("textures/unused.png").exists());
+        assert!(quarantine.join("textures/unused.png").is_file());
+        assert!(quarantine.join("textures/unused.png.meta").is_file());
+    }
+}
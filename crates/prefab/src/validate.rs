@@ -0,0 +1,468 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    reflect::{ReflectRef, TypeRegistry},
+    scene::DynamicEntity,
+    utils::{HashMap, HashSet},
+};
+use serde::Serialize;
+
+use crate::{
+    asset_paths::{asset_exists, scene_asset_references},
+    component::PrefabGuid,
+    diff::component,
+    format::{
+        binary::{is_binary_scene, BINARY_SCENE_EXTENSION},
+        loader::PrefabSceneLoader,
+        migration::SceneMigrations,
+        ron_tree::RonValue,
+        split::{read_scene_text, PARTS_DIR_EXTENSION},
+        SceneTree,
+    },
+};
+
+/// Kind of problem found by [`validate_scenes`]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    Read,
+    Parse,
+    UnregisteredType,
+    Deserialize,
+    Spawn,
+    DanglingReference,
+    MissingAsset,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Read => "read",
+            Self::Parse => "parse",
+            Self::UnregisteredType => "unregistered_type",
+            Self::Deserialize => "deserialize",
+            Self::Spawn => "spawn",
+            Self::DanglingReference => "dangling_reference",
+            Self::MissingAsset => "missing_asset",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Problem found in a scene file
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SceneIssue {
+    pub file: String,
+    pub kind: IssueKind,
+    /// Name or guid of the entity, `None` for problems of the whole file or its resources
+    pub entity: Option<String>,
+    /// Type path of the component or resource
+    pub component: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for SceneIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}:", self.file, self.kind)?;
+        if let Some(entity) = &self.entity {
+            write!(f, " {entity}:")?;
+        }
+        if let Some(component) = &self.component {
+            write!(f, " {component}:")?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// Result of [`validate_scenes`]
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ValidationReport {
+    /// Number of checked scene files
+    pub scenes: usize,
+    pub issues: Vec<SceneIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Report as pretty printed JSON, for CI tools
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Whether the file name has one of the scene extensions
+pub fn is_scene_file(name: &str) -> bool {
+    name.ends_with(".scn.ron")
+        || name.ends_with(".scn")
+        || name.ends_with(&format!(".{BINARY_SCENE_EXTENSION}"))
+}
+
+/// Scene files in `dir` and its subfolders, in path order.
+/// Parts folders of split scenes, checked with their index file, and hidden folders like `.recovery` are skipped.
+pub fn scene_files(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.as_ref().to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if path.is_dir() {
+                if !name.starts_with('.') && !name.ends_with(&format!(".{PARTS_DIR_EXTENSION}")) {
+                    dirs.push(path);
+                }
+            } else if is_scene_file(&name) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Load every scene file in `dir` with types registered in the `world` and check it.
+/// Asset paths are looked up in `asset_root`.
+pub fn validate_scenes(
+    world: &mut World,
+    dir: impl AsRef<Path>,
+    asset_root: impl AsRef<Path>,
+) -> io::Result<ValidationReport> {
+    let files = scene_files(dir)?;
+    let mut report = ValidationReport {
+        scenes: files.len(),
+        issues: vec![],
+    };
+    for file in files {
+        report
+            .issues
+            .extend(validate_scene_file(world, &file, asset_root.as_ref()));
+    }
+    Ok(report)
+}
+
+/// Check one scene file: it must parse, contain only registered types, deserialize and spawn,
+/// reference only its own entities and existing asset files
+pub fn validate_scene_file(
+    world: &mut World,
+    path: impl AsRef<Path>,
+    asset_root: impl AsRef<Path>,
+) -> Vec<SceneIssue> {
+    let path = path.as_ref();
+    let file = path.display().to_string();
+    let issue = |kind, entity, component, message| SceneIssue {
+        file: file.clone(),
+        kind,
+        entity,
+        component,
+        message,
+    };
+
+    let loader = PrefabSceneLoader::from_world(world);
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => return vec![issue(IssueKind::Read, None, None, e.to_string())],
+    };
+    let mut issues = vec![];
+    if !is_binary_scene(&bytes) {
+        let text = match read_scene_text(path) {
+            Ok(text) => text,
+            Err(e) => return vec![issue(IssueKind::Read, None, None, e.to_string())],
+        };
+        let mut tree = match SceneTree::parse(&text) {
+            Ok(tree) => tree,
+            Err(e) => return vec![issue(IssueKind::Parse, None, None, e.to_string())],
+        };
+        world
+            .get_resource_or_insert_with(SceneMigrations::default)
+            .migrate(&mut tree);
+        for (entity, type_path) in unregistered_types(&tree, &registry) {
+            issues.push(issue(
+                IssueKind::UnregisteredType,
+                entity,
+                Some(type_path),
+                "type is not registered".to_string(),
+            ));
+        }
+    }
+
+    let scene = match loader.load_from_file(path) {
+        Ok(scene) => scene,
+        Err(e) => {
+            issues.push(issue(IssueKind::Deserialize, None, None, e.to_string()));
+            return issues;
+        }
+    };
+
+    let mut spawned = World::new();
+    spawned.insert_resource(world.resource::<AppTypeRegistry>().clone());
+    if let Err(e) = scene.write_to_world(&mut spawned, &mut EntityHashMap::default()) {
+        issues.push(issue(IssueKind::Spawn, None, None, e.to_string()));
+    }
+
+    let ids = scene
+        .entities
+        .iter()
+        .map(|entity| entity.entity)
+        .collect::<HashSet<_>>();
+    let mut dangling = |entity: Option<String>, value: &dyn Reflect| {
+        let mut references = vec![];
+        entity_references(value, &mut references);
+        for reference in references {
+            if reference != Entity::PLACEHOLDER && !ids.contains(&reference) {
+                issues.push(issue(
+                    IssueKind::DanglingReference,
+                    entity.clone(),
+                    value
+                        .get_represented_type_info()
+                        .map(|info| info.type_path().to_string()),
+                    format!("references entity {reference} that is not in the scene"),
+                ));
+            }
+        }
+    };
+    for resource in scene.resources.iter() {
+        dangling(None, resource.as_ref());
+    }
+    for entity in scene.entities.iter() {
+        for value in entity.components.iter() {
+            dangling(Some(entity_label(entity)), value.as_ref());
+        }
+    }
+
+    let asset_root = asset_root.as_ref();
+    let labels = scene
+        .entities
+        .iter()
+        .map(|entity| (entity.entity, entity_label(entity)))
+        .collect::<HashMap<_, _>>();
+    for reference in scene_asset_references(&scene, &registry) {
+        if !asset_exists(asset_root, &reference.path) {
+            issues.push(issue(
+                IssueKind::MissingAsset,
+                labels.get(&reference.entity).cloned(),
+                Some(reference.type_path),
+                format!(
+                    "asset {} in field {} is not found in {}",
+                    reference.path,
+                    reference.field,
+                    asset_root.display()
+                ),
+            ));
+        }
+    }
+    issues
+}
+
+/// `(entity, type path)` of components and resources with types unknown to the registry
+fn unregistered_types(tree: &SceneTree, registry: &TypeRegistry) -> Vec<(Option<String>, String)> {
+    let is_unknown = |key: &RonValue| {
+        key.as_str()
+            .filter(|type_path| registry.get_with_type_path(type_path).is_none())
+    };
+    let mut unknown = vec![];
+    let resources = tree.0.field("resources").and_then(RonValue::entries);
+    for (key, _) in resources.into_iter().flatten() {
+        if let Some(type_path) = is_unknown(key) {
+            unknown.push((None, type_path));
+        }
+    }
+    let entities = tree.0.field("entities").and_then(RonValue::entries);
+    for (id, entity) in entities.into_iter().flatten() {
+        let components = entity.field("components").and_then(RonValue::entries);
+        for (key, _) in components.into_iter().flatten() {
+            if let Some(type_path) = is_unknown(key) {
+                unknown.push((Some(format!("entity {id}")), type_path));
+            }
+        }
+    }
+    unknown
+}
+
+/// Entity name, guid or id to show in the report
+fn entity_label(entity: &DynamicEntity) -> String {
+    component::<Name>(entity).map_or_else(
+        || {
+            component::<PrefabGuid>(entity).map_or_else(
+                || format!("entity {}", entity.entity),
+                |guid| format!("entity {}", guid.0),
+            )
+        },
+        |name| name.to_string(),
+    )
+}
+
+/// Every [`Entity`] stored in the reflected value
+fn entity_references(value: &dyn Reflect, out: &mut Vec<Entity>) {
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            for field in value.iter_fields() {
+                entity_references(field, out);
+            }
+        }
+        ReflectRef::TupleStruct(value) => {
+            for field in value.iter_fields() {
+                entity_references(field, out);
+            }
+        }
+        ReflectRef::Tuple(value) => {
+            for field in value.iter_fields() {
+                entity_references(field, out);
+            }
+        }
+        ReflectRef::List(value) => {
+            for item in value.iter() {
+                entity_references(item, out);
+            }
+        }
+        ReflectRef::Array(value) => {
+            for item in value.iter() {
+                entity_references(item, out);
+            }
+        }
+        ReflectRef::Map(value) => {
+            for (key, item) in value.iter() {
+                entity_references(key, out);
+                entity_references(item, out);
+            }
+        }
+        ReflectRef::Enum(value) => {
+            for field in value.iter_fields() {
+                entity_references(field.value(), out);
+            }
+        }
+        ReflectRef::Value(value) => {
+            if let Some(entity) = value.downcast_ref::<Entity>() {
+                out.push(*entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::{
+        asset_paths::ReflectAssetPaths,
+        component::SpriteTexture,
+        format::{migration::SceneVersion, unknown::UnknownComponents},
+        save::ChildrenPrefab,
+    };
+
+    fn world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Name>();
+            registry.register::<ChildrenPrefab>();
+            registry.register::<SpriteTexture>();
+            registry.register::<SceneVersion>();
+            registry.register::<UnknownComponents>();
+            registry
+                .get_mut(std::any::TypeId::of::<SpriteTexture>())
+                .unwrap()
+                .insert(ReflectAssetPaths {
+                    fields: vec!["texture"],
+                });
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    fn scene_text(world: &World) -> String {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        DynamicSceneBuilder::from_world(world)
+            .extract_entities(world.iter_entities().map(|entity| entity.id()))
+            .build()
+            .serialize(&registry)
+            .unwrap()
+    }
+
+    #[test]
+    fn reports_broken_scenes() {
+        let dir = TempDir::new("validate");
+        let scenes = dir.join("scenes");
+        fs::create_dir_all(scenes.join("nested")).unwrap();
+        fs::write(dir.join("hero.png"), "png").unwrap();
+
+        let mut valid = world();
+        valid.spawn((
+            Name::new("hero"),
+            SpriteTexture {
+                texture: "hero.png".to_string(),
+            },
+        ));
+        fs::write(scenes.join("valid.scn.ron"), scene_text(&valid)).unwrap();
+
+        let mut broken = world();
+        let missing = broken.spawn_empty().id();
+        broken.spawn((
+            Name::new("villain"),
+            ChildrenPrefab(vec![missing]),
+            SpriteTexture {
+                texture: "villain.png".to_string(),
+            },
+        ));
+        broken.despawn(missing);
+        let text = scene_text(&broken).replace(
+            "\"bevy_core::name::Name\"",
+            "\"game::Health\": (value: 5),\n        \"bevy_core::name::Name\"",
+        );
+        fs::write(scenes.join("nested/broken.scn.ron"), text).unwrap();
+        fs::write(scenes.join("nested/typo.scn.ron"), "(resources: {").unwrap();
+
+        let report = validate_scenes(&mut world(), &scenes, &dir).unwrap();
+
+        assert_eq!(report.scenes, 3);
+        let kinds = report
+            .issues
+            .iter()
+            .map(|issue| issue.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                IssueKind::UnregisteredType,
+                IssueKind::DanglingReference,
+                IssueKind::MissingAsset,
+                IssueKind::Parse,
+            ],
+            "{report:#?}"
+        );
+        assert_eq!(report.issues[0].component.as_deref(), Some("game::Health"));
+        assert_eq!(report.issues[2].entity.as_deref(), Some("villain"));
+        assert!(report
+            .issues
+            .iter()
+            .all(|issue| !issue.file.ends_with("valid.scn.ron")));
+        assert!(report.to_json().contains("\"kind\": \"missing_asset\""));
+    }
+
+    #[test]
+    fn skips_hidden_and_parts_folders() {
+        let dir = TempDir::new("validate_hidden");
+        for folder in [".recovery/levels", "levels/castle.parts", "levels"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            fs::write(dir.join(folder).join("castle.scn.ron"), "(resources: {").unwrap();
+        }
+
+        assert_eq!(
+            scene_files(&dir).unwrap(),
+            vec![dir.join("levels/castle.scn.ron")]
+        );
+    }
+}
@@ -7,8 +7,8 @@ pub mod ext {
 
 pub mod prelude {
     pub use crate::{
-        EditorCameraMarker, EditorEvent, EditorPrefabPath, EditorSet, EditorState, PrefabMarker,
-        PrefabMemoryCache, SelectParent,
+        EditorCameraMarker, EditorEvent, EditorPrefabPath, EditorSet, EditorState, MergeScene,
+        PrefabMarker, PrefabMemoryCache, SelectParent,
     };
 }
 
@@ -66,10 +66,25 @@ pub enum EditorPrefabPath {
 pub enum EditorEvent {
     Load(EditorPrefabPath),
     Save(EditorPrefabPath),
+    /// Remove all scene entities and start a new unsaved scene
+    New,
+    /// Append scene file to the open scene as one undoable change
+    Merge(MergeScene),
     LoadGltfAsPrefab(String),
     StartGame,
 }
 
+/// Scene file appended to the open scene by [`EditorEvent::Merge`]
+#[derive(Clone, Debug, Default)]
+pub struct MergeScene {
+    /// Asset path of the scene file
+    pub path: String,
+    /// Entity which becomes the parent of merged root entities
+    pub parent: Option<Entity>,
+    /// Transform applied on top of merged root entities
+    pub offset: Transform,
+}
+
 /// Component that makes the parent become selected when this mesh is.
 /// Stores the parent entity for reading.
 /// Must be not be paired with an entity that has PrefabMarker.
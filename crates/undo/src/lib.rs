@@ -604,6 +604,13 @@ pub struct ManyChanges {
     changes: Vec<Arc<dyn EditorChange + Send + Sync>>,
 }
 
+impl ManyChanges {
+    /// Group changes into one undo step. Changes are reverted in the given order
+    pub fn new(changes: Vec<Arc<dyn EditorChange + Send + Sync>>) -> Self {
+        Self { changes }
+    }
+}
+
 impl EditorChange for ManyChanges {
     fn revert(
         &self,
@@ -313,6 +313,28 @@ fn undo_ignore_ticks() {
     assert_eq!(ignore_storage.storage.len(), 1)
 }
 
+#[test]
+fn many_changes_are_one_undo_step() {
+    let mut app = configure_app();
+    app.update();
+
+    let first = app.world_mut().spawn_empty().id();
+    let second = app.world_mut().spawn_empty().id();
+    app.world_mut().send_event(NewChange {
+        change: Arc::new(ManyChanges::new(vec![
+            Arc::new(AddedEntity { entity: first }),
+            Arc::new(AddedEntity { entity: second }),
+        ])),
+    });
+    repeat_update(&mut app, 2);
+    assert_eq!(app.world().resource::<ChangeChain>().changes.len(), 1);
+
+    app.world_mut().send_event(UndoRedo::Undo);
+    repeat_update(&mut app, 2);
+    assert!(app.world().get_entity(first).is_none());
+    assert!(app.world().get_entity(second).is_none());
+}
+
 #[derive(Component, Default)]
 pub struct TestSync;
 
@@ -1,25 +1,34 @@
 use bevy::{
+    asset::AssetMetaCheck,
     prelude::*,
     window::{WindowMode, WindowResolution},
 };
 use game_lib::GamePlugin;
-use space_prefab::prelude::{PrefabBundle, PrefabPlugin};
+use space_prefab::prelude::{PrefabBundle, PrefabPlugin, StripEditorOnlyPlugin};
 
 fn main() {
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            resizable: true,
-            focused: true,
-            title: "Your Game".into(),
-            resolution: WindowResolution::new(1600., 900.),
-            visible: true,
-            mode: WindowMode::Fullscreen,
-            ..default()
-        }),
-        ..default()
-    }))
-    .add_plugins((PrefabPlugin, GamePlugin))
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    resizable: true,
+                    focused: true,
+                    title: "Your Game".into(),
+                    resolution: WindowResolution::new(1600., 900.),
+                    visible: true,
+                    mode: WindowMode::Fullscreen,
+                    ..default()
+                }),
+                ..default()
+            })
+            // Import settings are stored in `.meta` files next to the assets
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Always,
+                ..default()
+            }),
+    )
+    .add_plugins((PrefabPlugin, StripEditorOnlyPlugin, GamePlugin))
     .add_systems(Startup, setup)
     .run();
 }
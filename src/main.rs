@@ -1,4 +1,4 @@
-use bevy::{prelude::*, window::WindowResolution};
+use bevy::{asset::AssetMetaCheck, prelude::*, window::WindowResolution};
 use game_lib::GamePlugin;
 use space_editor::SpaceEditorPlugin;
 use space_editor_ui::{game_mode_changed, settings::GameModeSettings, simple_editor_setup};
@@ -11,17 +11,27 @@ fn main() {
     };
 
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            resizable: true,
-            focused: true,
-            title: "Space Editor".into(),
-            resolution: WindowResolution::new(1600., 900.),
-            visible: true,
-            ..default()
-        }),
-        ..default()
-    }))
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    resizable: true,
+                    focused: true,
+                    title: "Space Editor".into(),
+                    resolution: WindowResolution::new(1600., 900.),
+                    visible: true,
+                    ..default()
+                }),
+                // Editor asks to save unsaved changes before closing the window
+                close_when_requested: false,
+                ..default()
+            })
+            // Import settings are stored in `.meta` files next to the assets
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Always,
+                ..default()
+            }),
+    )
     .add_plugins((SpaceEditorPlugin, GamePlugin))
     .add_systems(Startup, simple_editor_setup)
     .add_systems(